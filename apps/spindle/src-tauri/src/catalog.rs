@@ -0,0 +1,980 @@
+//! Service catalog export/import as a portable, versioned JSON bundle.
+//!
+//! A bundle holds every service (config, args, dependencies by name/version) and the group
+//! aliases (by member services, since group ids are only meaningful on one machine).
+//! Imports only touch the database; callers reload [spindle_core::service::ServiceManager]
+//! and update group membership afterwards, which maps imported aliases onto real groups.
+//! Foreign formats (docker-compose, Procfile, systemd units) are converted by `spindle_core`
+//! and imported or exported here with the same semantics.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::DerefMut,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use spindle_core::hooks::ServiceHooks;
+use spindle_core::limits::ResourceLimits;
+use spindle_core::schedule::OverlapPolicy;
+use spindle_core::service::{
+    PortSpec, ProfileOverlay, ServiceConfig, ServiceManagerOptions, split_instance_name,
+    validate_configs_with_options,
+};
+use spindle_core::watch::WatchConfig;
+use sqlx::{Connection, Row, Sqlite, Transaction};
+use tauri::Manager;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Current bundle format version; bundles with a newer version are rejected.
+///
+/// Version 2 added `${VAR}` interpolation; values of version 1 bundles are imported literally.
+pub const CATALOG_FORMAT_VERSION: u32 = 2;
+
+/// A portable snapshot of the whole service catalog.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogBundle {
+    /// Bundle format version, see [CATALOG_FORMAT_VERSION].
+    pub format_version: u32,
+    /// All services in the catalog.
+    pub services: Vec<CatalogService>,
+    /// Group aliases, identified by their member services.
+    #[serde(default)]
+    pub group_aliases: Vec<CatalogGroupAlias>,
+}
+
+/// One service in a [CatalogBundle].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogService {
+    /// Service name.
+    pub name: String,
+    /// Service version.
+    pub version: String,
+    /// Executable program path.
+    pub program: String,
+    /// Optional description.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Optional workspace directory.
+    #[serde(default)]
+    pub workspace: Option<String>,
+    /// Startup arguments.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Dependencies as (name, version); `worker@emails` refers to one instance of the template `worker@`.
+    #[serde(default)]
+    pub dependencies: Vec<(String, String)>,
+    /// Instance names if the service is a template (name ending in `@`).
+    #[serde(default)]
+    pub instances: Vec<String>,
+    /// Number of replicas, if replicated.
+    #[serde(default)]
+    pub replicas: Option<u32>,
+    /// Replica quorum, if set.
+    #[serde(default)]
+    pub quorum: Option<u32>,
+    /// Named ports, each a port number or `"auto"`.
+    #[serde(default)]
+    pub ports: BTreeMap<String, PortSpec>,
+    /// Profile overlays by profile name; their dependencies are given like `dependencies`.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileOverlay>,
+    /// User (name or uid) to run as.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Group (name or gid) to run as.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Octal umask.
+    #[serde(default)]
+    pub umask: Option<String>,
+    /// Whether the service keeps running when Spindle exits.
+    #[serde(default)]
+    pub detached: bool,
+    /// Cron expression the service runs on, if scheduled.
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// What to do when a scheduled run is due while the previous one is still going.
+    #[serde(default)]
+    pub overlap: OverlapPolicy,
+    /// Files whose changes restart the service.
+    #[serde(default)]
+    pub watch: Option<WatchConfig>,
+    /// Rlimits and cgroup limits.
+    #[serde(default)]
+    pub limits: ResourceLimits,
+    /// Lifecycle hooks.
+    #[serde(default)]
+    pub hooks: ServiceHooks,
+}
+
+/// A group alias in a [CatalogBundle].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogGroupAlias {
+    /// Alias string.
+    pub alias: String,
+    /// Member services of the aliased group as (name, version).
+    pub services: Vec<(String, String)>,
+}
+
+/// How an import treats the services already in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogImportMode {
+    /// Keep existing services; bundle services with an existing (name, version) are skipped.
+    Merge,
+    /// Delete all existing services and aliases, then import the bundle.
+    Replace,
+}
+
+/// A bundle entry that was not imported, with the reason.
+#[derive(Debug, Serialize)]
+pub struct CatalogConflict {
+    /// Service name, or the alias for alias conflicts.
+    pub name: String,
+    /// Service version; `None` for alias conflicts.
+    pub version: Option<String>,
+    /// Why the entry was skipped.
+    pub reason: String,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Default, Serialize)]
+pub struct CatalogImportReport {
+    /// Imported services as (name, version).
+    pub imported: Vec<(String, String)>,
+    /// Skipped services.
+    pub conflicts: Vec<CatalogConflict>,
+    /// Skipped group aliases.
+    pub alias_conflicts: Vec<CatalogConflict>,
+    /// Non-fatal notes from converting foreign formats (e.g. unsupported compose keys).
+    pub warnings: Vec<String>,
+}
+
+/// Builds a [CatalogBundle] from the current database contents.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+///
+/// # Returns
+///
+/// `Ok(bundle)` on success, or an error.
+async fn build_catalog_bundle(app: &tauri::AppHandle) -> anyhow::Result<CatalogBundle> {
+    let stored_configs = crate::service::query_all_stored_service_config(app).await;
+    let service_id_key_map: HashMap<u32, (String, String)> = stored_configs
+        .iter()
+        .map(|config| {
+            (
+                config.service_id,
+                (config.name.clone(), config.version.clone()),
+            )
+        })
+        .collect();
+    let services = stored_configs
+        .into_iter()
+        .map(|config| CatalogService {
+            dependencies: config
+                .dependency_ids
+                .iter()
+                .filter_map(|id| service_id_key_map.get(id).cloned())
+                .chain(
+                    config
+                        .instance_dependencies
+                        .iter()
+                        .filter_map(|(id, instance)| {
+                            let (name, version) = service_id_key_map.get(id)?;
+                            Some((format!("{name}{instance}"), version.clone()))
+                        }),
+                )
+                .collect(),
+            instances: config.instances,
+            replicas: config.replicas,
+            quorum: config.quorum,
+            ports: config.ports,
+            user: config.user,
+            group: config.group,
+            umask: config.umask,
+            detached: config.detached,
+            schedule: config.schedule,
+            overlap: config.overlap,
+            watch: config.watch,
+            limits: config.limits,
+            hooks: config.hooks,
+            profiles: config
+                .profiles
+                .iter()
+                .map(|(profile, stored)| {
+                    (
+                        profile.clone(),
+                        crate::service::profile_overlay(stored, &service_id_key_map),
+                    )
+                })
+                .collect(),
+            name: config.name,
+            version: config.version,
+            program: config.program,
+            description: config.description,
+            workspace: config.workspace,
+            args: config.args,
+            env: config.env,
+        })
+        .collect();
+
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    let rows = sqlx::query(
+        "SELECT DISTINCT a.alias, s.name, s.version FROM service_group_alias a
+        JOIN service_group_membership m ON m.group_id = a.group_id
+        JOIN service s ON s.id = m.service_id
+        ORDER BY a.alias, s.name, s.version",
+    )
+    .fetch_all(db_conn.deref_mut())
+    .await?;
+    let mut group_aliases: Vec<CatalogGroupAlias> = Vec::new();
+    for row in rows {
+        let alias: String = row.get("alias");
+        let key = (row.get("name"), row.get("version"));
+        match group_aliases.last_mut() {
+            Some(last) if last.alias == alias => last.services.push(key),
+            _ => group_aliases.push(CatalogGroupAlias {
+                alias,
+                services: vec![key],
+            }),
+        }
+    }
+    Ok(CatalogBundle {
+        format_version: CATALOG_FORMAT_VERSION,
+        services,
+        group_aliases,
+    })
+}
+
+/// Writes the whole catalog to `path` as pretty-printed JSON.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `path` - Destination file path; overwritten if it exists.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error.
+async fn export_catalog(app: &tauri::AppHandle, path: &Path) -> anyhow::Result<()> {
+    let bundle = build_catalog_bundle(app).await?;
+    let content = serde_json::to_string_pretty(&bundle)?;
+    std::fs::write(path, content)?;
+    info!("path" = ?path, "services" = bundle.services.len(), "Service catalog exported");
+    Ok(())
+}
+
+/// Reads and parses a [CatalogBundle] from `path`, rejecting unsupported format versions and
+/// escaping the values of version 1 bundles.
+fn read_catalog_bundle(path: &Path) -> anyhow::Result<CatalogBundle> {
+    let content = std::fs::read_to_string(path)?;
+    let mut bundle: CatalogBundle = serde_json::from_str(&content)?;
+    if bundle.format_version > CATALOG_FORMAT_VERSION {
+        anyhow::bail!(
+            "Unsupported catalog format version {} (supported up to {})",
+            bundle.format_version,
+            CATALOG_FORMAT_VERSION
+        );
+    }
+    if bundle.format_version < 2 {
+        bundle.services.iter_mut().for_each(escape_uninterpolated);
+    }
+    Ok(bundle)
+}
+
+/// Escapes `$` as `$$` in a service of a bundle written before `${VAR}` interpolation, the
+/// same way migration 4 does for stored services, so its values stay literal.
+fn escape_uninterpolated(service: &mut CatalogService) {
+    let escape = |value: &mut String| *value = value.replace('$', "$$");
+    escape(&mut service.program);
+    if let Some(workspace) = service.workspace.as_mut() {
+        escape(workspace);
+    }
+    service.args.iter_mut().for_each(escape);
+    service.env.values_mut().for_each(escape);
+}
+
+/// Resolves a bundle dependency to a stored service id, and the instance for template instances.
+fn resolve_catalog_dependency(
+    key_id_map: &HashMap<(String, String), u32>,
+    dep: &(String, String),
+) -> Option<(u32, Option<String>)> {
+    if let Some(dep_id) = key_id_map.get(dep) {
+        return Some((*dep_id, None));
+    }
+    let (template, instance) = split_instance_name(&dep.0)?;
+    let dep_id = key_id_map.get(&(template.to_string(), dep.1.clone()))?;
+    Some((*dep_id, Some(instance.to_string())))
+}
+
+/// Resolves bundle dependencies to (dependency ids, instance dependencies); unresolved ones are dropped.
+fn resolve_catalog_dependencies(
+    key_id_map: &HashMap<(String, String), u32>,
+    dependencies: &[(String, String)],
+) -> (Vec<u32>, Vec<(u32, String)>) {
+    let mut dependency_ids: Vec<u32> = Vec::new();
+    let mut instance_dependencies: Vec<(u32, String)> = Vec::new();
+    for dep in dependencies {
+        match resolve_catalog_dependency(key_id_map, dep) {
+            Some((dep_id, None)) => dependency_ids.push(dep_id),
+            Some((dep_id, Some(instance))) => instance_dependencies.push((dep_id, instance)),
+            None => (),
+        }
+    }
+    (dependency_ids, instance_dependencies)
+}
+
+/// Within a transaction, imports the bundle services that pass validation, in dependency order.
+///
+/// # Arguments
+///
+/// * `tx` - Active SQLite transaction.
+/// * `services` - Bundle services to import.
+/// * `report` - Report collecting imported services and conflicts.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error on DB failure (the transaction is then dropped).
+async fn import_services(
+    tx: &mut Transaction<'_, Sqlite>,
+    services: Vec<CatalogService>,
+    report: &mut CatalogImportReport,
+) -> anyhow::Result<()> {
+    let mut key_id_map: HashMap<(String, String), u32> =
+        sqlx::query("SELECT id, name, version FROM service")
+            .fetch_all(tx.deref_mut())
+            .await?
+            .into_iter()
+            .map(|row| ((row.get("name"), row.get("version")), row.get("id")))
+            .collect();
+
+    let mut seen_keys: HashSet<(String, String)> = HashSet::new();
+    let mut candidates: Vec<CatalogService> = Vec::with_capacity(services.len());
+    for service in services {
+        let key = (service.name.clone(), service.version.clone());
+        let reason = if key_id_map.contains_key(&key) {
+            "Service already exists"
+        } else if !seen_keys.insert(key) {
+            "Service is duplicated in the bundle"
+        } else {
+            candidates.push(service);
+            continue;
+        };
+        report.conflicts.push(CatalogConflict {
+            name: service.name,
+            version: Some(service.version),
+            reason: reason.to_string(),
+        });
+    }
+
+    // Existing services cannot depend on new ones, so they only need to be present, without edges.
+    // Existing templates keep their instances so new services can depend on them.
+    let mut existing_instances: HashMap<u32, Vec<String>> = HashMap::new();
+    for row in sqlx::query("SELECT service_id, value FROM service_instance ORDER BY instance_idx")
+        .fetch_all(tx.deref_mut())
+        .await?
+    {
+        existing_instances
+            .entry(row.get("service_id"))
+            .or_default()
+            .push(row.get("value"));
+    }
+    let mut service_configs: Vec<ServiceConfig> = key_id_map
+        .iter()
+        .map(|((name, version), service_id)| ServiceConfig {
+            name: name.clone(),
+            version: version.clone(),
+            instances: existing_instances
+                .get(service_id)
+                .cloned()
+                .unwrap_or_default(),
+            ..Default::default()
+        })
+        .collect();
+    service_configs.extend(candidates.iter().map(|service| ServiceConfig {
+        name: service.name.clone(),
+        version: service.version.clone(),
+        program: service.program.clone().into(),
+        args: service.args.clone(),
+        env: service.env.clone(),
+        dependencies: service.dependencies.clone(),
+        workspace: service.workspace.as_ref().map(|workspace| workspace.into()),
+        instances: service.instances.clone(),
+        replicas: service.replicas,
+        quorum: service.quorum,
+        ports: service.ports.clone(),
+        profiles: service.profiles.clone(),
+        user: service.user.clone(),
+        group: service.group.clone(),
+        umask: service.umask.clone(),
+        detached: service.detached,
+        schedule: service.schedule.clone(),
+        overlap: service.overlap,
+        watch: service.watch.clone(),
+        limits: service.limits.clone(),
+        hooks: service.hooks.clone(),
+    }));
+    let options = ServiceManagerOptions {
+        variables: crate::variable::load_global_variables(tx.deref_mut()).await?,
+        profile: None,
+        state_dir: None,
+        service_logs: None,
+        run_records: None,
+    };
+    let validation_report = validate_configs_with_options(&service_configs, &options);
+    let mut pending: Vec<CatalogService> = Vec::with_capacity(candidates.len());
+    for service in candidates {
+        let reasons: Vec<&str> = validation_report
+            .issues_for(&service.name, &service.version)
+            .map(|issue| issue.reason.as_str())
+            .collect();
+        if reasons.is_empty() {
+            pending.push(service);
+        } else {
+            report.conflicts.push(CatalogConflict {
+                reason: reasons.join("; "),
+                name: service.name,
+                version: Some(service.version),
+            });
+        }
+    }
+
+    // Insert services whose dependencies all exist, until no more progress can be made.
+    while !pending.is_empty() {
+        let (ready, not_ready): (Vec<_>, Vec<_>) = pending.into_iter().partition(|service| {
+            service
+                .dependencies
+                .iter()
+                .chain(
+                    service
+                        .profiles
+                        .values()
+                        .flat_map(|overlay| overlay.dependencies.iter().flatten()),
+                )
+                .all(|dep| resolve_catalog_dependency(&key_id_map, dep).is_some())
+        });
+        if ready.is_empty() {
+            for service in not_ready {
+                report.conflicts.push(CatalogConflict {
+                    name: service.name,
+                    version: Some(service.version),
+                    reason: "Dependencies could not be resolved".to_string(),
+                });
+            }
+            break;
+        }
+        for service in ready {
+            let (dependency_ids, instance_dependencies) =
+                resolve_catalog_dependencies(&key_id_map, &service.dependencies);
+            let profiles: BTreeMap<String, crate::service::StoredProfile> = service
+                .profiles
+                .iter()
+                .map(|(profile, overlay)| {
+                    let (dependency_ids, instance_dependencies) = match &overlay.dependencies {
+                        Some(dependencies) => {
+                            let (dependency_ids, instance_dependencies) =
+                                resolve_catalog_dependencies(&key_id_map, dependencies);
+                            (Some(dependency_ids), instance_dependencies)
+                        }
+                        None => (None, Vec::new()),
+                    };
+                    let stored = crate::service::StoredProfile {
+                        args: overlay.args.clone(),
+                        env: overlay.env.clone(),
+                        workspace: overlay
+                            .workspace
+                            .as_ref()
+                            .map(|workspace| workspace.to_string_lossy().into_owned()),
+                        dependency_ids,
+                        instance_dependencies,
+                    };
+                    (profile.clone(), stored)
+                })
+                .collect();
+            let service_id =
+                crate::service::insert_service_row(tx, &service.name, &service.version).await?;
+            let detail = crate::service::ServiceDetail {
+                program: &service.program,
+                description: service.description.as_deref(),
+                workspace: service.workspace.as_deref(),
+                args: &service.args,
+                env: &service.env,
+                dependency_ids: &dependency_ids,
+                instances: &service.instances,
+                instance_dependencies: &instance_dependencies,
+                replicas: service.replicas,
+                quorum: service.quorum,
+                ports: &service.ports,
+                profiles: &profiles,
+                user: service.user.as_deref(),
+                group: service.group.as_deref(),
+                umask: service.umask.as_deref(),
+                detached: service.detached,
+                schedule: service.schedule.as_deref(),
+                overlap: service.overlap,
+                watch: service.watch.as_ref(),
+                limits: &service.limits,
+                hooks: &service.hooks,
+            };
+            crate::service::write_service_detail_rows(tx, service_id, &detail).await?;
+            let key = (service.name, service.version);
+            key_id_map.insert(key.clone(), service_id);
+            report.imported.push(key);
+        }
+        pending = not_ready;
+    }
+    Ok(())
+}
+
+/// Within a transaction, imports group aliases for the imported services.
+///
+/// Each alias gets a fresh placeholder group id and the imported member services are assigned to it,
+/// so the next group membership update migrates the alias onto the group those services end up in.
+///
+/// # Arguments
+///
+/// * `tx` - Active SQLite transaction.
+/// * `group_aliases` - Bundle group aliases.
+/// * `report` - Report collecting alias conflicts; `report.imported` must already be filled.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error on DB failure.
+async fn import_group_aliases(
+    tx: &mut Transaction<'_, Sqlite>,
+    group_aliases: Vec<CatalogGroupAlias>,
+    report: &mut CatalogImportReport,
+) -> anyhow::Result<()> {
+    let mut next_group_id: i64 = sqlx::query(
+        "SELECT MAX(
+            COALESCE((SELECT MAX(group_id) FROM service_group_membership), -1),
+            COALESCE((SELECT MAX(group_id) FROM service_group_alias), -1)
+        ) + 1 AS next_group_id",
+    )
+    .fetch_one(tx.deref_mut())
+    .await?
+    .get("next_group_id");
+    let mut assigned: HashSet<(String, String)> = HashSet::new();
+    for group_alias in group_aliases {
+        let alias_exists = sqlx::query("SELECT group_id FROM service_group_alias WHERE alias = $1")
+            .bind(&group_alias.alias)
+            .fetch_optional(tx.deref_mut())
+            .await?
+            .is_some();
+        if alias_exists {
+            report.alias_conflicts.push(CatalogConflict {
+                name: group_alias.alias,
+                version: None,
+                reason: "Alias already exists".to_string(),
+            });
+            continue;
+        }
+        let members: Vec<(String, String)> = group_alias
+            .services
+            .into_iter()
+            .filter(|key| report.imported.contains(key) && !assigned.contains(key))
+            .collect();
+        if members.is_empty() {
+            report.alias_conflicts.push(CatalogConflict {
+                name: group_alias.alias,
+                version: None,
+                reason: "None of the aliased services were imported".to_string(),
+            });
+            continue;
+        }
+        for (name, version) in members {
+            sqlx::query(
+                "INSERT INTO service_group_membership (service_id, group_id)
+                SELECT id, $1 FROM service WHERE name = $2 AND version = $3",
+            )
+            .bind(next_group_id)
+            .bind(&name)
+            .bind(&version)
+            .execute(tx.deref_mut())
+            .await?;
+            assigned.insert((name, version));
+        }
+        sqlx::query("INSERT INTO service_group_alias (group_id, alias) VALUES ($1, $2)")
+            .bind(next_group_id)
+            .bind(&group_alias.alias)
+            .execute(tx.deref_mut())
+            .await?;
+        next_group_id += 1;
+    }
+    Ok(())
+}
+
+/// Imports a catalog bundle from `path` in one transaction.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `path` - Bundle file path.
+/// * `mode` - [CatalogImportMode::Merge] or [CatalogImportMode::Replace].
+///
+/// # Returns
+///
+/// `Ok(report)` listing imported and skipped entries, or an error if the bundle cannot be read
+/// or on DB failure (nothing is imported then).
+async fn import_catalog(
+    app: &tauri::AppHandle,
+    path: &Path,
+    mode: CatalogImportMode,
+) -> anyhow::Result<CatalogImportReport> {
+    let bundle = read_catalog_bundle(path)?;
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    let mut tx = db_conn.begin().await?;
+    if mode == CatalogImportMode::Replace {
+        // Dependency rows go first: `dependency_id` is ON DELETE RESTRICT.
+        for table in ["service_dependency", "service", "service_group_alias"] {
+            sqlx::query(&format!("DELETE FROM {table}"))
+                .execute(tx.deref_mut())
+                .await?;
+        }
+    }
+    let mut report = CatalogImportReport::default();
+    import_services(&mut tx, bundle.services, &mut report).await?;
+    import_group_aliases(&mut tx, bundle.group_aliases, &mut report).await?;
+    tx.commit().await?;
+    if !report.conflicts.is_empty() || !report.alias_conflicts.is_empty() {
+        warn!("path" = ?path, "conflicts" = report.conflicts.len(), "alias_conflicts" = report.alias_conflicts.len(), "Service catalog imported with conflicts");
+    }
+    info!("path" = ?path, "imported" = report.imported.len(), "Service catalog imported");
+    Ok(report)
+}
+
+/// Converts a [ServiceConfig] produced by a foreign-format importer into a [CatalogService].
+fn catalog_service_from_config(config: ServiceConfig) -> CatalogService {
+    CatalogService {
+        name: config.name,
+        version: config.version,
+        program: config.program.to_string_lossy().into_owned(),
+        description: None,
+        workspace: config
+            .workspace
+            .map(|workspace| workspace.to_string_lossy().into_owned()),
+        args: config.args,
+        env: config.env,
+        dependencies: config.dependencies,
+        instances: config.instances,
+        replicas: config.replicas,
+        quorum: config.quorum,
+        ports: config.ports,
+        profiles: config.profiles,
+        user: config.user,
+        group: config.group,
+        umask: config.umask,
+        detached: config.detached,
+        schedule: config.schedule,
+        overlap: config.overlap,
+        watch: config.watch,
+        limits: config.limits,
+        hooks: config.hooks,
+    }
+}
+
+/// Imports services converted from a foreign format in one transaction, with merge semantics.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `path` - Source file path, for logging.
+/// * `configs` - Converted services.
+/// * `warnings` - Conversion warnings, copied into the report.
+///
+/// # Returns
+///
+/// `Ok(report)` listing imported and skipped services, or an error on DB failure.
+async fn import_foreign_services(
+    app: &tauri::AppHandle,
+    path: &Path,
+    configs: Vec<ServiceConfig>,
+    warnings: Vec<String>,
+) -> anyhow::Result<CatalogImportReport> {
+    let services = configs
+        .into_iter()
+        .map(catalog_service_from_config)
+        .collect();
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    let mut tx = db_conn.begin().await?;
+    let mut report = CatalogImportReport {
+        warnings,
+        ..Default::default()
+    };
+    import_services(&mut tx, services, &mut report).await?;
+    tx.commit().await?;
+    if !report.conflicts.is_empty() {
+        warn!("path" = ?path, "conflicts" = report.conflicts.len(), "Services imported with conflicts");
+    }
+    info!("path" = ?path, "imported" = report.imported.len(), "warnings" = report.warnings.len(), "Services imported");
+    Ok(report)
+}
+
+/// Imports the services of a `docker-compose.yml` in one transaction, with merge semantics.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `path` - Compose file path.
+/// * `version` - Version assigned to every imported service.
+///
+/// # Returns
+///
+/// `Ok(report)` listing imported and skipped services plus compose warnings, or an error if the
+/// file cannot be parsed or on DB failure (nothing is imported then).
+async fn import_compose(
+    app: &tauri::AppHandle,
+    path: &Path,
+    version: &str,
+) -> anyhow::Result<CatalogImportReport> {
+    let compose = spindle_core::compose::load_compose(path, version)?;
+    import_foreign_services(app, path, compose.services, compose.warnings).await
+}
+
+/// Imports the processes of a `Procfile` in one transaction, with merge semantics.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `path` - Procfile path; its directory becomes the workspace and its `.env`, if any, the environment.
+/// * `version` - Version assigned to every imported service.
+///
+/// # Returns
+///
+/// `Ok(report)` listing imported and skipped services, or an error if the Procfile is malformed
+/// or on DB failure (nothing is imported then).
+async fn import_procfile(
+    app: &tauri::AppHandle,
+    path: &Path,
+    version: &str,
+) -> anyhow::Result<CatalogImportReport> {
+    let configs = spindle_core::procfile::load_procfile(path, version)?;
+    import_foreign_services(app, path, configs, Vec::new()).await
+}
+
+/// Writes the services of a group to `path` as a Procfile.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB and service manager access.
+/// * `group_id` - Group id.
+/// * `path` - Destination file path; overwritten if it exists.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the group is invalid, a service name is not unique in the
+/// group, or on I/O failure.
+async fn export_procfile(app: &tauri::AppHandle, group_id: u32, path: &Path) -> anyhow::Result<()> {
+    let app_state = app.state::<Mutex<crate::AppState>>();
+    let service_manager = match app_state.lock().await.service_manager.as_ref() {
+        Some(sm) => sm.clone(),
+        None => anyhow::bail!("Service manager not initialized"),
+    };
+    if group_id as usize >= service_manager.group_num() {
+        anyhow::bail!("Invalid group id: {}", group_id);
+    }
+    let group_keys: HashSet<(String, String)> = service_manager
+        .group_service_keys(group_id as usize)
+        .into_iter()
+        .collect();
+    let stored_configs = crate::service::query_all_stored_service_config(app).await;
+    let mut configs: Vec<ServiceConfig> = crate::service::to_service_configs(&stored_configs)
+        .into_iter()
+        .filter(|config| group_keys.contains(&(config.name.clone(), config.version.clone())))
+        .collect();
+    configs.sort_by(|a, b| a.name.cmp(&b.name));
+    let content = spindle_core::procfile::render_procfile(&configs)?;
+    std::fs::write(path, content)?;
+    info!("path" = ?path, "group_id" = group_id, "services" = configs.len(), "Procfile exported");
+    Ok(())
+}
+
+/// Writes systemd user units for a group into `dir`: one `.service` per service and a `.target`
+/// named after the group alias (or `group-<id>` without alias).
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB and service manager access.
+/// * `group_id` - Group id.
+/// * `dir` - Output directory; created if missing.
+///
+/// # Returns
+///
+/// `Ok(paths)` of the written unit files, or an error if the group is invalid or on I/O failure.
+async fn export_systemd_units(
+    app: &tauri::AppHandle,
+    group_id: u32,
+    dir: &Path,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let app_state = app.state::<Mutex<crate::AppState>>();
+    let service_manager = match app_state.lock().await.service_manager.as_ref() {
+        Some(sm) => sm.clone(),
+        None => anyhow::bail!("Service manager not initialized"),
+    };
+    let group_name = crate::service::query_group_alias(app, group_id)
+        .await
+        .unwrap_or_else(|| format!("group-{group_id}"));
+    let paths = spindle_core::systemd::export_group_units(
+        &service_manager,
+        group_id as usize,
+        &group_name,
+        dir,
+    )?;
+    info!("dir" = ?dir, "group_id" = group_id, "units" = paths.len(), "systemd units exported");
+    Ok(paths)
+}
+
+/// Tauri commands exposed to the frontend: catalog export and import.
+pub mod tauri_cmd {
+    use std::path::PathBuf;
+
+    /// Exports all services and group aliases to a JSON bundle.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `path` - Destination file path.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(message)` on failure.
+    #[tauri::command]
+    pub async fn export_catalog(app: tauri::AppHandle, path: String) -> Result<(), String> {
+        super::export_catalog(&app, &PathBuf::from(path))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Imports a JSON bundle written by [export_catalog]. Conflicting or invalid services are
+    /// reported and skipped instead of failing the import. Reload the service manager and
+    /// update group membership afterwards to apply the changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `path` - Bundle file path.
+    /// * `mode` - `"merge"` or `"replace"`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(report)` on success, or `Err(message)` if the bundle is unreadable or on DB failure.
+    #[tauri::command]
+    pub async fn import_catalog(
+        app: tauri::AppHandle,
+        path: String,
+        mode: super::CatalogImportMode,
+    ) -> Result<super::CatalogImportReport, String> {
+        super::import_catalog(&app, &PathBuf::from(path), mode)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Imports the services of a `docker-compose.yml` as local processes. Image-only services,
+    /// unsupported keys and dropped dependencies are listed in the report's warnings.
+    /// Reload the service manager and update group membership afterwards to apply the changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `path` - Compose file path.
+    /// * `version` - Version assigned to every imported service.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(report)` on success, or `Err(message)` if the file is unreadable or on DB failure.
+    #[tauri::command]
+    pub async fn import_compose(
+        app: tauri::AppHandle,
+        path: String,
+        version: String,
+    ) -> Result<super::CatalogImportReport, String> {
+        super::import_compose(&app, &PathBuf::from(path), &version)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Imports the processes of a Procfile as services, using the Procfile's directory as
+    /// workspace and its `.env`, if present, as environment. Reload the service manager and
+    /// update group membership afterwards to apply the changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `path` - Procfile path.
+    /// * `version` - Version assigned to every imported service.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(report)` on success, or `Err(message)` if the Procfile is malformed or on DB failure.
+    #[tauri::command]
+    pub async fn import_procfile(
+        app: tauri::AppHandle,
+        path: String,
+        version: String,
+    ) -> Result<super::CatalogImportReport, String> {
+        super::import_procfile(&app, &PathBuf::from(path), &version)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Exports the services of a group as a Procfile.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `group_id` - Group id.
+    /// * `path` - Destination file path.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(message)` on failure.
+    #[tauri::command]
+    pub async fn export_procfile(
+        app: tauri::AppHandle,
+        group_id: u32,
+        path: String,
+    ) -> Result<(), String> {
+        super::export_procfile(&app, group_id, &PathBuf::from(path))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Writes systemd user units for a group into a directory. Nothing is installed or enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `group_id` - Group id.
+    /// * `dir` - Output directory.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(paths)` of the written unit files, or `Err(message)` on failure.
+    #[tauri::command]
+    pub async fn export_systemd_units(
+        app: tauri::AppHandle,
+        group_id: u32,
+        dir: String,
+    ) -> Result<Vec<String>, String> {
+        let paths = super::export_systemd_units(&app, group_id, &PathBuf::from(dir))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(paths
+            .into_iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect())
+    }
+}
@@ -57,13 +57,307 @@ CREATE TABLE IF NOT EXISTS service_group_alias (
     alias    TEXT NOT NULL UNIQUE
 );"##;
 
+const SPINDLE_MIGRATION_2: &str = r##"CREATE TABLE IF NOT EXISTS service_run (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    service_id   INTEGER NOT NULL,
+    started_at   INTEGER,
+    stopped_at   INTEGER NOT NULL,
+    exit_code    INTEGER,
+    signal       INTEGER,
+    reason       TEXT,
+    triggered_by TEXT NOT NULL,
+    CONSTRAINT fk_service_run_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_service_run_service_id_stopped_at ON service_run (service_id, stopped_at);"##;
+
+const SPINDLE_MIGRATION_3: &str = r##"CREATE TABLE IF NOT EXISTS service_env (
+    service_id INTEGER NOT NULL,
+    key        TEXT NOT NULL,
+    value      TEXT NOT NULL,
+    CONSTRAINT fk_service_env_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    PRIMARY KEY (service_id, key)
+);"##;
+
+const SPINDLE_MIGRATION_4: &str = r##"CREATE TABLE IF NOT EXISTS global_variable (
+    name  TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+-- Values saved before ${VAR} interpolation stay literal: `$` is escaped as `$$`.
+UPDATE service_config SET program = replace(program, '$', '$$'), workspace = replace(workspace, '$', '$$');
+UPDATE service_arg SET value = replace(value, '$', '$$');
+UPDATE service_env SET value = replace(value, '$', '$$');"##;
+
+const SPINDLE_MIGRATION_5: &str = r##"CREATE TABLE IF NOT EXISTS service_instance (
+    service_id   INTEGER NOT NULL,
+    instance_idx INTEGER NOT NULL,
+    value        TEXT NOT NULL,
+    CONSTRAINT fk_service_instance_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    PRIMARY KEY (service_id, instance_idx),
+    CHECK (instance_idx >= 0)
+);
+
+CREATE TABLE IF NOT EXISTS service_instance_dependency (
+    service_id    INTEGER NOT NULL,
+    dependency_id INTEGER NOT NULL,
+    instance      TEXT NOT NULL,
+    CONSTRAINT fk_service_instance_dependency_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    CONSTRAINT fk_service_instance_dependency_dependency_id
+        FOREIGN KEY (dependency_id) REFERENCES service (id) ON DELETE RESTRICT,
+    PRIMARY KEY (service_id, dependency_id, instance),
+    CHECK (service_id != dependency_id)
+);
+CREATE INDEX IF NOT EXISTS idx_service_instance_dependency_dependency_id ON service_instance_dependency (dependency_id);"##;
+
+const SPINDLE_MIGRATION_6: &str = r##"ALTER TABLE service_config ADD COLUMN replicas INTEGER CHECK (replicas >= 1);
+ALTER TABLE service_config ADD COLUMN quorum INTEGER CHECK (quorum >= 1);"##;
+
+const SPINDLE_MIGRATION_7: &str = r##"CREATE TABLE IF NOT EXISTS service_port (
+    service_id INTEGER NOT NULL,
+    name       TEXT NOT NULL,
+    port       INTEGER CHECK (port BETWEEN 1 AND 65535),
+    CONSTRAINT fk_service_port_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    PRIMARY KEY (service_id, name)
+);"##;
+
+const SPINDLE_MIGRATION_8: &str = r##"CREATE TABLE IF NOT EXISTS service_profile (
+    service_id            INTEGER NOT NULL,
+    profile               TEXT NOT NULL,
+    workspace             TEXT,
+    replaces_args         INTEGER NOT NULL DEFAULT 0,
+    replaces_dependencies INTEGER NOT NULL DEFAULT 0,
+    CONSTRAINT fk_service_profile_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    PRIMARY KEY (service_id, profile)
+);
+
+CREATE TABLE IF NOT EXISTS service_profile_arg (
+    service_id INTEGER NOT NULL,
+    profile    TEXT NOT NULL,
+    arg_idx    INTEGER NOT NULL,
+    value      TEXT NOT NULL,
+    CONSTRAINT fk_service_profile_arg_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    PRIMARY KEY (service_id, profile, arg_idx),
+    CHECK (arg_idx >= 0)
+);
+
+CREATE TABLE IF NOT EXISTS service_profile_env (
+    service_id INTEGER NOT NULL,
+    profile    TEXT NOT NULL,
+    key        TEXT NOT NULL,
+    value      TEXT NOT NULL,
+    CONSTRAINT fk_service_profile_env_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    PRIMARY KEY (service_id, profile, key)
+);
+
+CREATE TABLE IF NOT EXISTS service_profile_dependency (
+    service_id    INTEGER NOT NULL,
+    profile       TEXT NOT NULL,
+    dependency_id INTEGER NOT NULL,
+    instance      TEXT NOT NULL DEFAULT '',
+    CONSTRAINT fk_service_profile_dependency_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    CONSTRAINT fk_service_profile_dependency_dependency_id
+        FOREIGN KEY (dependency_id) REFERENCES service (id) ON DELETE RESTRICT,
+    PRIMARY KEY (service_id, profile, dependency_id, instance),
+    CHECK (service_id != dependency_id)
+);
+CREATE INDEX IF NOT EXISTS idx_service_profile_dependency_dependency_id ON service_profile_dependency (dependency_id);"##;
+
+const SPINDLE_MIGRATION_9: &str = r##"ALTER TABLE service_config ADD COLUMN user TEXT;
+ALTER TABLE service_config ADD COLUMN group_name TEXT;
+ALTER TABLE service_config ADD COLUMN umask TEXT;"##;
+
+const SPINDLE_MIGRATION_10: &str = r##"CREATE TABLE IF NOT EXISTS service_limit (
+    service_id    INTEGER PRIMARY KEY,
+    open_files    INTEGER CHECK (open_files >= 0),
+    core_size     INTEGER CHECK (core_size >= 0),
+    address_space INTEGER CHECK (address_space >= 0),
+    cpu_seconds   INTEGER CHECK (cpu_seconds >= 0),
+    memory_max    INTEGER CHECK (memory_max >= 0),
+    cpu_max       REAL CHECK (cpu_max > 0),
+    pids_max      INTEGER CHECK (pids_max > 0),
+    CONSTRAINT fk_service_limit_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE
+);"##;
+
+const SPINDLE_MIGRATION_11: &str =
+    r##"ALTER TABLE service_config ADD COLUMN detached INTEGER NOT NULL DEFAULT 0;"##;
+
+const SPINDLE_MIGRATION_12: &str = r##"CREATE TABLE IF NOT EXISTS service_hook (
+    service_id   INTEGER NOT NULL,
+    stage        TEXT NOT NULL CHECK (stage IN ('pre_start', 'post_start', 'pre_stop', 'post_stop')),
+    command      TEXT NOT NULL,
+    timeout_secs INTEGER CHECK (timeout_secs > 0),
+    CONSTRAINT fk_service_hook_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    PRIMARY KEY (service_id, stage)
+);"##;
+
+const SPINDLE_MIGRATION_13: &str = r##"ALTER TABLE service_config ADD COLUMN schedule TEXT;
+ALTER TABLE service_config ADD COLUMN schedule_overlap TEXT NOT NULL DEFAULT 'skip';"##;
+
+const SPINDLE_MIGRATION_14: &str = r##"CREATE TABLE IF NOT EXISTS service_watch (
+    service_id         INTEGER PRIMARY KEY,
+    debounce_ms        INTEGER CHECK (debounce_ms > 0),
+    restart_dependents INTEGER NOT NULL DEFAULT 0,
+    CONSTRAINT fk_service_watch_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS service_watch_pattern (
+    service_id  INTEGER NOT NULL,
+    kind        TEXT NOT NULL CHECK (kind IN ('path', 'ignore')),
+    pattern_idx INTEGER NOT NULL,
+    pattern     TEXT NOT NULL,
+    CONSTRAINT fk_service_watch_pattern_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    PRIMARY KEY (service_id, kind, pattern_idx)
+);"##;
+
+const SPINDLE_MIGRATION_15: &str = r##"CREATE TABLE IF NOT EXISTS log_entry (
+    id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,
+    stream    TEXT NOT NULL CHECK (stream IN ('stdout', 'stderr', 'spindle')),
+    name      TEXT,
+    version   TEXT,
+    level     TEXT CHECK (level IN ('ERROR', 'WARN', 'INFO', 'DEBUG', 'TRACE')),
+    target    TEXT,
+    message   TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_log_entry_service ON log_entry (name, version, timestamp);
+CREATE INDEX IF NOT EXISTS idx_log_entry_timestamp ON log_entry (timestamp);
+CREATE VIRTUAL TABLE IF NOT EXISTS log_entry_fts USING fts5 (
+    message,
+    content = 'log_entry',
+    content_rowid = 'id'
+);
+CREATE TRIGGER IF NOT EXISTS log_entry_fts_insert AFTER INSERT ON log_entry BEGIN
+    INSERT INTO log_entry_fts (rowid, message) VALUES (new.id, new.message);
+END;
+CREATE TRIGGER IF NOT EXISTS log_entry_fts_delete AFTER DELETE ON log_entry BEGIN
+    INSERT INTO log_entry_fts (log_entry_fts, rowid, message) VALUES ('delete', old.id, old.message);
+END;"##;
+
+const SPINDLE_MIGRATION_16: &str = r##"CREATE TABLE service_group_membership_new (
+    service_id INTEGER NOT NULL,
+    instance   TEXT NOT NULL DEFAULT '',
+    group_id   INTEGER NOT NULL,
+    CONSTRAINT fk_service_group_membership_service_id
+        FOREIGN KEY (service_id) REFERENCES service (id) ON DELETE CASCADE,
+    PRIMARY KEY (service_id, instance)
+);
+INSERT INTO service_group_membership_new (service_id, group_id)
+    SELECT service_id, group_id FROM service_group_membership;
+DROP TABLE service_group_membership;
+ALTER TABLE service_group_membership_new RENAME TO service_group_membership;
+CREATE INDEX IF NOT EXISTS idx_service_group_membership_group_id ON service_group_membership (group_id);"##;
+
 pub fn spindle_migrations() -> Vec<Migration> {
-    let ret = vec![Migration {
-        version: 1,
-        description: "initial service table",
-        sql: SPINDLE_MIGRATION_1,
-        kind: MigrationKind::Up,
-    }];
+    let ret = vec![
+        Migration {
+            version: 1,
+            description: "initial service table",
+            sql: SPINDLE_MIGRATION_1,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 2,
+            description: "service run history",
+            sql: SPINDLE_MIGRATION_2,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 3,
+            description: "service environment variables",
+            sql: SPINDLE_MIGRATION_3,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 4,
+            description: "global variables",
+            sql: SPINDLE_MIGRATION_4,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 5,
+            description: "service templates and instances",
+            sql: SPINDLE_MIGRATION_5,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "service replicas",
+            sql: SPINDLE_MIGRATION_6,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "service ports",
+            sql: SPINDLE_MIGRATION_7,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "service profiles",
+            sql: SPINDLE_MIGRATION_8,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "service user, group and umask",
+            sql: SPINDLE_MIGRATION_9,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "service resource limits",
+            sql: SPINDLE_MIGRATION_10,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "detached services",
+            sql: SPINDLE_MIGRATION_11,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "service lifecycle hooks",
+            sql: SPINDLE_MIGRATION_12,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "scheduled services",
+            sql: SPINDLE_MIGRATION_13,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 14,
+            description: "watched service files",
+            sql: SPINDLE_MIGRATION_14,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 15,
+            description: "searchable log store",
+            sql: SPINDLE_MIGRATION_15,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 16,
+            description: "group membership per template instance",
+            sql: SPINDLE_MIGRATION_16,
+            kind: MigrationKind::Up,
+        },
+    ];
     ret
 }
 
@@ -1,16 +1,28 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use spindle_core::service::ServiceManager;
 use tauri::Manager;
 use tokio::sync::Mutex;
 
+mod catalog;
 mod db;
+mod log_store;
 mod logger;
+mod profile;
+mod run_history;
 mod service;
+mod variable;
+
+/// Time allowed for stopping all services when the app exits.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 struct AppState {
     service_manager: Option<Arc<ServiceManager>>, // lazy init
-    logger_broadcast_receiver: Option<tokio::sync::broadcast::Receiver<String>>,
+    log_broadcast: Option<logger::LogBroadcast>,
+    /// Log subscriptions, by subscription id.
+    log_subscriptions: HashMap<u64, logger::LogSubscription>,
+    next_log_subscription_id: u64,
+    log_store_sender: Option<tokio::sync::mpsc::Sender<log_store::LogRecord>>,
     _logger_guard: Option<logger::WorkerGuard>,
 }
 
@@ -18,7 +30,10 @@ impl Default for AppState {
     fn default() -> Self {
         Self {
             service_manager: None,
-            logger_broadcast_receiver: None,
+            log_broadcast: None,
+            log_subscriptions: HashMap::new(),
+            next_log_subscription_id: 0,
+            log_store_sender: None,
             _logger_guard: None,
         }
     }
@@ -40,20 +55,28 @@ pub fn run() {
                 .inspect_err(|e| eprintln!("logger init failed: {:?}", e))
                 .unwrap_or_else(|_| logger::LoggerInitResult {
                     worker_guard: None,
-                    broadcast_receiver: None,
+                    broadcast: None,
+                    log_store_sender: None,
+                    log_store_receiver: None,
                 });
+            if let Some(receiver) = logger_result.log_store_receiver {
+                let retention = logger::log_store_retention(app.handle());
+                log_store::spawn_log_writer(app.handle().clone(), receiver, retention);
+            }
 
             // Store logger guard and broadcast receiver in AppState
             let app_state = app.state::<Mutex<AppState>>();
             let mut state = app_state.blocking_lock();
             state._logger_guard = logger_result.worker_guard;
-            state.logger_broadcast_receiver = logger_result.broadcast_receiver;
+            state.log_broadcast = logger_result.broadcast;
+            state.log_store_sender = logger_result.log_store_sender;
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // service
             service::tauri_cmd::add_service,
+            service::tauri_cmd::update_service,
             service::tauri_cmd::remove_service,
             service::tauri_cmd::reload_service_manager,
             service::tauri_cmd::update_service_group_membership,
@@ -62,13 +85,55 @@ pub fn run() {
             service::tauri_cmd::remove_group_alias,
             service::tauri_cmd::launch_group,
             service::tauri_cmd::stop_service,
+            service::tauri_cmd::scale_service,
+            service::tauri_cmd::set_group_profile,
+            service::tauri_cmd::profiles,
             service::tauri_cmd::service_state,
+            service::tauri_cmd::service_runtime_info,
+            service::tauri_cmd::next_scheduled_run,
             service::tauri_cmd::stop_group,
             service::tauri_cmd::aliased_group_service,
             service::tauri_cmd::unaliased_group_service,
+            // catalog
+            catalog::tauri_cmd::export_catalog,
+            catalog::tauri_cmd::import_catalog,
+            catalog::tauri_cmd::import_compose,
+            catalog::tauri_cmd::import_procfile,
+            catalog::tauri_cmd::export_procfile,
+            catalog::tauri_cmd::export_systemd_units,
+            variable::tauri_cmd::global_variables,
+            variable::tauri_cmd::set_global_variable,
+            variable::tauri_cmd::remove_global_variable,
+            profile::tauri_cmd::active_profile,
+            profile::tauri_cmd::set_active_profile,
+            // run history
+            run_history::tauri_cmd::service_run_history,
+            run_history::tauri_cmd::group_run_history,
             // logger
             logger::tauri_cmd::subscribe_log,
+            logger::tauri_cmd::unsubscribe_log,
+            logger::tauri_cmd::unsubscribe_logs,
+            logger::tauri_cmd::read_service_log,
+            // log store
+            log_store::tauri_cmd::query_logs,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                tauri::async_runtime::block_on(shutdown_service_manager(app));
+            }
+        });
+}
+
+/// Takes the ServiceManager out of the app state and stops all its services in dependency
+/// order, see [ServiceManager::shutdown]. Used on app exit and before a reload.
+pub(crate) async fn shutdown_service_manager(app: &tauri::AppHandle) {
+    let app_state = app.state::<Mutex<AppState>>();
+    let service_manager = app_state.lock().await.service_manager.take();
+    if let Some(service_manager) = service_manager
+        && let Err(e) = service_manager.shutdown(SHUTDOWN_TIMEOUT).await
+    {
+        tracing::warn!("error" = %e, "Services not stopped cleanly");
+    }
 }
@@ -0,0 +1,484 @@
+//! Searchable store of Spindle's own `tracing` events and captured service output.
+//!
+//! Both are queued as [LogRecord]s and written in batches by one task to the `log_entry` table of
+//! `spindle.db`, whose messages are indexed by the FTS5 table `log_entry_fts`. Entries older than
+//! the configured retention are pruned by the same task. The query command filters by service,
+//! time range, stream, level and full text, one page at a time.
+
+use std::{
+    ops::DerefMut,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use spindle_core::{service::ServiceManager, service_log::ServiceOutputLine};
+use sqlx::{Connection, QueryBuilder, Row, Sqlite};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{Event, Instrument, Subscriber, field::Field, info, warn};
+use tracing_subscriber::{layer::Context, registry::LookupSpan};
+
+/// Records the queue holds before new ones are dropped.
+pub const LOG_STORE_CAPACITY: usize = 4096;
+
+/// Max records written in one transaction.
+const MAX_BATCH: usize = 512;
+
+/// Time between two prunings of expired entries.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Max entries returned by one query page.
+const MAX_PAGE_SIZE: u32 = 1000;
+
+/// Name of the span the writer runs in; events inside it are not stored, so that writing the
+/// store never feeds back into it.
+const WRITER_SPAN: &str = "log_store_writer";
+
+/// Levels from most to least severe, as stored in `log_entry.level`.
+const LEVELS: [&str; 5] = ["ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
+
+/// Source of a log entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    /// Standard output of a service.
+    Stdout,
+    /// Standard error of a service.
+    Stderr,
+    /// A `tracing` event of Spindle itself.
+    Spindle,
+}
+
+impl LogStream {
+    /// Returns the stable string form, as stored in `log_entry.stream`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stdout => "stdout",
+            Self::Stderr => "stderr",
+            Self::Spindle => "spindle",
+        }
+    }
+}
+
+/// A log entry queued for the store.
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    /// Time of the entry in milliseconds since the Unix epoch.
+    pub timestamp: i64,
+    pub stream: LogStream,
+    /// Service name, for service output and for events with a `name` field.
+    pub name: Option<String>,
+    /// Service version, for service output and for events with a `version` field.
+    pub version: Option<String>,
+    /// Level of a Spindle event; `None` for service output.
+    pub level: Option<&'static str>,
+    /// Target of a Spindle event; `None` for service output.
+    pub target: Option<String>,
+    /// Output line, or event message followed by its other fields as `key=value`.
+    pub message: String,
+}
+
+impl From<ServiceOutputLine> for LogRecord {
+    fn from(line: ServiceOutputLine) -> Self {
+        Self {
+            timestamp: line.timestamp as i64,
+            stream: match line.stream {
+                spindle_core::service_log::OutputStream::Stdout => LogStream::Stdout,
+                spindle_core::service_log::OutputStream::Stderr => LogStream::Stderr,
+            },
+            name: Some(line.key.0.to_string()),
+            version: Some(line.key.1.to_string()),
+            level: None,
+            target: None,
+            message: line.line,
+        }
+    }
+}
+
+/// Collects the message, the other fields and the service key of an event.
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: String,
+    name: Option<String>,
+    version: Option<String>,
+}
+
+impl tracing::field::Visit for EventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "name" => self.name = Some(value.to_string()),
+            "version" => self.version = Some(value.to_string()),
+            _ => {}
+        }
+        self.record_debug(field, &value);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        use std::fmt::Write;
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// A tracing layer that queues events for the log store.
+///
+/// Events are dropped when the queue is full, so logging never waits for the database.
+pub struct LogStoreLayer {
+    sender: mpsc::Sender<LogRecord>,
+}
+
+impl LogStoreLayer {
+    /// Creates a layer and the queue it sends to.
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing:
+    /// - The layer instance
+    /// - A sender for queuing other records, such as service output
+    /// - The receiver to hand to [spawn_log_writer]
+    pub fn new() -> (Self, mpsc::Sender<LogRecord>, mpsc::Receiver<LogRecord>) {
+        let (sender, receiver) = mpsc::channel(LOG_STORE_CAPACITY);
+        (
+            Self {
+                sender: sender.clone(),
+            },
+            sender,
+            receiver,
+        )
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for LogStoreLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if let Some(scope) = ctx.event_scope(event)
+            && scope.into_iter().any(|span| span.name() == WRITER_SPAN)
+        {
+            return;
+        }
+        let metadata = event.metadata();
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+        let mut message = if visitor.message.is_empty() {
+            metadata.name().to_string()
+        } else {
+            visitor.message
+        };
+        message.push_str(&visitor.fields);
+        let record = LogRecord {
+            timestamp: Utc::now().timestamp_millis(),
+            stream: LogStream::Spindle,
+            name: visitor.name,
+            version: visitor.version,
+            level: Some(metadata.level().as_str()),
+            target: Some(metadata.target().to_string()),
+            message,
+        };
+        let _ = self.sender.try_send(record);
+    }
+}
+
+/// Inserts a batch of records into `log_entry` in one transaction.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `records` - Records to insert.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the connection or an insert fails.
+async fn insert_log_records(app: &tauri::AppHandle, records: &[LogRecord]) -> anyhow::Result<()> {
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    let mut tx = db_conn.begin().await?;
+    for record in records {
+        sqlx::query(
+            "INSERT INTO log_entry (timestamp, stream, name, version, level, target, message)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(record.timestamp)
+        .bind(record.stream.as_str())
+        .bind(record.name.as_deref())
+        .bind(record.version.as_deref())
+        .bind(record.level)
+        .bind(record.target.as_deref())
+        .bind(&record.message)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Deletes entries older than `retention`.
+///
+/// # Returns
+///
+/// `Ok(count)` of deleted entries, or an error.
+async fn prune_log_entries(app: &tauri::AppHandle, retention: Duration) -> anyhow::Result<u64> {
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    let cutoff = Utc::now().timestamp_millis() - retention.as_millis() as i64;
+    let result = sqlx::query("DELETE FROM log_entry WHERE timestamp < $1")
+        .bind(cutoff)
+        .execute(db_conn.deref_mut())
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Spawns the task that writes queued records to the database and prunes expired entries.
+///
+/// The task ends when every sender of the queue is dropped.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `receiver` - Receiver returned by [LogStoreLayer::new].
+/// * `retention` - Age after which entries are deleted; `None` keeps them forever.
+pub fn spawn_log_writer(
+    app: tauri::AppHandle,
+    mut receiver: mpsc::Receiver<LogRecord>,
+    retention: Option<Duration>,
+) {
+    let fut = async move {
+        let mut batch = Vec::with_capacity(MAX_BATCH);
+        let mut last_prune: Option<Instant> = None;
+        while receiver.recv_many(&mut batch, MAX_BATCH).await > 0 {
+            if let Err(e) = insert_log_records(&app, &batch).await {
+                warn!("error" = ?e, "dropped" = batch.len(), "Failed to write log entries");
+            }
+            batch.clear();
+            if let Some(retention) = retention
+                && last_prune.is_none_or(|time| time.elapsed() >= PRUNE_INTERVAL)
+            {
+                last_prune = Some(Instant::now());
+                if let Err(e) = prune_log_entries(&app, retention).await {
+                    warn!("error" = ?e, "Failed to prune log entries");
+                }
+            }
+        }
+        info!("Log store writer stopped");
+    };
+    tauri::async_runtime::spawn(fut.instrument(tracing::info_span!(WRITER_SPAN)));
+}
+
+/// Spawns a task that queues the captured output of `service_manager` for the store.
+///
+/// The task ends when the manager is dropped (e.g. replaced by a reload) or the queue is closed.
+///
+/// # Arguments
+///
+/// * `sender` - Queue of the log store.
+/// * `service_manager` - Manager whose output to store.
+pub fn spawn_output_indexer(sender: mpsc::Sender<LogRecord>, service_manager: &ServiceManager) {
+    let mut receiver = service_manager.subscribe_output();
+    let fut = async move {
+        loop {
+            match receiver.recv().await {
+                Ok(line) => {
+                    if sender.send(line.into()).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!(
+                        "skipped" = n,
+                        "Log store lagged, some service output was not stored"
+                    );
+                }
+            }
+        }
+        info!("Service output indexer stopped");
+    };
+    tokio::spawn(fut);
+}
+
+/// Filters and page of a log store query; unset filters match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LogQuery {
+    /// Service name.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Service version; only used with `name`.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Earliest time, in milliseconds since the Unix epoch (inclusive).
+    #[serde(default)]
+    pub from: Option<i64>,
+    /// Latest time, in milliseconds since the Unix epoch (exclusive).
+    #[serde(default)]
+    pub to: Option<i64>,
+    /// Streams to include; all if unset.
+    #[serde(default)]
+    pub streams: Option<Vec<LogStream>>,
+    /// Least severe level of Spindle events to include, e.g. `"WARN"`; service output has no
+    /// level and is not filtered by it.
+    #[serde(default)]
+    pub min_level: Option<String>,
+    /// FTS5 query matched against messages, e.g. `"connection refused" OR timeout`.
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Zero-based page index.
+    #[serde(default)]
+    pub page: u32,
+    /// Number of entries per page, at most 1000.
+    pub page_size: u32,
+}
+
+/// One row from the `log_entry` table.
+#[derive(Debug, Serialize)]
+pub struct StoredLogEntry {
+    /// Primary key id of the entry.
+    pub id: i64,
+    /// Time in milliseconds since the Unix epoch.
+    pub timestamp: i64,
+    /// `stdout`, `stderr` or `spindle`.
+    pub stream: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub level: Option<String>,
+    pub target: Option<String>,
+    pub message: String,
+}
+
+/// One page of log entries, newest first.
+#[derive(Debug, Serialize)]
+pub struct LogPage {
+    /// Total number of entries matching the query, across all pages.
+    pub total: u32,
+    /// Entries on the requested page.
+    pub entries: Vec<StoredLogEntry>,
+}
+
+/// Appends the `WHERE` clause of `query` to `builder`.
+///
+/// # Returns
+///
+/// `Ok(())`, or an error if the level is unknown.
+fn push_log_filter(builder: &mut QueryBuilder<'_, Sqlite>, query: &LogQuery) -> anyhow::Result<()> {
+    builder.push(" WHERE 1 = 1");
+    if let Some(name) = &query.name {
+        builder.push(" AND name = ").push_bind(name.clone());
+        if let Some(version) = &query.version {
+            builder.push(" AND version = ").push_bind(version.clone());
+        }
+    }
+    if let Some(from) = query.from {
+        builder.push(" AND timestamp >= ").push_bind(from);
+    }
+    if let Some(to) = query.to {
+        builder.push(" AND timestamp < ").push_bind(to);
+    }
+    if let Some(streams) = &query.streams {
+        builder.push(" AND stream IN (");
+        let mut separated = builder.separated(", ");
+        // An empty list matches nothing.
+        separated.push("NULL");
+        for stream in streams {
+            separated.push_bind(stream.as_str());
+        }
+        builder.push(")");
+    }
+    if let Some(min_level) = &query.min_level {
+        let idx = LEVELS
+            .iter()
+            .position(|level| level.eq_ignore_ascii_case(min_level))
+            .ok_or_else(|| anyhow::anyhow!("Invalid level: {}", min_level))?;
+        builder.push(" AND (level IS NULL OR level IN (");
+        let mut separated = builder.separated(", ");
+        for level in &LEVELS[..=idx] {
+            separated.push_bind(*level);
+        }
+        builder.push("))");
+    }
+    if let Some(text) = &query.text {
+        builder
+            .push(" AND id IN (SELECT rowid FROM log_entry_fts WHERE log_entry_fts MATCH ")
+            .push_bind(text.clone())
+            .push(")");
+    }
+    Ok(())
+}
+
+/// Queries one page of log entries, newest first.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `query` - Filters and page.
+///
+/// # Returns
+///
+/// `Ok(LogPage)` on success, or an error if a filter is invalid (including FTS5 syntax errors)
+/// or on DB error.
+async fn query_log_entries(app: &tauri::AppHandle, query: &LogQuery) -> anyhow::Result<LogPage> {
+    let page_size = query.page_size.min(MAX_PAGE_SIZE);
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    let mut count = QueryBuilder::new("SELECT COUNT(*) AS total FROM log_entry");
+    push_log_filter(&mut count, query)?;
+    let total: u32 = count
+        .build()
+        .fetch_one(db_conn.deref_mut())
+        .await?
+        .get("total");
+    let mut select = QueryBuilder::new("SELECT * FROM log_entry");
+    push_log_filter(&mut select, query)?;
+    select
+        .push(" ORDER BY timestamp DESC, id DESC LIMIT ")
+        .push_bind(page_size)
+        .push(" OFFSET ")
+        .push_bind(query.page as i64 * page_size as i64);
+    let entries = select
+        .build()
+        .fetch_all(db_conn.deref_mut())
+        .await?
+        .iter()
+        .map(|row| StoredLogEntry {
+            id: row.get("id"),
+            timestamp: row.get("timestamp"),
+            stream: row.get("stream"),
+            name: row.get("name"),
+            version: row.get("version"),
+            level: row.get("level"),
+            target: row.get("target"),
+            message: row.get("message"),
+        })
+        .collect();
+    Ok(LogPage { total, entries })
+}
+
+/// Tauri commands exposed to the frontend: searching the log store.
+pub mod tauri_cmd {
+    /// Returns one page of stored log entries matching `query`, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `query` - Service, time range, streams, minimum level, full-text query and page; e.g.
+    ///   `{"name": "api", "streams": ["stderr"], "text": "timeout", "page_size": 100}`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(page)` on success, or `Err(message)` on an invalid filter or DB error.
+    #[tauri::command]
+    pub async fn query_logs(
+        app: tauri::AppHandle,
+        query: super::LogQuery,
+    ) -> Result<super::LogPage, String> {
+        super::query_log_entries(&app, &query)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
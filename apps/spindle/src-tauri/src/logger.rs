@@ -1,19 +1,31 @@
 //! Logger initialization using `tracing_subscriber` and `tracing_appender`.
 //! Composes console and rolling file output via layers for easy extension.
+//! Also holds the settings of the per-service output logs, see [spindle_core::service_log].
 
-use std::path::PathBuf;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::PathBuf,
+    sync::{Arc, PoisonError},
+    time::Duration,
+};
 
-use chrono::Utc;
+use chrono::{SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
-use std::fmt::Write;
+use spindle_core::service_log::{DEFAULT_MAX_LOG_FILES, LogRotation, ServiceLogOptions};
+use tauri::Manager;
 use tauri_plugin_store::StoreExt;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
 pub use tracing::level_filters::LevelFilter;
-use tracing::{Event, Level, Subscriber};
+use tracing::{
+    Event, Level, Subscriber,
+    field::{Field, Visit},
+};
 pub use tracing_appender::non_blocking::WorkerGuard;
 use tracing_appender::rolling;
 use tracing_subscriber::{fmt, layer::Layer, prelude::*, registry};
 
+use crate::log_store::{LogRecord, LogStoreLayer};
+
 /// Logger initialization configuration.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LoggerConfig {
@@ -26,6 +38,12 @@ pub struct LoggerConfig {
     pub file: Option<FileLogConfig>,
     /// Broadcast output config; `None` disables broadcast logging.
     pub broadcast: Option<BroadcastLogConfig>,
+    /// Log files of captured service output.
+    #[serde(default)]
+    pub service_log: ServiceLogConfig,
+    /// Searchable store of events and service output, see [crate::log_store].
+    #[serde(default)]
+    pub store: LogStoreConfig,
 }
 
 /// Rolling file log configuration.
@@ -41,6 +59,41 @@ pub struct FileLogConfig {
     pub level: LevelFilter,
 }
 
+/// Service output log configuration.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServiceLogConfig {
+    /// Directory for the service log files; `None` uses `service-logs` in the app data directory.
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+    /// When a service log file is rotated. Default is daily.
+    #[serde(default)]
+    pub rotation: LogRotation,
+    /// Rotated files kept per service. Default is 7.
+    #[serde(default = "default_service_log_max_files")]
+    pub max_files: usize,
+}
+
+fn default_service_log_max_files() -> usize {
+    DEFAULT_MAX_LOG_FILES
+}
+
+fn default_broadcast_backlog() -> usize {
+    256
+}
+
+/// Log store configuration.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogStoreConfig {
+    /// Whether events and service output are stored. Default is `true`.
+    pub enabled: bool,
+    /// Minimum level for stored events. Default is INFO.
+    #[serde(with = "level_filter_serde")]
+    pub level: LevelFilter,
+    /// Days entries are kept; `0` keeps them forever. Default is 7.
+    pub retention_days: u32,
+}
+
 /// Broadcast log configuration.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BroadcastLogConfig {
@@ -49,6 +102,9 @@ pub struct BroadcastLogConfig {
     pub level: LevelFilter,
     /// Capacity of the broadcast channel. Default is 128.
     pub capacity: usize,
+    /// Recent events replayed to new subscribers. Default is 256.
+    #[serde(default = "default_broadcast_backlog")]
+    pub backlog: usize,
 }
 
 /// Serialization helper for LevelFilter
@@ -119,6 +175,28 @@ impl Default for LoggerConfig {
             console_level: LevelFilter::WARN,
             file: None,
             broadcast: None,
+            service_log: ServiceLogConfig::default(),
+            store: LogStoreConfig::default(),
+        }
+    }
+}
+
+impl Default for ServiceLogConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            rotation: LogRotation::Daily,
+            max_files: DEFAULT_MAX_LOG_FILES,
+        }
+    }
+}
+
+impl Default for LogStoreConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            level: LevelFilter::INFO,
+            retention_days: 7,
         }
     }
 }
@@ -138,6 +216,7 @@ impl Default for BroadcastLogConfig {
         Self {
             level: LevelFilter::INFO,
             capacity: 128,
+            backlog: default_broadcast_backlog(),
         }
     }
 }
@@ -146,8 +225,13 @@ impl Default for BroadcastLogConfig {
 pub struct LoggerInitResult {
     /// Worker guard for file logging, if enabled. Must be kept alive.
     pub worker_guard: Option<WorkerGuard>,
-    /// Broadcast receiver for log messages, if broadcast logging is enabled.
-    pub broadcast_receiver: Option<broadcast::Receiver<String>>,
+    /// Broadcast handle for subscribing to log events, if broadcast logging is enabled.
+    pub broadcast: Option<LogBroadcast>,
+    /// Sender of the log store queue, if the log store is enabled; used to store service output.
+    pub log_store_sender: Option<mpsc::Sender<LogRecord>>,
+    /// Receiver of the log store queue, if the log store is enabled; hand it to
+    /// [crate::log_store::spawn_log_writer].
+    pub log_store_receiver: Option<mpsc::Receiver<LogRecord>>,
 }
 
 /// Saves logger configuration to store.
@@ -192,9 +276,49 @@ fn load_config_from_store(app_handle: &tauri::AppHandle) -> Option<LoggerConfig>
     }
 }
 
+/// Returns the service log settings for the stored logger configuration.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle, used for the store and the app data directory.
+///
+/// # Returns
+///
+/// `Some(options)`, or `None` if no directory is configured and the app data directory is unknown.
+pub(crate) fn service_log_options(app: &tauri::AppHandle) -> Option<ServiceLogOptions> {
+    let config = load_config_from_store(app)
+        .map(|config| config.service_log)
+        .unwrap_or_default();
+    let directory = match config.directory {
+        Some(directory) => directory,
+        None => app.path().app_data_dir().ok()?.join("service-logs"),
+    };
+    Some(ServiceLogOptions {
+        directory,
+        rotation: config.rotation,
+        max_files: config.max_files,
+    })
+}
+
+/// Returns how long log store entries are kept, according to the stored logger configuration.
+///
+/// # Returns
+///
+/// `Some(retention)`, or `None` if entries are kept forever.
+pub(crate) fn log_store_retention(app: &tauri::AppHandle) -> Option<Duration> {
+    let config = load_config_from_store(app)
+        .map(|config| config.store)
+        .unwrap_or_default();
+    match config.retention_days {
+        0 => None,
+        days => Some(Duration::from_secs(u64::from(days) * 24 * 60 * 60)),
+    }
+}
+
 /// Initializes the global logger.
 ///
-/// Adds console and/or rolling file layers from `config`, and optionally a broadcast layer.
+/// Adds console and/or rolling file layers from `config`, and optionally broadcast and log store
+/// layers.
 /// When file output is enabled, the returned `WorkerGuard` must be held by the caller until process
 /// exit, or the background writer thread may stop and logs can be lost.
 ///
@@ -209,7 +333,8 @@ fn load_config_from_store(app_handle: &tauri::AppHandle) -> Option<LoggerConfig>
 ///
 /// A `LoggerInitResult` containing:
 /// * `worker_guard` - `Some(guard)` when file logging is enabled; the caller must keep `guard` alive.
-/// * `broadcast_receiver` - `Some(receiver)` when broadcast logging is enabled; use this to receive log messages.
+/// * `broadcast` - `Some(handle)` when broadcast logging is enabled; use this to subscribe to log events.
+/// * `log_store_sender`, `log_store_receiver` - `Some` when the log store is enabled.
 ///
 /// # Errors
 ///
@@ -288,12 +413,27 @@ fn create_broadcast_layer(
     broadcast_cfg: &BroadcastLogConfig,
 ) -> (
     Box<dyn Layer<registry::Registry> + Send + Sync>,
-    broadcast::Receiver<String>,
+    LogBroadcast,
 ) {
-    let (broadcast_layer, receiver) =
-        BroadcastLayer::new(broadcast_cfg.level, broadcast_cfg.capacity);
+    let (broadcast_layer, broadcast) = BroadcastLayer::new(
+        broadcast_cfg.level,
+        broadcast_cfg.capacity,
+        broadcast_cfg.backlog,
+    );
     let layer: Box<dyn Layer<registry::Registry> + Send + Sync> = Box::new(broadcast_layer);
-    (layer, receiver)
+    (layer, broadcast)
+}
+
+/// Creates log store layer.
+fn create_log_store_layer(
+    store_cfg: &LogStoreConfig,
+) -> (
+    Box<dyn Layer<registry::Registry> + Send + Sync>,
+    mpsc::Sender<LogRecord>,
+    mpsc::Receiver<LogRecord>,
+) {
+    let (layer, sender, receiver) = LogStoreLayer::new();
+    (layer.with_filter(store_cfg.level).boxed(), sender, receiver)
 }
 
 /// Internal function that initializes the logger with a specific configuration.
@@ -314,10 +454,20 @@ fn init_logger_with_config(config: LoggerConfig) -> anyhow::Result<LoggerInitRes
     }
 
     // Add broadcast layer
-    let mut broadcast_receiver = None;
+    let mut broadcast = None;
     if let Some(ref broadcast_cfg) = config.broadcast {
-        let (layer, receiver) = create_broadcast_layer(broadcast_cfg);
-        broadcast_receiver = Some(receiver);
+        let (layer, handle) = create_broadcast_layer(broadcast_cfg);
+        broadcast = Some(handle);
+        layers.push(layer);
+    }
+
+    // Add log store layer
+    let mut log_store_sender = None;
+    let mut log_store_receiver = None;
+    if config.store.enabled {
+        let (layer, sender, receiver) = create_log_store_layer(&config.store);
+        log_store_sender = Some(sender);
+        log_store_receiver = Some(receiver);
         layers.push(layer);
     }
 
@@ -333,16 +483,158 @@ fn init_logger_with_config(config: LoggerConfig) -> anyhow::Result<LoggerInitRes
 
     Ok(LoggerInitResult {
         worker_guard: guard,
-        broadcast_receiver,
+        broadcast,
+        log_store_sender,
+        log_store_receiver,
     })
 }
 
+/// A log event as broadcast to subscribers, serialized to JSON for the frontend.
+#[derive(Clone, Debug, Serialize)]
+pub struct LogEvent {
+    /// Time of the event, RFC 3339 in UTC with milliseconds.
+    pub timestamp: String,
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`.
+    pub level: &'static str,
+    /// Target of the event, usually its module path.
+    pub target: String,
+    /// Message of the event; the event name if it has no message.
+    pub message: String,
+    /// Other fields of the event, such as the `name` and `version` of a service.
+    pub fields: BTreeMap<String, serde_json::Value>,
+    /// Spans the event occurred in, outermost first.
+    pub spans: Vec<LogSpan>,
+}
+
+/// A span in the context of a [LogEvent].
+#[derive(Clone, Debug, Serialize)]
+pub struct LogSpan {
+    /// Span name.
+    pub name: &'static str,
+    /// Fields recorded on the span.
+    pub fields: BTreeMap<String, serde_json::Value>,
+}
+
+/// Collects fields as JSON values, keeping numbers and booleans typed; the `message` field is
+/// kept apart.
+#[derive(Default)]
+struct JsonFieldVisitor {
+    message: Option<String>,
+    fields: BTreeMap<String, serde_json::Value>,
+}
+
+impl JsonFieldVisitor {
+    fn insert(&mut self, field: &Field, value: serde_json::Value) {
+        self.fields.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for JsonFieldVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_string());
+        } else {
+            self.insert(field, value.into());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let value = format!("{:?}", value);
+        if field.name() == "message" {
+            self.message = Some(value);
+        } else {
+            self.insert(field, value.into());
+        }
+    }
+}
+
+impl LogEvent {
+    /// Returns the event emitted in place of `count` events a subscriber missed.
+    fn dropped(count: u64) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            level: Level::WARN.as_str(),
+            target: module_path!().to_string(),
+            message: format!("dropped {} messages", count),
+            fields: BTreeMap::from([("dropped".to_string(), count.into())]),
+            spans: Vec::new(),
+        }
+    }
+}
+
+/// Handle for subscribing to the events of a [BroadcastLayer], starting with its backlog of
+/// recent events.
+#[derive(Clone)]
+pub struct LogBroadcast {
+    sender: broadcast::Sender<LogEvent>,
+    /// Most recent events, oldest first. It stays locked while an event is sent, so a new
+    /// subscriber never misses an event nor gets one twice.
+    backlog: Arc<std::sync::Mutex<VecDeque<LogEvent>>>,
+    backlog_capacity: usize,
+}
+
+impl LogBroadcast {
+    /// Subscribes to log events.
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing:
+    /// - The backlog of recent events, oldest first
+    /// - A receiver for the events sent after them
+    pub fn subscribe(&self) -> (Vec<LogEvent>, broadcast::Receiver<LogEvent>) {
+        let backlog = self.backlog.lock().unwrap_or_else(PoisonError::into_inner);
+        (backlog.iter().cloned().collect(), self.sender.subscribe())
+    }
+
+    /// Adds an event to the backlog and sends it to the subscribers.
+    fn send(&self, event: LogEvent) {
+        let mut backlog = self.backlog.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.backlog_capacity > 0 {
+            if backlog.len() >= self.backlog_capacity {
+                backlog.pop_front();
+            }
+            backlog.push_back(event.clone());
+        }
+        // Sending fails only when there are no subscribers, and the event is in the backlog.
+        let _ = self.sender.send(event);
+    }
+}
+
+/// A subscription of a webview to log events, see [tauri_cmd::subscribe_log].
+pub struct LogSubscription {
+    /// Label of the webview that subscribed.
+    pub webview: String,
+    /// Event name the log events are emitted to.
+    pub event_name: String,
+    /// Task forwarding the log events.
+    pub task: tokio::task::JoinHandle<()>,
+}
+
+/// Fields of a span, kept in its extensions for the events inside it.
+struct SpanFields(BTreeMap<String, serde_json::Value>);
+
 /// A tracing layer that broadcasts log events via a tokio broadcast channel.
 ///
 /// This layer intercepts log events at or above the specified level and sends them
-/// through a broadcast channel as pretty-formatted strings. Multiple receivers can subscribe to receive these logs.
+/// through a broadcast channel as [LogEvent]s. Multiple receivers can subscribe to receive these logs.
 pub struct BroadcastLayer {
-    sender: broadcast::Sender<String>,
+    broadcast: LogBroadcast,
     level: LevelFilter,
 }
 
@@ -353,15 +645,27 @@ impl BroadcastLayer {
     ///
     /// * `level` - Minimum log level to intercept and broadcast
     /// * `capacity` - Capacity of the broadcast channel (default: 1000)
+    /// * `backlog` - Number of recent events replayed to new subscribers
     ///
     /// # Returns
     ///
     /// A tuple containing:
     /// - The layer instance
-    /// - A receiver handle to subscribe to log messages
-    pub fn new(level: LevelFilter, capacity: usize) -> (Self, broadcast::Receiver<String>) {
-        let (sender, receiver) = broadcast::channel(capacity);
-        (Self { sender, level }, receiver)
+    /// - A handle to subscribe to log events
+    pub fn new(level: LevelFilter, capacity: usize, backlog: usize) -> (Self, LogBroadcast) {
+        let (sender, _) = broadcast::channel(capacity);
+        let broadcast = LogBroadcast {
+            sender,
+            backlog: Arc::new(std::sync::Mutex::new(VecDeque::with_capacity(backlog))),
+            backlog_capacity: backlog,
+        };
+        (
+            Self {
+                broadcast: broadcast.clone(),
+                level,
+            },
+            broadcast,
+        )
     }
 }
 
@@ -369,7 +673,45 @@ impl<S> Layer<S> for BroadcastLayer
 where
     S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
 {
-    fn on_event(&self, event: &Event<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        id: &tracing::span::Id,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = JsonFieldVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(message) = visitor.message {
+            visitor.fields.insert("message".to_string(), message.into());
+        }
+        span.extensions_mut().insert(SpanFields(visitor.fields));
+    }
+
+    fn on_record(
+        &self,
+        id: &tracing::span::Id,
+        values: &tracing::span::Record<'_>,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = JsonFieldVisitor::default();
+        values.record(&mut visitor);
+        if let Some(message) = visitor.message {
+            visitor.fields.insert("message".to_string(), message.into());
+        }
+        let mut extensions = span.extensions_mut();
+        match extensions.get_mut::<SpanFields>() {
+            Some(SpanFields(fields)) => fields.extend(visitor.fields),
+            None => extensions.insert(SpanFields(visitor.fields)),
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: tracing_subscriber::layer::Context<'_, S>) {
         let metadata = event.metadata();
         let level = *metadata.level();
 
@@ -392,98 +734,41 @@ where
             return;
         }
 
-        // Format the event as a pretty string
-        let mut formatted = String::new();
-
-        // Format timestamp (UTC RFC3339)
-        let now = Utc::now();
-        if write!(formatted, "{} ", now.to_rfc3339()).is_err() {
-            eprintln!("BroadcastLayer: Failed to write timestamp");
-            return;
-        }
-
-        // Format level
-        let level_str = match level {
-            Level::ERROR => "ERROR",
-            Level::WARN => "WARN ",
-            Level::INFO => "INFO ",
-            Level::DEBUG => "DEBUG",
-            Level::TRACE => "TRACE",
+        // Collect message and fields
+        let mut visitor = JsonFieldVisitor::default();
+        event.record(&mut visitor);
+
+        // Collect span context, outermost first
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| LogSpan {
+                        name: span.name(),
+                        fields: span
+                            .extensions()
+                            .get::<SpanFields>()
+                            .map(|SpanFields(fields)| fields.clone())
+                            .unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let log_event = LogEvent {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            level: level.as_str(),
+            target: metadata.target().to_string(),
+            message: visitor
+                .message
+                .unwrap_or_else(|| metadata.name().to_string()),
+            fields: visitor.fields,
+            spans,
         };
-        if write!(formatted, "{:5} ", level_str).is_err() {
-            eprintln!("BroadcastLayer: Failed to write level");
-            return;
-        }
-
-        // Format target/module path
-        if write!(formatted, "{}: ", metadata.target()).is_err() {
-            eprintln!("BroadcastLayer: Failed to write target");
-            return;
-        }
-
-        // Collect and format fields
-        let mut fields = Vec::new();
-        let mut message = String::new();
-
-        event.record(
-            &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
-                let field_name = field.name();
-                let field_value = format!("{:?}", value);
-
-                if field_name == "message" {
-                    message = field_value;
-                } else {
-                    fields.push((field_name.to_string(), field_value));
-                }
-            },
-        );
-
-        // Add message
-        if !message.is_empty() {
-            if write!(formatted, "{}", message).is_err() {
-                eprintln!("BroadcastLayer: Failed to write message");
-                return;
-            }
-        } else {
-            if write!(formatted, "{}", metadata.name()).is_err() {
-                eprintln!("BroadcastLayer: Failed to write event name");
-                return;
-            }
-        }
-
-        // Add additional fields
-        if !fields.is_empty() {
-            if write!(formatted, " ").is_err() {
-                eprintln!("BroadcastLayer: Failed to write field separator");
-                return;
-            }
-            for (i, (key, value)) in fields.iter().enumerate() {
-                if i > 0 {
-                    if write!(formatted, ", ").is_err() {
-                        eprintln!("BroadcastLayer: Failed to write field separator");
-                        return;
-                    }
-                }
-                if write!(formatted, "{}={}", key, value).is_err() {
-                    eprintln!("BroadcastLayer: Failed to write field");
-                    return;
-                }
-            }
-        }
-
-        // Add newline
-        if writeln!(formatted).is_err() {
-            eprintln!("BroadcastLayer: Failed to write newline");
-            return;
-        }
 
-        // Send formatted message
-        if let Err(e) = self.sender.send(formatted) {
-            eprintln!(
-                "BroadcastLayer: Failed to send log message to broadcast channel: {}",
-                e
-            );
-        }
+        // Send log event
+        self.broadcast.send(log_event);
     }
 
     fn enabled(
@@ -505,54 +790,180 @@ where
 }
 
 pub mod tauri_cmd {
+    use spindle_core::service_log::{LogRange, ServiceLogPage};
     use tauri::{Emitter, Manager};
     use tokio::sync::Mutex;
 
+    /// Reads a byte or line range of the captured output history of a service.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `name` - Service name, including any replica suffix.
+    /// * `version` - Service version.
+    /// * `range` - `{"bytes": {"offset", "len"}}`, or `{"lines": {"start", "count"}}` where a
+    ///   missing `start` reads the last `count` lines.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(page)` with the content and the total size of the history, or `Err(message)` on failure.
+    #[tauri::command]
+    pub async fn read_service_log(
+        app: tauri::AppHandle,
+        name: String,
+        version: String,
+        range: LogRange,
+    ) -> Result<ServiceLogPage, String> {
+        let options = super::service_log_options(&app)
+            .ok_or_else(|| "Service log directory not available".to_string())?;
+        tokio::task::spawn_blocking(move || {
+            spindle_core::service_log::read_service_log(&options.directory, &name, &version, range)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+    }
+
     /// Subscribes to log events and emits them to the frontend.
     ///
+    /// Each event is emitted as a JSON [super::LogEvent]: `timestamp`, `level`, `target`,
+    /// `message`, a `fields` object and the enclosing `spans`, each with a `name` and `fields`.
+    /// The recent events kept in the backlog are emitted first. If the subscription falls behind
+    /// and misses events, a WARN event with the message `dropped N messages` and a `dropped`
+    /// field is emitted in their place.
+    ///
     /// # Arguments
     ///
     /// * `app` - Tauri app handle.
+    /// * `webview` - Calling webview, injected by Tauri.
     /// * `event_name` - Event name to emit logs to.
     ///
     /// # Returns
     ///
-    /// `Ok(())` if the log subscription is successful, `Err(String)` if the logger is not initialized.
+    /// `Ok(id)` of the subscription, to pass to [unsubscribe_log], or `Err(String)` if the
+    /// logger is not initialized.
     ///
     /// # Note
     ///
-    /// Each call to this function creates a new subscription task. If called multiple times,
-    /// multiple tasks will be spawned, each forwarding logs to the frontend. The tasks will
-    /// run until the broadcast channel is closed or the application exits.
+    /// Each call creates a new subscription task, which runs until it is unsubscribed, the
+    /// broadcast channel is closed or the application exits. A webview that reloads should
+    /// cancel its previous subscriptions with [unsubscribe_logs], or events are emitted more
+    /// than once.
     #[tauri::command]
-    pub async fn subscribe_log(app: tauri::AppHandle, event_name: String) -> Result<(), String> {
+    pub async fn subscribe_log(
+        app: tauri::AppHandle,
+        webview: tauri::Webview,
+        event_name: String,
+    ) -> Result<u64, String> {
         let app_state = app.state::<Mutex<crate::AppState>>();
-        let mut receiver = app_state
-            .lock()
-            .await
-            .logger_broadcast_receiver
+        let mut state = app_state.lock().await;
+        // Subscriptions whose channel closed are not unsubscribed by anyone.
+        state
+            .log_subscriptions
+            .retain(|_, subscription| !subscription.task.is_finished());
+        let (backlog, mut receiver) = state
+            .log_broadcast
             .as_ref()
-            .map(|r| r.resubscribe())
+            .map(|broadcast| broadcast.subscribe())
             .ok_or_else(|| "Logger not initialized".to_string())?;
 
+        let emitter = app.clone();
+        let subscription_event_name = event_name.clone();
         let fut = async move {
+            let emit = |event: &super::LogEvent| {
+                if let Err(e) = emitter.emit(&event_name, event) {
+                    eprintln!("Failed to emit log event '{}': {}", event_name, e);
+                }
+            };
+            for event in &backlog {
+                emit(event);
+            }
             loop {
                 match receiver.recv().await {
-                    Ok(message) => {
-                        if let Err(e) = app.emit(&event_name, message) {
-                            eprintln!("Failed to emit log event '{}': {}", event_name, e);
-                        }
-                    }
+                    Ok(event) => emit(&event),
                     Err(tokio::sync::broadcast::error::RecvError::Closed) => {
                         eprintln!("Broadcast channel closed, stopping log subscription");
                         break;
                     }
-                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        emit(&super::LogEvent::dropped(n));
+                    }
                 }
             }
         };
 
-        tokio::spawn(fut);
+        state.next_log_subscription_id += 1;
+        let id = state.next_log_subscription_id;
+        state.log_subscriptions.insert(
+            id,
+            super::LogSubscription {
+                webview: webview.label().to_string(),
+                event_name: subscription_event_name,
+                task: tokio::spawn(fut),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Cancels a log subscription, stopping its events.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `id` - Subscription id returned by [subscribe_log].
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(message)` if there is no such subscription.
+    #[tauri::command]
+    pub async fn unsubscribe_log(app: tauri::AppHandle, id: u64) -> Result<(), String> {
+        let app_state = app.state::<Mutex<crate::AppState>>();
+        let subscription = app_state
+            .lock()
+            .await
+            .log_subscriptions
+            .remove(&id)
+            .ok_or_else(|| format!("Log subscription not found: {}", id))?;
+        subscription.task.abort();
         Ok(())
     }
+
+    /// Cancels all log subscriptions of the calling webview, such as those it made before a
+    /// reload, without needing their ids.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `webview` - Calling webview, injected by Tauri.
+    /// * `event_name` - If set, only the subscriptions emitting to this event are cancelled.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(count)` of cancelled subscriptions.
+    #[tauri::command]
+    pub async fn unsubscribe_logs(
+        app: tauri::AppHandle,
+        webview: tauri::Webview,
+        event_name: Option<String>,
+    ) -> Result<usize, String> {
+        let app_state = app.state::<Mutex<crate::AppState>>();
+        let mut state = app_state.lock().await;
+        let ids: Vec<u64> = state
+            .log_subscriptions
+            .iter()
+            .filter(|(_, subscription)| {
+                subscription.webview == webview.label()
+                    && event_name
+                        .as_ref()
+                        .is_none_or(|event_name| subscription.event_name == *event_name)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &ids {
+            if let Some(subscription) = state.log_subscriptions.remove(id) {
+                subscription.task.abort();
+            }
+        }
+        Ok(ids.len())
+    }
 }
@@ -0,0 +1,106 @@
+//! App-wide active profile and Tauri command layer.
+//!
+//! The active profile is kept in `tauri_plugin_store` and used as the default profile of every
+//! group when the [spindle_core::service::ServiceManager] is (re)built. Groups can still select
+//! their own profile, see `service::tauri_cmd::set_group_profile`.
+
+use tauri_plugin_store::StoreExt;
+use tracing::warn;
+
+const STORE_PATH: &str = "spindle-kv-store";
+const STORE_KEY: &str = "active_profile";
+
+/// Loads the active profile from the store.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle.
+///
+/// # Returns
+///
+/// `Some(profile)` if a profile is active, `None` otherwise or if the store is unavailable.
+pub(crate) fn load_active_profile(app: &tauri::AppHandle) -> Option<String> {
+    let store = app.store(STORE_PATH).ok()?;
+    let json_value = store.get(STORE_KEY)?;
+    match serde_json::from_value::<Option<String>>(json_value) {
+        Ok(profile) => profile,
+        Err(e) => {
+            warn!("error" = ?e, "Failed to deserialize active profile from store");
+            None
+        }
+    }
+}
+
+/// Saves the active profile to the store.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle.
+/// * `profile` - Profile to activate, or `None` for the base configs.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the store cannot be accessed or saved.
+fn save_active_profile(app: &tauri::AppHandle, profile: Option<&str>) -> anyhow::Result<()> {
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| anyhow::anyhow!("Failed to access store: {}", e))?;
+    store.set(STORE_KEY, serde_json::json!(profile));
+    store
+        .save()
+        .map_err(|e| anyhow::anyhow!("Failed to save active profile: {}", e))?;
+    Ok(())
+}
+
+/// Tauri commands exposed to the frontend.
+pub mod tauri_cmd {
+    use tauri::Manager;
+    use tokio::sync::Mutex;
+    use tracing::warn;
+
+    /// Returns the app-wide active profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    ///
+    /// # Returns
+    ///
+    /// `Some(profile)` if a profile is active, `None` otherwise.
+    #[tauri::command]
+    pub async fn active_profile(app: tauri::AppHandle) -> Option<String> {
+        super::load_active_profile(&app)
+    }
+
+    /// Sets the app-wide active profile and applies it to every group that is not running.
+    ///
+    /// Running groups keep their profile until they are stopped and the profile is set again,
+    /// or the service manager is reloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `profile` - Profile to activate, or `None` for the base configs.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(message)` if the profile cannot be saved.
+    #[tauri::command]
+    pub async fn set_active_profile(
+        app: tauri::AppHandle,
+        profile: Option<String>,
+    ) -> Result<(), String> {
+        super::save_active_profile(&app, profile.as_deref()).map_err(|e| e.to_string())?;
+        let app_state = app.state::<Mutex<crate::AppState>>();
+        let service_manager = match app_state.lock().await.service_manager.as_ref() {
+            Some(sm) => sm.clone(),
+            None => return Ok(()),
+        };
+        for group_id in 0..service_manager.group_num() {
+            if let Err(e) = service_manager.set_group_profile(group_id, profile.as_deref()) {
+                warn!("error" = %e, "group_id" = group_id, "Active profile not applied to group");
+            }
+        }
+        Ok(())
+    }
+}
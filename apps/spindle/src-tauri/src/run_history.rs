@@ -0,0 +1,276 @@
+//! Run history persistence and Tauri command layer.
+//!
+//! This module records finished service runs sent by the service manager into the
+//! `service_run` table and exposes paginated history queries per service and per group.
+
+use std::ops::DerefMut;
+
+use serde::Serialize;
+use spindle_core::service::ServiceRunRecord;
+use sqlx::Row;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// One row from the `service_run` table, joined with the service name and version.
+#[derive(Debug, Serialize)]
+pub struct StoredServiceRun {
+    /// Primary key id of the run.
+    pub id: u32,
+    /// Database id of the service.
+    pub service_id: u32,
+    /// Service name.
+    pub name: String,
+    /// Service version.
+    pub version: String,
+    /// Spawn time in milliseconds since the Unix epoch; `None` if the process never spawned.
+    pub started_at: Option<i64>,
+    /// Stop time in milliseconds since the Unix epoch.
+    pub stopped_at: i64,
+    /// Exit code, if the process exited normally.
+    pub exit_code: Option<i32>,
+    /// Terminating signal, if any.
+    pub signal: Option<i32>,
+    /// Failure reason; `None` for runs stopped on request.
+    pub reason: Option<String>,
+    /// What launched the run (see [spindle_core::service::ServiceRunTrigger]).
+    pub triggered_by: String,
+}
+
+/// One page of run history, newest first.
+#[derive(Debug, Serialize)]
+pub struct RunHistoryPage {
+    /// Total number of runs matching the query, across all pages.
+    pub total: u32,
+    /// Runs on the requested page.
+    pub runs: Vec<StoredServiceRun>,
+}
+
+/// Inserts a finished run into `service_run`.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `record` - Run record sent by the service manager.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the service is unknown or the insert fails.
+async fn insert_service_run(
+    app: &tauri::AppHandle,
+    record: &ServiceRunRecord,
+) -> anyhow::Result<()> {
+    let (name, version) = (&*record.key.0, &*record.key.1);
+    let service_id = crate::service::query_stored_service_id_for_key(app, name, version)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Service not found: {}:v{}", name, version))?;
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    sqlx::query(
+        "INSERT INTO service_run (service_id, started_at, stopped_at, exit_code, signal, reason, triggered_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(service_id)
+    .bind(record.started_at.map(|t| t as i64))
+    .bind(record.stopped_at as i64)
+    .bind(record.exit_code)
+    .bind(record.signal)
+    .bind(record.reason.as_deref())
+    .bind(record.triggered_by.as_str())
+    .execute(db_conn.deref_mut())
+    .await?;
+    Ok(())
+}
+
+/// Number of run records buffered before the service manager waits for the recorder.
+const RUN_RECORD_BUFFER: usize = 256;
+
+/// Spawns a task that writes every run record sent to the returned sender to the database.
+///
+/// The sender goes into [spindle_core::service::ServiceManagerOptions::run_records] before
+/// the manager is built, so no run is missed. The task ends once the manager is dropped
+/// (e.g. replaced by a reload) and the records it sent are written.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+///
+/// # Returns
+///
+/// The sender for the manager's run records.
+pub fn spawn_run_recorder(app: tauri::AppHandle) -> mpsc::Sender<ServiceRunRecord> {
+    let (sender, mut receiver) = mpsc::channel(RUN_RECORD_BUFFER);
+    let fut = async move {
+        while let Some(record) = receiver.recv().await {
+            if let Err(e) = insert_service_run(&app, &record).await {
+                warn!("error" = ?e, "name" = &*record.key.0, "version" = &*record.key.1, "Failed to record service run");
+            }
+        }
+        info!("Run recorder stopped");
+    };
+    tokio::spawn(fut);
+    sender
+}
+
+/// Maps a joined `service_run` row to a [StoredServiceRun].
+fn stored_service_run_from_row(row: &sqlx::sqlite::SqliteRow) -> StoredServiceRun {
+    StoredServiceRun {
+        id: row.get("id"),
+        service_id: row.get("service_id"),
+        name: row.get("name"),
+        version: row.get("version"),
+        started_at: row.get("started_at"),
+        stopped_at: row.get("stopped_at"),
+        exit_code: row.get("exit_code"),
+        signal: row.get("signal"),
+        reason: row.get("reason"),
+        triggered_by: row.get("triggered_by"),
+    }
+}
+
+/// Queries one page of runs for the given service, newest first.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `service_id` - Database id of the service.
+/// * `page` - Zero-based page index.
+/// * `page_size` - Number of runs per page.
+///
+/// # Returns
+///
+/// `Ok(RunHistoryPage)` on success, or an error.
+async fn query_service_run_history(
+    app: &tauri::AppHandle,
+    service_id: u32,
+    page: u32,
+    page_size: u32,
+) -> anyhow::Result<RunHistoryPage> {
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    let total: u32 = sqlx::query("SELECT COUNT(*) AS total FROM service_run WHERE service_id = $1")
+        .bind(service_id)
+        .fetch_one(db_conn.deref_mut())
+        .await?
+        .get("total");
+    let runs = sqlx::query(
+        "SELECT r.*, s.name, s.version FROM service_run r
+        JOIN service s ON s.id = r.service_id
+        WHERE r.service_id = $1
+        ORDER BY r.stopped_at DESC, r.id DESC
+        LIMIT $2 OFFSET $3",
+    )
+    .bind(service_id)
+    .bind(page_size)
+    .bind(page as i64 * page_size as i64)
+    .fetch_all(db_conn.deref_mut())
+    .await?
+    .iter()
+    .map(stored_service_run_from_row)
+    .collect();
+    Ok(RunHistoryPage { total, runs })
+}
+
+/// Queries one page of runs for all services in the given group, newest first.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `group_id` - Group id (see `service_group_membership`).
+/// * `page` - Zero-based page index.
+/// * `page_size` - Number of runs per page.
+///
+/// # Returns
+///
+/// `Ok(RunHistoryPage)` on success, or an error.
+async fn query_group_run_history(
+    app: &tauri::AppHandle,
+    group_id: u32,
+    page: u32,
+    page_size: u32,
+) -> anyhow::Result<RunHistoryPage> {
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    let total: u32 = sqlx::query(
+        "SELECT COUNT(*) AS total FROM service_run r
+        WHERE r.service_id IN (SELECT service_id FROM service_group_membership WHERE group_id = $1)",
+    )
+    .bind(group_id)
+    .fetch_one(db_conn.deref_mut())
+    .await?
+    .get("total");
+    let runs = sqlx::query(
+        "SELECT r.*, s.name, s.version FROM service_run r
+        JOIN service s ON s.id = r.service_id
+        WHERE r.service_id IN (SELECT service_id FROM service_group_membership WHERE group_id = $1)
+        ORDER BY r.stopped_at DESC, r.id DESC
+        LIMIT $2 OFFSET $3",
+    )
+    .bind(group_id)
+    .bind(page_size)
+    .bind(page as i64 * page_size as i64)
+    .fetch_all(db_conn.deref_mut())
+    .await?
+    .iter()
+    .map(stored_service_run_from_row)
+    .collect();
+    Ok(RunHistoryPage { total, runs })
+}
+
+/// Tauri commands exposed to the frontend: paginated run history per service and per group.
+pub mod tauri_cmd {
+    /// Returns one page of run history for a service by (name, version), newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    /// * `page` - Zero-based page index.
+    /// * `page_size` - Number of runs per page.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(page)` on success, or `Err(message)` if the service is not found or on DB error.
+    #[tauri::command]
+    pub async fn service_run_history(
+        app: tauri::AppHandle,
+        name: String,
+        version: String,
+        page: u32,
+        page_size: u32,
+    ) -> Result<super::RunHistoryPage, String> {
+        let service_id = crate::service::query_stored_service_id_for_key(&app, &name, &version)
+            .await
+            .ok_or_else(|| format!("Service not found: {}:v{}", name, version))?;
+        super::query_service_run_history(&app, service_id, page, page_size)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns one page of run history for all services in a group, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `group_id` - Group id.
+    /// * `page` - Zero-based page index.
+    /// * `page_size` - Number of runs per page.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(page)` on success, or `Err(message)` on DB error.
+    #[tauri::command]
+    pub async fn group_run_history(
+        app: tauri::AppHandle,
+        group_id: u32,
+        page: u32,
+        page_size: u32,
+    ) -> Result<super::RunHistoryPage, String> {
+        super::query_group_run_history(&app, group_id, page, page_size)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
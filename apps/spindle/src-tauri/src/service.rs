@@ -3,10 +3,22 @@
 //! This module loads/saves service config from the database, builds [ServiceManager],
 //! and exposes Tauri commands to the frontend (CRUD, reload, group aliases).
 
-use std::{collections::HashMap, ops::DerefMut, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::DerefMut,
+    sync::Arc,
+};
 
 use serde::Serialize;
-use spindle_core::service::ServiceManager;
+use spindle_core::hooks::{HookCommand, HookStage, ServiceHooks};
+use spindle_core::limits::ResourceLimits;
+use spindle_core::schedule::OverlapPolicy;
+use spindle_core::service::{
+    PortSpec, ProfileOverlay, ServiceConfig, ServiceManager, ServiceManagerOptions,
+    split_instance_name, split_replica_name, validate_configs_with_options, validate_replicas,
+    validate_service_name,
+};
+use spindle_core::watch::WatchConfig;
 use sqlx::{Connection, Row, Sqlite, Transaction, pool::PoolConnection};
 use tauri::Manager;
 use tokio::sync::Mutex;
@@ -29,14 +41,64 @@ pub struct StoredServiceConfig {
     pub workspace: Option<String>,
     /// Startup arguments (ordered by arg_idx).
     pub args: Vec<String>,
+    /// Extra environment variables.
+    pub env: BTreeMap<String, String>,
     /// Database ids of dependency services.
     pub dependency_ids: Vec<u32>,
-    /// Group id this service belongs to (matches [ServiceManager] group index).
+    /// Instance names if this service is a template (name ending in `@`).
+    pub instances: Vec<String>,
+    /// Dependencies on single instances of template services, as (template id, instance).
+    pub instance_dependencies: Vec<(u32, String)>,
+    /// Number of replicas; `None` runs a single process.
+    pub replicas: Option<u32>,
+    /// Replicas that must run for dependents to start; `None` means all.
+    pub quorum: Option<u32>,
+    /// Named ports, fixed or `auto`.
+    pub ports: BTreeMap<String, PortSpec>,
+    /// Profile overlays by profile name.
+    pub profiles: BTreeMap<String, StoredProfile>,
+    /// User (name or uid) to run as.
+    pub user: Option<String>,
+    /// Group (name or gid) to run as.
+    pub group: Option<String>,
+    /// Octal umask.
+    pub umask: Option<String>,
+    /// Whether the service keeps running when Spindle exits.
+    pub detached: bool,
+    /// Cron expression the service runs on, if scheduled.
+    pub schedule: Option<String>,
+    /// What to do when a scheduled run is due while the previous one is still going.
+    pub overlap: OverlapPolicy,
+    /// Files whose changes restart the service.
+    pub watch: Option<WatchConfig>,
+    /// Rlimits and cgroup limits.
+    pub limits: ResourceLimits,
+    /// Lifecycle hooks.
+    pub hooks: ServiceHooks,
+    /// Group id this service belongs to (matches [ServiceManager] group index); for a template,
+    /// the lowest group id of its instances.
     /// `None` for newly added services that haven't been grouped yet.
     pub group_id: Option<u32>,
 }
 
-/// One row from the `service_config` table: program path, description, workspace.
+/// Profile overlay of a stored service; unset fields keep the base value.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StoredProfile {
+    /// Replacement startup arguments.
+    pub args: Option<Vec<String>>,
+    /// Environment variables added on top of the base env.
+    pub env: BTreeMap<String, String>,
+    /// Replacement workspace directory.
+    pub workspace: Option<String>,
+    /// Replacement dependency ids; `None` keeps the base dependencies.
+    pub dependency_ids: Option<Vec<u32>>,
+    /// Replacement dependencies on template instances, as (template id, instance).
+    /// Only used when `dependency_ids` is set.
+    pub instance_dependencies: Vec<(u32, String)>,
+}
+
+/// One row from the `service_config` table: program path, description, workspace, replicas,
+/// user, group, umask, detached flag, schedule and overlap policy.
 pub struct ServiceConfigRow {
     /// Executable program path.
     pub program: String,
@@ -44,6 +106,22 @@ pub struct ServiceConfigRow {
     pub description: Option<String>,
     /// Optional workspace directory.
     pub workspace: Option<String>,
+    /// Optional number of replicas.
+    pub replicas: Option<u32>,
+    /// Optional replica quorum.
+    pub quorum: Option<u32>,
+    /// Optional user to run as.
+    pub user: Option<String>,
+    /// Optional group to run as.
+    pub group: Option<String>,
+    /// Optional octal umask.
+    pub umask: Option<String>,
+    /// Whether the service runs detached.
+    pub detached: bool,
+    /// Optional cron schedule.
+    pub schedule: Option<String>,
+    /// Overlap policy of scheduled runs.
+    pub overlap: OverlapPolicy,
 }
 
 /// Queries `name` and `version` for the given service from the `service` table.
@@ -112,6 +190,14 @@ async fn query_service_config(
         program: row.get("program"),
         description: row.get("description"),
         workspace: row.get("workspace"),
+        replicas: row.get("replicas"),
+        quorum: row.get("quorum"),
+        user: row.get("user"),
+        group: row.get("group_name"),
+        umask: row.get("umask"),
+        detached: row.get("detached"),
+        schedule: row.get("schedule"),
+        overlap: OverlapPolicy::from_name(row.get("schedule_overlap")).unwrap_or_default(),
     };
     Some(ret)
 }
@@ -158,6 +244,240 @@ async fn query_service_args(
     Some(ret)
 }
 
+/// Queries the environment variables for the given service from `service_env`.
+///
+/// # Arguments
+///
+/// * `service_id` - Database id of the service.
+/// * `db_conn` - Active pool connection to the spindle DB.
+///
+/// # Returns
+///
+/// `Some(env)` on success, or `None` on error.
+async fn query_service_env(
+    service_id: u32,
+    db_conn: &mut PoolConnection<crate::db::SpindleDbType>,
+) -> Option<BTreeMap<String, String>> {
+    let query_result = sqlx::query(
+        "SELECT key, value FROM service_env
+        WHERE service_id = $1",
+    )
+    .bind(service_id)
+    .fetch_all(db_conn.deref_mut())
+    .await;
+    let rows = match query_result {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!("error" = ?e, "service_id" = service_id, "Failed to read stored service env");
+            return None;
+        }
+    };
+    let ret = rows
+        .into_iter()
+        .map(|row| (row.get("key"), row.get("value")))
+        .collect();
+    Some(ret)
+}
+
+/// Queries the named ports for the given service from `service_port`; a NULL port means `auto`.
+///
+/// # Arguments
+///
+/// * `service_id` - Database id of the service.
+/// * `db_conn` - Active pool connection to the spindle DB.
+///
+/// # Returns
+///
+/// `Some(ports)` on success, or `None` on error.
+async fn query_service_ports(
+    service_id: u32,
+    db_conn: &mut PoolConnection<crate::db::SpindleDbType>,
+) -> Option<BTreeMap<String, PortSpec>> {
+    let query_result = sqlx::query(
+        "SELECT name, port FROM service_port
+        WHERE service_id = $1",
+    )
+    .bind(service_id)
+    .fetch_all(db_conn.deref_mut())
+    .await;
+    let rows = match query_result {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!("error" = ?e, "service_id" = service_id, "Failed to read stored service ports");
+            return None;
+        }
+    };
+    let ret = rows
+        .into_iter()
+        .map(|row| {
+            let port: Option<u16> = row.get("port");
+            (
+                row.get("name"),
+                port.map_or(PortSpec::Auto, PortSpec::Fixed),
+            )
+        })
+        .collect();
+    Some(ret)
+}
+
+/// Queries the resource limits for the given service from `service_limit`.
+///
+/// # Arguments
+///
+/// * `service_id` - Database id of the service.
+/// * `db_conn` - Active pool connection to the spindle DB.
+///
+/// # Returns
+///
+/// `Some(limits)` on success (all unset if there is no row), or `None` on error.
+async fn query_service_limits(
+    service_id: u32,
+    db_conn: &mut PoolConnection<crate::db::SpindleDbType>,
+) -> Option<ResourceLimits> {
+    let query_result = sqlx::query(
+        "SELECT * FROM service_limit
+        WHERE service_id = $1",
+    )
+    .bind(service_id)
+    .fetch_optional(db_conn.deref_mut())
+    .await;
+    let row = match query_result {
+        Ok(Some(row)) => row,
+        Ok(None) => return Some(ResourceLimits::default()),
+        Err(e) => {
+            warn!("error" = ?e, "service_id" = service_id, "Failed to read stored service limits");
+            return None;
+        }
+    };
+    let get_u64 = |column: &str| row.get::<Option<i64>, _>(column).map(|value| value as u64);
+    let ret = ResourceLimits {
+        open_files: get_u64("open_files"),
+        core_size: get_u64("core_size"),
+        address_space: get_u64("address_space"),
+        cpu_seconds: get_u64("cpu_seconds"),
+        memory_max: get_u64("memory_max"),
+        cpu_max: row.get("cpu_max"),
+        pids_max: get_u64("pids_max"),
+    };
+    Some(ret)
+}
+
+/// Queries the lifecycle hooks for the given service from `service_hook`.
+///
+/// # Arguments
+///
+/// * `service_id` - Database id of the service.
+/// * `db_conn` - Active pool connection to the spindle DB.
+///
+/// # Returns
+///
+/// `Some(hooks)` on success, or `None` on error.
+async fn query_service_hooks(
+    service_id: u32,
+    db_conn: &mut PoolConnection<crate::db::SpindleDbType>,
+) -> Option<ServiceHooks> {
+    let query_result = sqlx::query(
+        "SELECT stage, command, timeout_secs FROM service_hook
+        WHERE service_id = $1",
+    )
+    .bind(service_id)
+    .fetch_all(db_conn.deref_mut())
+    .await;
+    let rows = match query_result {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!("error" = ?e, "service_id" = service_id, "Failed to read stored service hooks");
+            return None;
+        }
+    };
+    let mut ret = ServiceHooks::default();
+    for row in rows {
+        let stage: String = row.get("stage");
+        let Some(stage) = HookStage::from_name(&stage) else {
+            warn!(
+                "service_id" = service_id,
+                "stage" = stage,
+                "Unknown hook stage ignored"
+            );
+            continue;
+        };
+        *ret.get_mut(stage) = Some(HookCommand {
+            command: row.get("command"),
+            timeout_secs: row
+                .get::<Option<i64>, _>("timeout_secs")
+                .map(|value| value as u64),
+        });
+    }
+    Some(ret)
+}
+
+/// Queries the watched files for the given service from `service_watch` and
+/// `service_watch_pattern` (ordered by pattern_idx).
+///
+/// # Arguments
+///
+/// * `service_id` - Database id of the service.
+/// * `db_conn` - Active pool connection to the spindle DB.
+///
+/// # Returns
+///
+/// `Some(watch)` on success (`None` inside if the service watches nothing), or `None` on error.
+async fn query_service_watch(
+    service_id: u32,
+    db_conn: &mut PoolConnection<crate::db::SpindleDbType>,
+) -> Option<Option<WatchConfig>> {
+    let query_result = sqlx::query(
+        "SELECT debounce_ms, restart_dependents FROM service_watch
+        WHERE service_id = $1",
+    )
+    .bind(service_id)
+    .fetch_optional(db_conn.deref_mut())
+    .await;
+    let row = match query_result {
+        Ok(Some(row)) => row,
+        Ok(None) => return Some(None),
+        Err(e) => {
+            warn!("error" = ?e, "service_id" = service_id, "Failed to read stored service watch");
+            return None;
+        }
+    };
+    let mut ret = WatchConfig {
+        debounce_ms: row
+            .get::<Option<i64>, _>("debounce_ms")
+            .map(|value| value as u64),
+        restart_dependents: row.get("restart_dependents"),
+        ..Default::default()
+    };
+    let query_result = sqlx::query(
+        "SELECT kind, pattern FROM service_watch_pattern
+        WHERE service_id = $1
+        ORDER BY pattern_idx",
+    )
+    .bind(service_id)
+    .fetch_all(db_conn.deref_mut())
+    .await;
+    let rows = match query_result {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!("error" = ?e, "service_id" = service_id, "Failed to read stored service watch patterns");
+            return None;
+        }
+    };
+    for row in rows {
+        let kind: String = row.get("kind");
+        match kind.as_str() {
+            "path" => ret.paths.push(row.get("pattern")),
+            "ignore" => ret.ignore.push(row.get("pattern")),
+            _ => warn!(
+                "service_id" = service_id,
+                "kind" = kind,
+                "Unknown watch pattern kind ignored"
+            ),
+        }
+    }
+    Some(Some(ret))
+}
+
 /// Queries dependency ids for the given service from `service_dependency`.
 ///
 /// # Arguments
@@ -193,7 +513,168 @@ async fn query_service_dependency_ids(
     Some(ret)
 }
 
-/// Queries the group_id for the given service from `service_group_membership`.
+/// Queries the instance names of a template service from `service_instance`.
+///
+/// # Arguments
+///
+/// * `service_id` - Database id of the service.
+/// * `db_conn` - Active pool connection to the spindle DB.
+///
+/// # Returns
+///
+/// `Some(instances)` (ordered by instance_idx) on success, or `None` on error.
+async fn query_service_instances(
+    service_id: u32,
+    db_conn: &mut PoolConnection<crate::db::SpindleDbType>,
+) -> Option<Vec<String>> {
+    let query_result = sqlx::query(
+        "SELECT value FROM service_instance
+        WHERE service_id = $1
+        ORDER BY instance_idx",
+    )
+    .bind(service_id)
+    .fetch_all(db_conn.deref_mut())
+    .await;
+    match query_result {
+        Ok(rows) => Some(rows.into_iter().map(|row| row.get("value")).collect()),
+        Err(e) => {
+            warn!("error" = ?e, "service_id" = service_id, "Failed to read stored service instances");
+            None
+        }
+    }
+}
+
+/// Queries the dependencies on template instances for the given service from `service_instance_dependency`.
+///
+/// # Arguments
+///
+/// * `service_id` - Database id of the service.
+/// * `db_conn` - Active pool connection to the spindle DB.
+///
+/// # Returns
+///
+/// `Some(instance_dependencies)` as (template id, instance) on success, or `None` on error.
+async fn query_service_instance_dependencies(
+    service_id: u32,
+    db_conn: &mut PoolConnection<crate::db::SpindleDbType>,
+) -> Option<Vec<(u32, String)>> {
+    let query_result = sqlx::query(
+        "SELECT dependency_id, instance FROM service_instance_dependency
+        WHERE service_id = $1",
+    )
+    .bind(service_id)
+    .fetch_all(db_conn.deref_mut())
+    .await;
+    match query_result {
+        Ok(rows) => Some(
+            rows.into_iter()
+                .map(|row| (row.get("dependency_id"), row.get("instance")))
+                .collect(),
+        ),
+        Err(e) => {
+            warn!("error" = ?e, "service_id" = service_id, "Failed to read stored service instance dependencies");
+            None
+        }
+    }
+}
+
+/// Queries the profile overlays for the given service from `service_profile` and its detail tables.
+///
+/// # Arguments
+///
+/// * `service_id` - Database id of the service.
+/// * `db_conn` - Active pool connection to the spindle DB.
+///
+/// # Returns
+///
+/// `Some(profiles)` on success, or `None` on error.
+async fn query_service_profiles(
+    service_id: u32,
+    db_conn: &mut PoolConnection<crate::db::SpindleDbType>,
+) -> Option<BTreeMap<String, StoredProfile>> {
+    let query_result = async {
+        let mut ret: BTreeMap<String, StoredProfile> = BTreeMap::new();
+        let rows = sqlx::query(
+            "SELECT profile, workspace, replaces_args, replaces_dependencies FROM service_profile
+            WHERE service_id = $1",
+        )
+        .bind(service_id)
+        .fetch_all(db_conn.deref_mut())
+        .await?;
+        for row in rows {
+            let replaces_args: bool = row.get("replaces_args");
+            let replaces_dependencies: bool = row.get("replaces_dependencies");
+            let profile = StoredProfile {
+                args: replaces_args.then(Vec::new),
+                workspace: row.get("workspace"),
+                dependency_ids: replaces_dependencies.then(Vec::new),
+                ..Default::default()
+            };
+            ret.insert(row.get("profile"), profile);
+        }
+        let rows = sqlx::query(
+            "SELECT profile, value FROM service_profile_arg
+            WHERE service_id = $1
+            ORDER BY profile, arg_idx",
+        )
+        .bind(service_id)
+        .fetch_all(db_conn.deref_mut())
+        .await?;
+        for row in rows {
+            let profile: String = row.get("profile");
+            if let Some(args) = ret.get_mut(&profile).and_then(|p| p.args.as_mut()) {
+                args.push(row.get("value"));
+            }
+        }
+        let rows = sqlx::query(
+            "SELECT profile, key, value FROM service_profile_env
+            WHERE service_id = $1",
+        )
+        .bind(service_id)
+        .fetch_all(db_conn.deref_mut())
+        .await?;
+        for row in rows {
+            let profile: String = row.get("profile");
+            if let Some(stored) = ret.get_mut(&profile) {
+                stored.env.insert(row.get("key"), row.get("value"));
+            }
+        }
+        let rows = sqlx::query(
+            "SELECT profile, dependency_id, instance FROM service_profile_dependency
+            WHERE service_id = $1",
+        )
+        .bind(service_id)
+        .fetch_all(db_conn.deref_mut())
+        .await?;
+        for row in rows {
+            let profile: String = row.get("profile");
+            let Some(stored) = ret.get_mut(&profile) else {
+                continue;
+            };
+            let dependency_id: u32 = row.get("dependency_id");
+            let instance: String = row.get("instance");
+            if instance.is_empty() {
+                if let Some(dependency_ids) = stored.dependency_ids.as_mut() {
+                    dependency_ids.push(dependency_id);
+                }
+            } else {
+                stored.instance_dependencies.push((dependency_id, instance));
+            }
+        }
+        Ok::<_, sqlx::Error>(ret)
+    }
+    .await;
+    match query_result {
+        Ok(ret) => Some(ret),
+        Err(e) => {
+            warn!("error" = ?e, "service_id" = service_id, "Failed to read stored service profiles");
+            None
+        }
+    }
+}
+
+/// Queries the group_id for the given service from `service_group_membership`; for a template,
+/// the lowest group_id of its instances.
 ///
 /// # Arguments
 ///
@@ -209,7 +690,9 @@ async fn query_service_group_id(
 ) -> Option<u32> {
     let query_result = sqlx::query(
         "SELECT group_id FROM service_group_membership
-        WHERE service_id = $1",
+        WHERE service_id = $1
+        ORDER BY group_id
+        LIMIT 1",
     )
     .bind(service_id)
     .fetch_optional(db_conn.deref_mut())
@@ -256,10 +739,43 @@ async fn query_stored_service_config(
         Some(args) => args,
         None => return None,
     };
+    let env = match query_service_env(service_id, &mut db_conn).await {
+        Some(env) => env,
+        None => return None,
+    };
+    let ports = match query_service_ports(service_id, &mut db_conn).await {
+        Some(ports) => ports,
+        None => return None,
+    };
+    let profiles = match query_service_profiles(service_id, &mut db_conn).await {
+        Some(profiles) => profiles,
+        None => return None,
+    };
+    let limits = match query_service_limits(service_id, &mut db_conn).await {
+        Some(limits) => limits,
+        None => return None,
+    };
+    let hooks = match query_service_hooks(service_id, &mut db_conn).await {
+        Some(hooks) => hooks,
+        None => return None,
+    };
+    let watch = match query_service_watch(service_id, &mut db_conn).await {
+        Some(watch) => watch,
+        None => return None,
+    };
     let dependency_ids = match query_service_dependency_ids(service_id, &mut db_conn).await {
         Some(ids) => ids,
         None => return None,
     };
+    let instances = match query_service_instances(service_id, &mut db_conn).await {
+        Some(instances) => instances,
+        None => return None,
+    };
+    let instance_dependencies =
+        match query_service_instance_dependencies(service_id, &mut db_conn).await {
+            Some(instance_dependencies) => instance_dependencies,
+            None => return None,
+        };
     // For newly added services, group_id may not exist yet.
     // ServiceManager will automatically group services by dependencies,
     // and update_service_group_membership will update the correct group_id.
@@ -272,7 +788,23 @@ async fn query_stored_service_config(
         description: service_config_row.description,
         workspace: service_config_row.workspace,
         args,
+        env,
         dependency_ids,
+        instances,
+        instance_dependencies,
+        replicas: service_config_row.replicas,
+        quorum: service_config_row.quorum,
+        ports,
+        profiles,
+        user: service_config_row.user,
+        group: service_config_row.group,
+        umask: service_config_row.umask,
+        detached: service_config_row.detached,
+        schedule: service_config_row.schedule,
+        overlap: service_config_row.overlap,
+        watch,
+        limits,
+        hooks,
         group_id,
     };
     Some(ret)
@@ -309,6 +841,28 @@ async fn query_all_service_id(app: &tauri::AppHandle) -> Vec<u32> {
     ret
 }
 
+/// Loads every service in the database as a [StoredServiceConfig].
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+///
+/// # Returns
+///
+/// All services that could be fully loaded; incomplete services are skipped.
+pub(crate) async fn query_all_stored_service_config(
+    app: &tauri::AppHandle,
+) -> Vec<StoredServiceConfig> {
+    let service_ids = query_all_service_id(app).await;
+    let mut ret = Vec::with_capacity(service_ids.len());
+    for service_id in service_ids {
+        if let Some(config) = query_stored_service_config(app, service_id).await {
+            ret.push(config);
+        }
+    }
+    ret
+}
+
 /// Queries the database id of a service by (name, version).
 ///
 /// # Arguments
@@ -320,7 +874,7 @@ async fn query_all_service_id(app: &tauri::AppHandle) -> Vec<u32> {
 /// # Returns
 ///
 /// `Some(service_id)` if found, or `None` if not found or on error.
-async fn query_service_id_by_name_and_version(
+pub(crate) async fn query_service_id_by_name_and_version(
     app: &tauri::AppHandle,
     name: &str,
     version: &str,
@@ -347,18 +901,112 @@ async fn query_service_id_by_name_and_version(
     }
 }
 
-/// Inserts a new service into the database (service, service_config, service_arg, service_dependency).
+/// Queries the database id of the stored service that runs as (name, version).
+///
+/// Instances of a template (e.g. `worker@emails`) are stored as their template (`worker@`),
+/// replicas (e.g. `api#2`) as their service (`api`).
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `name` - Service name as known to [ServiceManager].
+/// * `version` - Service version.
+///
+/// # Returns
+///
+/// `Some(service_id)` if found, else `None`.
+pub(crate) async fn query_stored_service_id_for_key(
+    app: &tauri::AppHandle,
+    name: &str,
+    version: &str,
+) -> Option<u32> {
+    query_stored_service_instance_for_key(app, name, version)
+        .await
+        .map(|(service_id, _)| service_id)
+}
+
+/// Like [query_stored_service_id_for_key], also returning the instance name for an instance of
+/// a template.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `name` - Service name as known to [ServiceManager].
+/// * `version` - Service version.
+///
+/// # Returns
+///
+/// `Some((service_id, instance))` if found, with an empty instance for a service that is not a
+/// template instance; else `None`.
+async fn query_stored_service_instance_for_key<'a>(
+    app: &tauri::AppHandle,
+    name: &'a str,
+    version: &str,
+) -> Option<(u32, &'a str)> {
+    let name = split_replica_name(name).map_or(name, |(name, _)| name);
+    if let Some(service_id) = query_service_id_by_name_and_version(app, name, version).await {
+        return Some((service_id, ""));
+    }
+    let (template, instance) = split_instance_name(name)?;
+    query_service_id_by_name_and_version(app, template, version)
+        .await
+        .map(|service_id| (service_id, instance))
+}
+
+/// Settings of a service stored outside the `service` table, borrowed for writing.
+pub(crate) struct ServiceDetail<'a> {
+    /// Executable program path.
+    pub program: &'a str,
+    /// Optional description.
+    pub description: Option<&'a str>,
+    /// Optional workspace path.
+    pub workspace: Option<&'a str>,
+    /// Startup arguments.
+    pub args: &'a [String],
+    /// Extra environment variables.
+    pub env: &'a BTreeMap<String, String>,
+    /// Database ids of dependency services.
+    pub dependency_ids: &'a [u32],
+    /// Instance names if the service is a template.
+    pub instances: &'a [String],
+    /// Dependencies on single instances of template services, as (template id, instance).
+    pub instance_dependencies: &'a [(u32, String)],
+    /// Number of replicas, if replicated.
+    pub replicas: Option<u32>,
+    /// Replica quorum, if set.
+    pub quorum: Option<u32>,
+    /// Named ports.
+    pub ports: &'a BTreeMap<String, PortSpec>,
+    /// Profile overlays by profile name.
+    pub profiles: &'a BTreeMap<String, StoredProfile>,
+    /// User to run as.
+    pub user: Option<&'a str>,
+    /// Group to run as.
+    pub group: Option<&'a str>,
+    /// Octal umask.
+    pub umask: Option<&'a str>,
+    /// Whether the service runs detached.
+    pub detached: bool,
+    /// Cron schedule, if scheduled.
+    pub schedule: Option<&'a str>,
+    /// Overlap policy of scheduled runs.
+    pub overlap: OverlapPolicy,
+    /// Watched files, if any.
+    pub watch: Option<&'a WatchConfig>,
+    /// Rlimits and cgroup limits.
+    pub limits: &'a ResourceLimits,
+    /// Lifecycle hooks.
+    pub hooks: &'a ServiceHooks,
+}
+
+/// Inserts a new service into the database (service and all detail tables).
 ///
 /// # Arguments
 ///
 /// * `app` - Tauri app handle for DB access.
 /// * `name` - Service name.
 /// * `version` - Service version.
-/// * `program` - Executable program path.
-/// * `description` - Optional description.
-/// * `workspace` - Optional workspace path.
-/// * `args` - Startup arguments.
-/// * `dependency_ids` - Database ids of dependency services.
+/// * `detail` - Program, description, workspace, args, env, instances and dependencies.
 ///
 /// # Returns
 ///
@@ -367,39 +1015,84 @@ async fn insert_stored_service_config(
     app: &tauri::AppHandle,
     name: &str,
     version: &str,
-    program: &str,
-    description: Option<&str>,
-    workspace: Option<&str>,
-    args: &[String],
-    dependency_ids: &[u32],
+    detail: &ServiceDetail<'_>,
 ) -> anyhow::Result<u32> {
     let mut db_conn = crate::db::acquire_spindle_db_conn(app)
         .await
         .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
     let mut tx = db_conn.begin().await?;
-    let service_id =
-        match sqlx::query("INSERT INTO service (name, version) VALUES ($1, $2) RETURNING id")
-            .bind(name)
-            .bind(version)
-            .fetch_one(tx.deref_mut())
-            .await
-        {
-            Ok(row) => row.get::<'_, i64, &str>("id") as u32,
-            Err(e) => {
-                warn!("error" = ?e, "name" = name, "version" = version, "Failed to insert service");
-                anyhow::bail!("Failed to insert service");
-            }
-        };
+    let service_id = insert_service_row(&mut tx, name, version).await?;
+    write_service_detail_rows(&mut tx, service_id, detail).await?;
+    tx.commit().await?;
+    Ok(service_id)
+}
+
+/// Within a transaction, inserts a row into the `service` table.
+///
+/// # Arguments
+///
+/// * `tx` - Active SQLite transaction.
+/// * `name` - Service name.
+/// * `version` - Service version.
+///
+/// # Returns
+///
+/// `Ok(service_id)` with the newly assigned id, or an error (e.g. (name, version) already exists).
+pub(crate) async fn insert_service_row(
+    tx: &mut Transaction<'_, Sqlite>,
+    name: &str,
+    version: &str,
+) -> anyhow::Result<u32> {
+    match sqlx::query("INSERT INTO service (name, version) VALUES ($1, $2) RETURNING id")
+        .bind(name)
+        .bind(version)
+        .fetch_one(tx.deref_mut())
+        .await
+    {
+        Ok(row) => Ok(row.get::<'_, i64, &str>("id") as u32),
+        Err(e) => {
+            warn!("error" = ?e, "name" = name, "version" = version, "Failed to insert service");
+            anyhow::bail!("Failed to insert service");
+        }
+    }
+}
+
+/// Within a transaction, inserts the detail rows of a service (`service_config`, `service_arg`,
+/// `service_env`, `service_port`, `service_limit`, `service_hook`, `service_watch*`, `service_profile*`, `service_instance`, `service_dependency` and
+/// `service_instance_dependency`).
+///
+/// # Arguments
+///
+/// * `tx` - Active SQLite transaction.
+/// * `service_id` - Database id of the service; its detail rows must not exist yet.
+/// * `detail` - Program, description, workspace, args, env, instances and dependencies.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error.
+pub(crate) async fn write_service_detail_rows(
+    tx: &mut Transaction<'_, Sqlite>,
+    service_id: u32,
+    detail: &ServiceDetail<'_>,
+) -> anyhow::Result<()> {
     sqlx::query(
-        "INSERT INTO service_config (service_id, program, description, workspace) VALUES ($1, $2, $3, $4)"
+        "INSERT INTO service_config (service_id, program, description, workspace, replicas, quorum, user, group_name, umask, detached, schedule, schedule_overlap) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)"
     )
     .bind(service_id)
-    .bind(program)
-    .bind(description)
-    .bind(workspace)
+    .bind(detail.program)
+    .bind(detail.description)
+    .bind(detail.workspace)
+    .bind(detail.replicas)
+    .bind(detail.quorum)
+    .bind(detail.user)
+    .bind(detail.group)
+    .bind(detail.umask)
+    .bind(detail.detached)
+    .bind(detail.schedule)
+    .bind(detail.overlap.as_str())
     .execute(tx.deref_mut())
     .await?;
-    for (arg_idx, arg) in args.iter().enumerate() {
+    for (arg_idx, arg) in detail.args.iter().enumerate() {
         sqlx::query("INSERT INTO service_arg (service_id, arg_idx, value) VALUES ($1, $2, $3)")
             .bind(service_id)
             .bind(arg_idx as u32)
@@ -407,15 +1100,360 @@ async fn insert_stored_service_config(
             .execute(tx.deref_mut())
             .await?;
     }
-    for dependency_id in dependency_ids {
+    for (key, value) in detail.env {
+        sqlx::query("INSERT INTO service_env (service_id, key, value) VALUES ($1, $2, $3)")
+            .bind(service_id)
+            .bind(key)
+            .bind(value)
+            .execute(tx.deref_mut())
+            .await?;
+    }
+    if detail.limits != &ResourceLimits::default() {
+        let limits = detail.limits;
+        let to_i64 = |value: Option<u64>| value.map(|value| value.min(i64::MAX as u64) as i64);
+        sqlx::query(
+            "INSERT INTO service_limit (service_id, open_files, core_size, address_space, cpu_seconds, memory_max, cpu_max, pids_max) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(service_id)
+        .bind(to_i64(limits.open_files))
+        .bind(to_i64(limits.core_size))
+        .bind(to_i64(limits.address_space))
+        .bind(to_i64(limits.cpu_seconds))
+        .bind(to_i64(limits.memory_max))
+        .bind(limits.cpu_max)
+        .bind(to_i64(limits.pids_max))
+        .execute(tx.deref_mut())
+        .await?;
+    }
+    for stage in HookStage::ALL {
+        let Some(hook) = detail.hooks.get(stage) else {
+            continue;
+        };
+        sqlx::query(
+            "INSERT INTO service_hook (service_id, stage, command, timeout_secs) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(service_id)
+        .bind(stage.as_str())
+        .bind(&hook.command)
+        .bind(
+            hook.timeout_secs
+                .map(|value| value.min(i64::MAX as u64) as i64),
+        )
+        .execute(tx.deref_mut())
+        .await?;
+    }
+    if let Some(watch) = detail.watch {
+        sqlx::query(
+            "INSERT INTO service_watch (service_id, debounce_ms, restart_dependents) VALUES ($1, $2, $3)",
+        )
+        .bind(service_id)
+        .bind(
+            watch
+                .debounce_ms
+                .map(|value| value.min(i64::MAX as u64) as i64),
+        )
+        .bind(watch.restart_dependents)
+        .execute(tx.deref_mut())
+        .await?;
+        let patterns = watch
+            .paths
+            .iter()
+            .map(|pattern| ("path", pattern))
+            .chain(watch.ignore.iter().map(|pattern| ("ignore", pattern)));
+        for (pattern_idx, (kind, pattern)) in patterns.enumerate() {
+            sqlx::query(
+                "INSERT INTO service_watch_pattern (service_id, kind, pattern_idx, pattern) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(service_id)
+            .bind(kind)
+            .bind(pattern_idx as u32)
+            .bind(pattern)
+            .execute(tx.deref_mut())
+            .await?;
+        }
+    }
+    for (name, spec) in detail.ports {
+        let port = match spec {
+            PortSpec::Auto => None,
+            PortSpec::Fixed(port) => Some(*port),
+        };
+        sqlx::query("INSERT INTO service_port (service_id, name, port) VALUES ($1, $2, $3)")
+            .bind(service_id)
+            .bind(name)
+            .bind(port)
+            .execute(tx.deref_mut())
+            .await?;
+    }
+    for (profile, stored) in detail.profiles {
+        sqlx::query(
+            "INSERT INTO service_profile (service_id, profile, workspace, replaces_args, replaces_dependencies) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(service_id)
+        .bind(profile)
+        .bind(stored.workspace.as_deref())
+        .bind(stored.args.is_some())
+        .bind(stored.dependency_ids.is_some())
+        .execute(tx.deref_mut())
+        .await?;
+        for (arg_idx, arg) in stored.args.iter().flatten().enumerate() {
+            sqlx::query(
+                "INSERT INTO service_profile_arg (service_id, profile, arg_idx, value) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(service_id)
+            .bind(profile)
+            .bind(arg_idx as u32)
+            .bind(arg)
+            .execute(tx.deref_mut())
+            .await?;
+        }
+        for (key, value) in &stored.env {
+            sqlx::query(
+                "INSERT INTO service_profile_env (service_id, profile, key, value) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(service_id)
+            .bind(profile)
+            .bind(key)
+            .bind(value)
+            .execute(tx.deref_mut())
+            .await?;
+        }
+        if stored.dependency_ids.is_none() {
+            continue;
+        }
+        let dependencies = stored
+            .dependency_ids
+            .iter()
+            .flatten()
+            .map(|dependency_id| (*dependency_id, ""))
+            .chain(
+                stored
+                    .instance_dependencies
+                    .iter()
+                    .map(|(dependency_id, instance)| (*dependency_id, instance.as_str())),
+            );
+        for (dependency_id, instance) in dependencies {
+            sqlx::query(
+                "INSERT INTO service_profile_dependency (service_id, profile, dependency_id, instance) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(service_id)
+            .bind(profile)
+            .bind(dependency_id)
+            .bind(instance)
+            .execute(tx.deref_mut())
+            .await?;
+        }
+    }
+    for (instance_idx, instance) in detail.instances.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO service_instance (service_id, instance_idx, value) VALUES ($1, $2, $3)",
+        )
+        .bind(service_id)
+        .bind(instance_idx as u32)
+        .bind(instance)
+        .execute(tx.deref_mut())
+        .await?;
+    }
+    for dependency_id in detail.dependency_ids {
         sqlx::query("INSERT INTO service_dependency (service_id, dependency_id) VALUES ($1, $2)")
             .bind(service_id)
-            .bind(dependency_id)
+            .bind(dependency_id)
+            .execute(tx.deref_mut())
+            .await?;
+    }
+    for (dependency_id, instance) in detail.instance_dependencies {
+        sqlx::query(
+            "INSERT INTO service_instance_dependency (service_id, dependency_id, instance) VALUES ($1, $2, $3)",
+        )
+        .bind(service_id)
+        .bind(dependency_id)
+        .bind(instance)
+        .execute(tx.deref_mut())
+        .await?;
+    }
+    Ok(())
+}
+
+/// Within a transaction, searches for a dependency path from any of `dependency_ids` back to
+/// `service_id`, along the dependencies active in `profile`.
+///
+/// # Arguments
+///
+/// * `tx` - Active SQLite transaction.
+/// * `service_id` - Database id of the service whose dependencies are being set.
+/// * `profile` - Profile whose dependencies are followed; `None` for the base dependencies.
+/// * `dependency_ids` - Proposed dependency ids of the service in `profile`, including templates of instance dependencies.
+///
+/// # Returns
+///
+/// `Ok(Some(path))` with the service ids of the cycle (starting and ending at `service_id`) if
+/// the proposed dependencies would create one, `Ok(None)` otherwise.
+async fn find_dependency_cycle(
+    tx: &mut Transaction<'_, Sqlite>,
+    service_id: u32,
+    profile: Option<&str>,
+    dependency_ids: &[u32],
+) -> anyhow::Result<Option<Vec<u32>>> {
+    // Depth-first search along dependency edges; each stack entry carries its path from service_id.
+    let mut visited: HashSet<u32> = HashSet::new();
+    let mut stack: Vec<Vec<u32>> = dependency_ids
+        .iter()
+        .map(|dependency_id| vec![service_id, *dependency_id])
+        .collect();
+    while let Some(path) = stack.pop() {
+        let Some(&cur) = path.last() else {
+            continue;
+        };
+        if cur == service_id {
+            return Ok(Some(path));
+        }
+        if !visited.insert(cur) {
+            continue;
+        }
+        // A profile that replaces the dependencies of a service hides its base dependencies.
+        let next_ids: Vec<u32> = sqlx::query(
+            "SELECT dependency_id FROM service_profile_dependency WHERE service_id = $1 AND profile = $2
+            UNION SELECT dependency_id FROM service_dependency WHERE service_id = $1 AND NOT EXISTS (
+                SELECT 1 FROM service_profile WHERE service_id = $1 AND profile = $2 AND replaces_dependencies = 1)
+            UNION SELECT dependency_id FROM service_instance_dependency WHERE service_id = $1 AND NOT EXISTS (
+                SELECT 1 FROM service_profile WHERE service_id = $1 AND profile = $2 AND replaces_dependencies = 1)",
+        )
+        .bind(cur)
+        .bind(profile)
+        .fetch_all(tx.deref_mut())
+        .await?
+        .into_iter()
+        .map(|row| row.get("dependency_id"))
+        .collect();
+        for next_id in next_ids {
+            let mut next_path = path.clone();
+            next_path.push(next_id);
+            stack.push(next_path);
+        }
+    }
+    Ok(None)
+}
+
+/// Rewrites the detail rows of an existing service in one transaction.
+///
+/// The name and version are kept. The update is rejected if the new dependencies would create a
+/// cycle in the base config or in any profile, each with the dependencies active in it.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `service_id` - Database id of the service to update.
+/// * `detail` - Program, description, workspace, args, env, instances and dependencies.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the service does not exist, a cycle is found, or on DB error.
+async fn update_stored_service_config(
+    app: &tauri::AppHandle,
+    service_id: u32,
+    detail: &ServiceDetail<'_>,
+) -> anyhow::Result<()> {
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    let mut tx = db_conn.begin().await?;
+    let exists = sqlx::query("SELECT id FROM service WHERE id = $1")
+        .bind(service_id)
+        .fetch_optional(tx.deref_mut())
+        .await?
+        .is_some();
+    if !exists {
+        anyhow::bail!("Service not found: {}", service_id);
+    }
+    let base_dependency_ids: Vec<u32> = detail
+        .dependency_ids
+        .iter()
+        .copied()
+        .chain(detail.instance_dependencies.iter().map(|(id, _)| *id))
+        .collect();
+    let mut profiles: Vec<Option<String>> = vec![None];
+    let stored_profiles: Vec<String> = sqlx::query(
+        "SELECT DISTINCT profile FROM service_profile WHERE replaces_dependencies = 1 ORDER BY profile",
+    )
+    .fetch_all(tx.deref_mut())
+    .await?
+    .into_iter()
+    .map(|row| row.get("profile"))
+    .collect();
+    for profile in detail.profiles.keys().cloned().chain(stored_profiles) {
+        if !profiles.contains(&Some(profile.clone())) {
+            profiles.push(Some(profile));
+        }
+    }
+    for profile in profiles {
+        let dependency_ids: Vec<u32> = match profile
+            .as_ref()
+            .and_then(|profile| detail.profiles.get(profile))
+            .filter(|stored| stored.dependency_ids.is_some())
+        {
+            Some(stored) => stored
+                .dependency_ids
+                .iter()
+                .flatten()
+                .copied()
+                .chain(stored.instance_dependencies.iter().map(|(id, _)| *id))
+                .collect(),
+            None => base_dependency_ids.clone(),
+        };
+        let Some(cycle) =
+            find_dependency_cycle(&mut tx, service_id, profile.as_deref(), &dependency_ids).await?
+        else {
+            continue;
+        };
+        let mut cycle_names = Vec::with_capacity(cycle.len());
+        for id in cycle {
+            let name = match sqlx::query("SELECT name, version FROM service WHERE id = $1")
+                .bind(id)
+                .fetch_optional(tx.deref_mut())
+                .await?
+            {
+                Some(row) => format!(
+                    "{}:v{}",
+                    row.get::<'_, String, &str>("name"),
+                    row.get::<'_, String, &str>("version")
+                ),
+                None => id.to_string(),
+            };
+            cycle_names.push(name);
+        }
+        warn!("service_id" = service_id, "profile" = ?profile, "cycle" = ?cycle_names, "Dependency cycle rejected");
+        match profile {
+            Some(profile) => anyhow::bail!(
+                "Dependency cycle in profile {profile}: {}",
+                cycle_names.join(" -> ")
+            ),
+            None => anyhow::bail!("Dependency cycle: {}", cycle_names.join(" -> ")),
+        }
+    }
+    for table in [
+        "service_config",
+        "service_arg",
+        "service_env",
+        "service_port",
+        "service_limit",
+        "service_hook",
+        "service_watch",
+        "service_watch_pattern",
+        "service_profile",
+        "service_profile_arg",
+        "service_profile_env",
+        "service_profile_dependency",
+        "service_instance",
+        "service_dependency",
+        "service_instance_dependency",
+    ] {
+        sqlx::query(&format!("DELETE FROM {table} WHERE service_id = $1"))
+            .bind(service_id)
             .execute(tx.deref_mut())
             .await?;
     }
+    write_service_detail_rows(&mut tx, service_id, detail).await?;
     tx.commit().await?;
-    Ok(service_id)
+    Ok(())
 }
 
 /// Removes the service with the given `service_id` from the database.
@@ -445,7 +1483,7 @@ async fn remove_stored_service_config(
     Ok(())
 }
 
-/// Builds a [ServiceManager] from the given [StoredServiceConfig] list (including dependency name/version mapping).
+/// Converts [StoredServiceConfig]s to core [ServiceConfig]s, mapping dependency ids to (name, version).
 ///
 /// # Arguments
 ///
@@ -453,10 +1491,8 @@ async fn remove_stored_service_config(
 ///
 /// # Returns
 ///
-/// `Ok(Arc<ServiceManager>)` on success, or an error.
-async fn create_service_manager(
-    configs: &[StoredServiceConfig],
-) -> anyhow::Result<Arc<ServiceManager>> {
+/// One [ServiceConfig] per stored config; dependency ids not in `configs` are dropped.
+pub(crate) fn to_service_configs(configs: &[StoredServiceConfig]) -> Vec<ServiceConfig> {
     let service_id_key_map: HashMap<u32, (String, String)> = configs
         .iter()
         .map(|config| {
@@ -468,24 +1504,151 @@ async fn create_service_manager(
         .collect();
     let mut service_configs = Vec::with_capacity(configs.len());
     for config in configs {
-        let mut dependencies = Vec::with_capacity(config.dependency_ids.len());
-        for dependency_id in config.dependency_ids.iter() {
-            if let Some((dep_name, dep_version)) = service_id_key_map.get(dependency_id) {
-                dependencies.push((dep_name.clone(), dep_version.clone()));
-            }
-        }
-        let service_config = spindle_core::service::ServiceConfig {
+        let dependencies = dependency_keys(
+            &config.dependency_ids,
+            &config.instance_dependencies,
+            &service_id_key_map,
+        );
+        let profiles = config
+            .profiles
+            .iter()
+            .map(|(profile, stored)| {
+                (
+                    profile.clone(),
+                    profile_overlay(stored, &service_id_key_map),
+                )
+            })
+            .collect();
+        let service_config = ServiceConfig {
             name: config.name.clone(),
             version: config.version.clone(),
             program: config.program.clone().into(),
             args: config.args.clone(),
+            env: config.env.clone(),
             dependencies,
             workspace: config.workspace.as_ref().map(|workspace| workspace.into()),
+            instances: config.instances.clone(),
+            replicas: config.replicas,
+            quorum: config.quorum,
+            ports: config.ports.clone(),
+            profiles,
+            user: config.user.clone(),
+            group: config.group.clone(),
+            umask: config.umask.clone(),
+            detached: config.detached,
+            schedule: config.schedule.clone(),
+            overlap: config.overlap,
+            watch: config.watch.clone(),
+            limits: config.limits.clone(),
+            hooks: config.hooks.clone(),
         };
         service_configs.push(service_config);
     }
-    Ok(spindle_core::service::ServiceManager::from_configs(
-        service_configs,
+    service_configs
+}
+
+/// Maps dependency ids and instance dependencies to (name, version); unknown ids are dropped.
+fn dependency_keys(
+    dependency_ids: &[u32],
+    instance_dependencies: &[(u32, String)],
+    service_id_key_map: &HashMap<u32, (String, String)>,
+) -> Vec<(String, String)> {
+    let mut ret = Vec::with_capacity(dependency_ids.len() + instance_dependencies.len());
+    for dependency_id in dependency_ids {
+        if let Some((dep_name, dep_version)) = service_id_key_map.get(dependency_id) {
+            ret.push((dep_name.clone(), dep_version.clone()));
+        }
+    }
+    for (dependency_id, instance) in instance_dependencies {
+        if let Some((dep_name, dep_version)) = service_id_key_map.get(dependency_id) {
+            ret.push((format!("{dep_name}{instance}"), dep_version.clone()));
+        }
+    }
+    ret
+}
+
+/// Converts a [StoredProfile] to a [ProfileOverlay] with dependencies as (name, version).
+///
+/// # Arguments
+///
+/// * `stored` - Stored profile overlay.
+/// * `service_id_key_map` - Map from service id to (name, version); unknown ids are dropped.
+///
+/// # Returns
+///
+/// The [ProfileOverlay].
+pub(crate) fn profile_overlay(
+    stored: &StoredProfile,
+    service_id_key_map: &HashMap<u32, (String, String)>,
+) -> ProfileOverlay {
+    ProfileOverlay {
+        args: stored.args.clone(),
+        env: stored.env.clone(),
+        workspace: stored.workspace.as_ref().map(|workspace| workspace.into()),
+        dependencies: stored.dependency_ids.as_ref().map(|dependency_ids| {
+            dependency_keys(
+                dependency_ids,
+                &stored.instance_dependencies,
+                service_id_key_map,
+            )
+        }),
+    }
+}
+
+/// Builds a [ServiceManager] from the given [StoredServiceConfig] list (including dependency name/version mapping).
+///
+/// # Arguments
+///
+/// * `configs` - Slice of stored service configs already loaded from the DB.
+/// * `options` - Manager options (e.g. global variables).
+///
+/// # Returns
+///
+/// `Ok(Arc<ServiceManager>)` on success, or an error.
+async fn create_service_manager(
+    configs: &[StoredServiceConfig],
+    options: ServiceManagerOptions,
+) -> anyhow::Result<Arc<ServiceManager>> {
+    Ok(ServiceManager::from_configs_with_options(
+        to_service_configs(configs),
+        options,
+    ))
+}
+
+/// Validates a new service against the services already in the database.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `proposed` - Config of the service about to be added.
+///
+/// # Returns
+///
+/// `Ok(())` if the proposed service would be accepted by [ServiceManager], or `Err(message)`
+/// listing every reason it would be rejected.
+async fn validate_proposed_service(
+    app: &tauri::AppHandle,
+    proposed: ServiceConfig,
+) -> Result<(), String> {
+    let stored_configs = query_all_stored_service_config(app).await;
+    let mut service_configs = to_service_configs(&stored_configs);
+    let (name, version) = (proposed.name.clone(), proposed.version.clone());
+    service_configs.push(proposed);
+    let options = crate::variable::service_manager_options(app).await;
+    let report = validate_configs_with_options(&service_configs, &options);
+    let reasons: Vec<&str> = report
+        .issues_for(&name, &version)
+        .map(|issue| issue.reason.as_str())
+        .collect();
+    if reasons.is_empty() {
+        return Ok(());
+    }
+    warn!("name" = name, "version" = version, "reasons" = ?reasons, "Service rejected by validation");
+    Err(format!(
+        "Invalid service {}:v{}: {}",
+        name,
+        version,
+        reasons.join("; ")
     ))
 }
 
@@ -577,30 +1740,18 @@ async fn migrate_group_alias(
             .collect();
     let mut prev: Vec<(&str, Vec<String>)> = Vec::with_capacity(group_aliases.len());
     for (alias, group_id) in group_aliases.iter() {
-        let service_ids: Vec<u32> =
-            sqlx::query("SELECT service_id FROM service_group_membership WHERE group_id = $1")
-                .bind(*group_id)
-                .fetch_all(tx.deref_mut())
-                .await?
-                .into_iter()
-                .map(|row| row.get("service_id"))
-                .collect();
-        let mut service_names = Vec::with_capacity(service_ids.len());
-        for service_id in service_ids {
-            let service_name: String = match sqlx::query("SELECT name FROM service WHERE id = $1")
-                .bind(service_id)
-                .fetch_optional(tx.deref_mut())
-                .await
-            {
-                Ok(Some(row)) => row.get("name"),
-                Ok(None) => continue,
-                Err(e) => {
-                    warn!("error" = ?e, "service_id" = service_id, "Failed to query service name");
-                    continue;
-                }
-            };
-            service_names.push(service_name);
-        }
+        // Instances of a template are named as ServiceManager names them, e.g. `worker@emails`.
+        let mut service_names: Vec<String> = sqlx::query(
+            "SELECT s.name || m.instance AS name FROM service_group_membership m
+            JOIN service s ON s.id = m.service_id
+            WHERE m.group_id = $1",
+        )
+        .bind(*group_id)
+        .fetch_all(tx.deref_mut())
+        .await?
+        .into_iter()
+        .map(|row| row.get("name"))
+        .collect();
         service_names.sort();
         prev.push((alias.as_str(), service_names));
     }
@@ -658,19 +1809,20 @@ async fn update_service_group_membership(app: &tauri::AppHandle) -> anyhow::Resu
     for group_idx in 0..group_num {
         let services = service_manager.group_service_keys(group_idx);
         for (name, version) in services {
-            let service_id = match query_service_id_by_name_and_version(app, &name, &version).await
-            {
-                Some(id) => id,
-                None => {
-                    warn!("name" = name, "version" = version, "Service not found");
-                    continue;
-                }
-            };
-            // group_idx as group_id
+            let (service_id, instance) =
+                match query_stored_service_instance_for_key(app, &name, &version).await {
+                    Some(found) => found,
+                    None => {
+                        warn!("name" = name, "version" = version, "Service not found");
+                        continue;
+                    }
+                };
+            // group_idx as group_id; each instance of a template has its own row
             sqlx::query(
-                "INSERT INTO service_group_membership (service_id, group_id) VALUES ($1, $2)",
+                "INSERT OR IGNORE INTO service_group_membership (service_id, instance, group_id) VALUES ($1, $2, $3)",
             )
             .bind(service_id)
+            .bind(instance)
             .bind(group_idx as u32)
             .execute(tx.deref_mut())
             .await?;
@@ -717,7 +1869,7 @@ async fn insert_group_alias(
 /// # Returns
 ///
 /// `Some(alias)` if found, or `None` if not found or on error.
-async fn query_group_alias(app: &tauri::AppHandle, group_id: u32) -> Option<String> {
+pub(crate) async fn query_group_alias(app: &tauri::AppHandle, group_id: u32) -> Option<String> {
     let mut db_conn = match crate::db::acquire_spindle_db_conn(app).await {
         Some(conn) => conn,
         None => return None,
@@ -794,9 +1946,11 @@ pub struct GroupInfo {
     pub alias: Option<String>,
     /// Services in the group.
     pub services: Vec<StoredServiceConfig>,
+    /// Active profile of the group; `None` runs the base configs.
+    pub profile: Option<String>,
 }
 
-/// Collects [GroupInfo] for a group: group_id, alias, and stored service configs.
+/// Collects [GroupInfo] for a group: group_id, alias, stored service configs and active profile.
 ///
 /// # Arguments
 ///
@@ -816,8 +1970,11 @@ async fn collect_group_info(
 ) -> GroupInfo {
     let service_keys = service_manager.group_service_keys(group_id);
     let mut services = Vec::with_capacity(service_keys.len());
+    let mut seen_service_ids = HashSet::new();
     for (name, version) in service_keys {
-        if let Some(service_id) = query_service_id_by_name_and_version(app, &name, &version).await {
+        if let Some(service_id) = query_stored_service_id_for_key(app, &name, &version).await
+            && seen_service_ids.insert(service_id)
+        {
             if let Some(config) = query_stored_service_config(app, service_id).await {
                 services.push(config);
             }
@@ -827,6 +1984,7 @@ async fn collect_group_info(
         group_id: group_id as u32,
         alias,
         services,
+        profile: service_manager.group_profile(group_id),
     }
 }
 
@@ -885,13 +2043,110 @@ async fn unaliased_group_service(app: &tauri::AppHandle) -> Vec<GroupInfo> {
     ret
 }
 
+/// Resolves dependencies given as (name, version) to their database ids.
+///
+/// A name such as `worker@emails` that is not a stored service resolves to the template
+/// `worker@` and becomes an instance dependency.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `dependencies` - List of (name, version) for dependencies.
+///
+/// # Returns
+///
+/// `Ok((dependency_ids, instance_dependencies))` on success, or `Err(message)` naming the first
+/// dependency not found.
+async fn resolve_dependencies(
+    app: &tauri::AppHandle,
+    dependencies: Vec<(String, String)>,
+) -> Result<(Vec<u32>, Vec<(u32, String)>), String> {
+    let mut dependency_ids = Vec::with_capacity(dependencies.len());
+    let mut instance_dependencies = Vec::new();
+    for (dep_name, dep_version) in dependencies {
+        if let Some(dep_id) =
+            query_service_id_by_name_and_version(app, &dep_name, &dep_version).await
+        {
+            dependency_ids.push(dep_id);
+            continue;
+        }
+        let instance_dependency = match split_instance_name(&dep_name) {
+            Some((template, instance)) => {
+                query_service_id_by_name_and_version(app, template, &dep_version)
+                    .await
+                    .map(|template_id| (template_id, instance.to_string()))
+            }
+            None => None,
+        };
+        match instance_dependency {
+            Some(instance_dependency) => instance_dependencies.push(instance_dependency),
+            None => {
+                warn!(
+                    "dep_name" = dep_name,
+                    "dep_version" = dep_version,
+                    "Dependency not found"
+                );
+                return Err(format!(
+                    "Dependency not found: {}:v{}",
+                    dep_name, dep_version
+                ));
+            }
+        }
+    }
+    Ok((dependency_ids, instance_dependencies))
+}
+
+/// Resolves the dependencies of profile overlays to ids, see [resolve_dependencies].
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `profiles` - Profile overlays with dependencies as (name, version).
+///
+/// # Returns
+///
+/// `Ok(profiles)` ready to store, or `Err(message)` if a dependency is not found.
+async fn resolve_profiles(
+    app: &tauri::AppHandle,
+    profiles: BTreeMap<String, ProfileOverlay>,
+) -> Result<BTreeMap<String, StoredProfile>, String> {
+    let mut ret = BTreeMap::new();
+    for (profile, overlay) in profiles {
+        if profile.is_empty() {
+            return Err("Profile name must not be empty".to_string());
+        }
+        let (dependency_ids, instance_dependencies) = match overlay.dependencies {
+            Some(dependencies) => {
+                let (dependency_ids, instance_dependencies) =
+                    resolve_dependencies(app, dependencies).await?;
+                (Some(dependency_ids), instance_dependencies)
+            }
+            None => (None, Vec::new()),
+        };
+        let stored = StoredProfile {
+            args: overlay.args,
+            env: overlay.env,
+            workspace: overlay
+                .workspace
+                .map(|workspace| workspace.to_string_lossy().into_owned()),
+            dependency_ids,
+            instance_dependencies,
+        };
+        ret.insert(profile, stored);
+    }
+    Ok(ret)
+}
+
 /// Tauri commands exposed to the frontend: service add/remove, reload, group membership and aliases.
 pub mod tauri_cmd {
+    use std::collections::BTreeMap;
+
     use tauri::Manager;
     use tokio::sync::Mutex;
     use tracing::{info, warn};
 
     /// Adds a new service and persists it to the database; dependencies are given as (name, version) and resolved to dependency_ids.
+    /// The service is validated against the stored services first, so duplicates and dependency cycles are rejected.
     ///
     /// # Arguments
     ///
@@ -902,12 +2157,28 @@ pub mod tauri_cmd {
     /// * `description` - Optional description.
     /// * `workspace` - Optional workspace path.
     /// * `args` - Startup arguments.
-    /// * `dependencies` - List of (name, version) for dependencies.
+    /// * `env` - Optional extra environment variables.
+    /// * `dependencies` - List of (name, version) for dependencies; `worker@emails` depends on one instance of the template `worker@`.
+    /// * `instances` - Optional instance names; only for templates (name ending in `@`).
+    /// * `replicas` - Optional number of copies to run, each with `SPINDLE_REPLICA` set to its index.
+    /// * `quorum` - Optional number of replicas that must run for dependents to start; defaults to all.
+    /// * `ports` - Optional named ports, each a port number or `"auto"`.
+    /// * `profiles` - Optional profile overlays by profile name; their dependencies are given as (name, version).
+    /// * `user` - Optional user (name or uid) to run as.
+    /// * `group` - Optional group (name or gid) to run as; defaults to the user's primary group.
+    /// * `umask` - Optional octal umask, e.g. `027`.
+    /// * `detached` - Optional flag to keep the service running when Spindle exits; defaults to false.
+    /// * `schedule` - Optional cron expression to run the service on, e.g. `*/15 * * * *`; for services that run and exit.
+    /// * `overlap` - Optional policy for a scheduled run due while the previous one is still going; defaults to skip.
+    /// * `watch` - Optional files, as globs relative to the workspace, whose changes restart the service.
+    /// * `limits` - Optional rlimits and cgroup limits.
+    /// * `hooks` - Optional pre-start, post-start, pre-stop and post-stop hook commands.
     ///
     /// # Returns
     ///
-    /// `Ok(service_id)` with the new service id, or `Err(message)` on failure (e.g. dependency not found).
+    /// `Ok(service_id)` with the new service id, or `Err(message)` on failure (e.g. dependency not found or validation failed).
     #[tauri::command]
+    #[allow(clippy::too_many_arguments)]
     pub async fn add_service(
         app: tauri::AppHandle,
         name: String,
@@ -916,40 +2187,212 @@ pub mod tauri_cmd {
         description: Option<String>,
         workspace: Option<String>,
         args: Vec<String>,
+        env: Option<BTreeMap<String, String>>,
         dependencies: Vec<(String, String)>,
+        instances: Option<Vec<String>>,
+        replicas: Option<u32>,
+        quorum: Option<u32>,
+        ports: Option<BTreeMap<String, super::PortSpec>>,
+        profiles: Option<BTreeMap<String, super::ProfileOverlay>>,
+        user: Option<String>,
+        group: Option<String>,
+        umask: Option<String>,
+        detached: Option<bool>,
+        schedule: Option<String>,
+        overlap: Option<super::OverlapPolicy>,
+        watch: Option<super::WatchConfig>,
+        limits: Option<super::ResourceLimits>,
+        hooks: Option<super::ServiceHooks>,
     ) -> Result<u32, String> {
-        let mut dependency_ids = Vec::with_capacity(dependencies.len());
-        for (dep_name, dep_version) in dependencies {
-            match super::query_service_id_by_name_and_version(&app, &dep_name, &dep_version).await {
-                Some(dep_id) => dependency_ids.push(dep_id),
-                None => {
-                    warn!(
-                        "dep_name" = dep_name,
-                        "dep_version" = dep_version,
-                        "Dependency not found"
-                    );
-                    return Err(format!(
-                        "Dependency not found: {}:v{}",
-                        dep_name, dep_version
-                    ));
-                }
-            }
+        let env = env.unwrap_or_default();
+        let instances = instances.unwrap_or_default();
+        let ports = ports.unwrap_or_default();
+        let profiles = profiles.unwrap_or_default();
+        let limits = limits.unwrap_or_default();
+        let hooks = hooks.unwrap_or_default();
+        let detached = detached.unwrap_or_default();
+        let overlap = overlap.unwrap_or_default();
+        if !instances.is_empty() && !name.ends_with('@') {
+            return Err(format!(
+                "Instances require a template name ending in `@`: {}",
+                name
+            ));
         }
-        let service_id = super::insert_stored_service_config(
-            &app,
-            &name,
-            &version,
-            &program,
-            description.as_deref(),
-            workspace.as_deref(),
-            &args,
-            &dependency_ids,
-        )
-        .await
-        .map_err(|e| e.to_string())?;
+        let proposed = super::ServiceConfig {
+            name: name.clone(),
+            version: version.clone(),
+            program: program.clone().into(),
+            args: args.clone(),
+            env: env.clone(),
+            dependencies: dependencies.clone(),
+            workspace: workspace.as_ref().map(|workspace| workspace.into()),
+            instances: instances.clone(),
+            replicas,
+            quorum,
+            ports: ports.clone(),
+            profiles: profiles.clone(),
+            user: user.clone(),
+            group: group.clone(),
+            umask: umask.clone(),
+            detached,
+            schedule: schedule.clone(),
+            overlap,
+            watch: watch.clone(),
+            limits: limits.clone(),
+            hooks: hooks.clone(),
+        };
+        // Checked here too, as a template without instances is not validated as a service.
+        super::validate_service_name(&name)?;
+        super::validate_proposed_service(&app, proposed).await?;
+        let (dependency_ids, instance_dependencies) =
+            super::resolve_dependencies(&app, dependencies).await?;
+        let profiles = super::resolve_profiles(&app, profiles).await?;
+        let detail = super::ServiceDetail {
+            program: &program,
+            description: description.as_deref(),
+            workspace: workspace.as_deref(),
+            args: &args,
+            env: &env,
+            dependency_ids: &dependency_ids,
+            instances: &instances,
+            instance_dependencies: &instance_dependencies,
+            replicas,
+            quorum,
+            ports: &ports,
+            profiles: &profiles,
+            user: user.as_deref(),
+            group: group.as_deref(),
+            umask: umask.as_deref(),
+            detached,
+            schedule: schedule.as_deref(),
+            overlap,
+            watch: watch.as_ref(),
+            limits: &limits,
+            hooks: &hooks,
+        };
+        let service_id = super::insert_stored_service_config(&app, &name, &version, &detail)
+            .await
+            .map_err(|e| e.to_string())?;
         Ok(service_id)
     }
 
+    /// Rewrites the config, args and dependencies of an existing service in one transaction.
+    /// Name and version are unchanged; dependencies are given as (name, version).
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `service_id` - Database id of the service to update.
+    /// * `program` - Executable program path.
+    /// * `description` - Optional description.
+    /// * `workspace` - Optional workspace path.
+    /// * `args` - Startup arguments.
+    /// * `env` - Optional extra environment variables.
+    /// * `dependencies` - List of (name, version) for dependencies.
+    /// * `instances` - Optional instance names; for a template, replaces all its instances.
+    /// * `replicas` - Optional number of copies to run.
+    /// * `quorum` - Optional number of replicas that must run for dependents to start.
+    /// * `ports` - Optional named ports; replaces all ports of the service.
+    /// * `profiles` - Optional profile overlays; replaces all profiles of the service.
+    /// * `user` - Optional user (name or uid) to run as.
+    /// * `group` - Optional group (name or gid) to run as.
+    /// * `umask` - Optional octal umask.
+    /// * `detached` - Optional flag to keep the service running when Spindle exits.
+    /// * `schedule` - Optional cron expression to run the service on.
+    /// * `overlap` - Optional policy for a scheduled run due while the previous one is still going.
+    /// * `watch` - Optional files whose changes restart the service.
+    /// * `limits` - Optional rlimits and cgroup limits.
+    /// * `hooks` - Optional pre-start, post-start, pre-stop and post-stop hook commands.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(message)` on failure (e.g. dependency not found or dependency cycle).
+    #[tauri::command]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_service(
+        app: tauri::AppHandle,
+        service_id: u32,
+        program: String,
+        description: Option<String>,
+        workspace: Option<String>,
+        args: Vec<String>,
+        env: Option<BTreeMap<String, String>>,
+        dependencies: Vec<(String, String)>,
+        instances: Option<Vec<String>>,
+        replicas: Option<u32>,
+        quorum: Option<u32>,
+        ports: Option<BTreeMap<String, super::PortSpec>>,
+        profiles: Option<BTreeMap<String, super::ProfileOverlay>>,
+        user: Option<String>,
+        group: Option<String>,
+        umask: Option<String>,
+        detached: Option<bool>,
+        schedule: Option<String>,
+        overlap: Option<super::OverlapPolicy>,
+        watch: Option<super::WatchConfig>,
+        limits: Option<super::ResourceLimits>,
+        hooks: Option<super::ServiceHooks>,
+    ) -> Result<(), String> {
+        let env = env.unwrap_or_default();
+        let instances = instances.unwrap_or_default();
+        let ports = ports.unwrap_or_default();
+        let profiles = profiles.unwrap_or_default();
+        let limits = limits.unwrap_or_default();
+        let hooks = hooks.unwrap_or_default();
+        let detached = detached.unwrap_or_default();
+        let overlap = overlap.unwrap_or_default();
+        if !instances.is_empty() {
+            let mut db_conn = crate::db::acquire_spindle_db_conn(&app)
+                .await
+                .ok_or_else(|| "Failed to acquire database connection".to_string())?;
+            let (name, _) = super::query_service_name_and_version(service_id, &mut db_conn)
+                .await
+                .ok_or_else(|| format!("Service not found: {}", service_id))?;
+            if !name.ends_with('@') {
+                return Err(format!(
+                    "Instances require a template name ending in `@`: {}",
+                    name
+                ));
+            }
+            for instance in &instances {
+                super::validate_service_name(&format!("{name}{instance}"))?;
+            }
+        }
+        super::validate_replicas(replicas, quorum)?;
+        let (dependency_ids, instance_dependencies) =
+            super::resolve_dependencies(&app, dependencies).await?;
+        let profiles = super::resolve_profiles(&app, profiles).await?;
+        let detail = super::ServiceDetail {
+            program: &program,
+            description: description.as_deref(),
+            workspace: workspace.as_deref(),
+            args: &args,
+            env: &env,
+            dependency_ids: &dependency_ids,
+            instances: &instances,
+            instance_dependencies: &instance_dependencies,
+            replicas,
+            quorum,
+            ports: &ports,
+            profiles: &profiles,
+            user: user.as_deref(),
+            group: group.as_deref(),
+            umask: umask.as_deref(),
+            detached,
+            schedule: schedule.as_deref(),
+            overlap,
+            watch: watch.as_ref(),
+            limits: &limits,
+            hooks: &hooks,
+        };
+        super::update_stored_service_config(&app, service_id, &detail)
+            .await
+            .map_err(|e| {
+                warn!("error" = ?e, "service_id" = service_id, "Failed to update service");
+                e.to_string()
+            })
+    }
+
     /// Removes a service by (name, version). Succeeds silently if the service does not exist.
     ///
     /// # Arguments
@@ -984,7 +2427,8 @@ pub mod tauri_cmd {
         }
     }
 
-    /// Loads all service configs from the database, rebuilds [ServiceManager], and updates app state.
+    /// Shuts down the current [ServiceManager], loads all service configs from the database,
+    /// rebuilds [ServiceManager], and updates app state.
     ///
     /// # Arguments
     ///
@@ -995,18 +2439,21 @@ pub mod tauri_cmd {
     /// `Ok(())` on success, or `Err(message)` on failure.
     #[tauri::command]
     pub async fn reload_service_manager(app: tauri::AppHandle) -> Result<(), String> {
-        let service_ids = super::query_all_service_id(&app).await;
-        let mut configs = Vec::with_capacity(service_ids.len());
-        for service_id in service_ids {
-            if let Some(config) = super::query_stored_service_config(&app, service_id).await {
-                configs.push(config);
-            }
-        }
-        let service_manager = super::create_service_manager(&configs)
+        // The old manager must be gone before the new one adopts detached services and starts
+        // schedulers and watchers, or both would act on them.
+        crate::shutdown_service_manager(&app).await;
+        let configs = super::query_all_stored_service_config(&app).await;
+        let mut options = crate::variable::service_manager_options(&app).await;
+        options.run_records = Some(crate::run_history::spawn_run_recorder(app.clone()));
+        let service_manager = super::create_service_manager(&configs, options)
             .await
             .map_err(|e| e.to_string())?;
         let app_state = app.state::<Mutex<crate::AppState>>();
-        app_state.lock().await.service_manager = Some(service_manager);
+        let mut state = app_state.lock().await;
+        if let Some(sender) = state.log_store_sender.clone() {
+            crate::log_store::spawn_output_indexer(sender, &service_manager);
+        }
+        state.service_manager = Some(service_manager);
         Ok(())
     }
 
@@ -1090,6 +2537,7 @@ pub mod tauri_cmd {
     /// * `app` - Tauri app handle.
     /// * `group_id` - Group id to launch.
     /// * `timeout_ms` - Max duration to wait for each service to reach Running.
+    /// * `profile` - Optional profile to launch with; `None` keeps the group's active profile.
     ///
     /// # Returns
     ///
@@ -1099,6 +2547,7 @@ pub mod tauri_cmd {
         app: tauri::AppHandle,
         group_id: usize,
         timeout_ms: u64,
+        profile: Option<String>,
     ) -> Result<(), String> {
         let app_state = app.state::<Mutex<crate::AppState>>();
         let service_manager = match app_state.lock().await.service_manager.as_ref() {
@@ -1110,12 +2559,67 @@ pub mod tauri_cmd {
             return Err(format!("Invalid group id: {}", group_id));
         }
         let service_start_timeout = std::time::Duration::from_millis(timeout_ms);
+        match profile {
+            Some(profile) => {
+                service_manager
+                    .launch_group_with_profile(group_id, service_start_timeout, Some(&profile))
+                    .await
+            }
+            None => {
+                service_manager
+                    .launch_group(group_id, service_start_timeout)
+                    .await
+            }
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    /// Sets the active profile of a group; rejected while the group is running.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `group_id` - Group id.
+    /// * `profile` - Profile to activate, or `None` for the base configs.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(message)` on failure.
+    #[tauri::command]
+    pub async fn set_group_profile(
+        app: tauri::AppHandle,
+        group_id: usize,
+        profile: Option<String>,
+    ) -> Result<(), String> {
+        let app_state = app.state::<Mutex<crate::AppState>>();
+        let service_manager = match app_state.lock().await.service_manager.as_ref() {
+            Some(sm) => sm.clone(),
+            None => return Err("Service manager not initialized".to_string()),
+        };
         service_manager
-            .launch_group(group_id, service_start_timeout)
-            .await
+            .set_group_profile(group_id, profile.as_deref())
             .map_err(|e| e.to_string())
     }
 
+    /// Returns the names of all profiles defined by any service.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(profiles)` sorted by name, or `Err(message)` if the service manager is not initialized.
+    #[tauri::command]
+    pub async fn profiles(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+        let app_state = app.state::<Mutex<crate::AppState>>();
+        let service_manager = match app_state.lock().await.service_manager.as_ref() {
+            Some(sm) => sm.clone(),
+            None => return Err("Service manager not initialized".to_string()),
+        };
+        Ok(service_manager.profiles())
+    }
+
     /// Stops a service by (name, version).
     ///
     /// # Arguments
@@ -1144,6 +2648,36 @@ pub mod tauri_cmd {
             .map_err(|e| e.to_string())
     }
 
+    /// Changes the number of running replicas of a replicated service; only the difference is
+    /// started or stopped. The stored replica count is not changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    /// * `replicas` - New number of replicas.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(message)` if the service is unknown or not replicated.
+    #[tauri::command]
+    pub async fn scale_service(
+        app: tauri::AppHandle,
+        name: String,
+        version: String,
+        replicas: u32,
+    ) -> Result<(), String> {
+        let app_state = app.state::<Mutex<crate::AppState>>();
+        let service_manager = match app_state.lock().await.service_manager.as_ref() {
+            Some(sm) => sm.clone(),
+            None => return Err("Service manager not initialized".to_string()),
+        };
+        service_manager
+            .scale_service(&name, &version, replicas)
+            .map_err(|e| e.to_string())
+    }
+
     /// Returns the state of a service by (name, version).
     ///
     /// # Arguments
@@ -1172,6 +2706,62 @@ pub mod tauri_cmd {
         Ok(state.to_string())
     }
 
+    /// Returns runtime info (PID, start/stop time, exit status, restarts) of a service by (name, version).
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(info)` on success, or `Err(message)` on failure.
+    #[tauri::command]
+    pub async fn service_runtime_info(
+        app: tauri::AppHandle,
+        name: String,
+        version: String,
+    ) -> Result<spindle_core::service::ServiceRuntimeInfo, String> {
+        let app_state = app.state::<Mutex<crate::AppState>>();
+        let service_manager = match app_state.lock().await.service_manager.as_ref() {
+            Some(sm) => sm.clone(),
+            None => return Err("Service manager not initialized".to_string()),
+        };
+        service_manager
+            .service_runtime_info(&name, &version)
+            .ok_or("Service not found".to_string())
+    }
+
+    /// Returns when a scheduled service runs next, by (name, version).
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(millis))` since the Unix epoch, `Ok(None)` if the service is not scheduled, or
+    /// `Err(message)` on failure.
+    #[tauri::command]
+    pub async fn next_scheduled_run(
+        app: tauri::AppHandle,
+        name: String,
+        version: String,
+    ) -> Result<Option<u64>, String> {
+        let app_state = app.state::<Mutex<crate::AppState>>();
+        let service_manager = match app_state.lock().await.service_manager.as_ref() {
+            Some(sm) => sm.clone(),
+            None => return Err("Service manager not initialized".to_string()),
+        };
+        if service_manager.service_state(&name, &version).is_none() {
+            return Err("Service not found".to_string());
+        }
+        Ok(service_manager.next_scheduled_run(&name, &version))
+    }
+
     /// Stops all services in the given group.
     ///
     /// # Arguments
@@ -0,0 +1,189 @@
+//! User-defined global variables and Tauri command layer.
+//!
+//! Global variables are stored in the `global_variable` table and passed to
+//! [spindle_core::service::ServiceManager] for `${VAR}` interpolation in service configs.
+
+use std::{collections::HashMap, ops::DerefMut};
+
+use spindle_core::service::ServiceManagerOptions;
+use sqlx::{Row, SqliteConnection};
+use tauri::Manager;
+use tracing::warn;
+
+/// Loads all global variables.
+///
+/// # Arguments
+///
+/// * `conn` - SQLite connection (a pool connection or an active transaction).
+///
+/// # Returns
+///
+/// `Ok(map)` of variable name to value, or an error.
+pub(crate) async fn load_global_variables(
+    conn: &mut SqliteConnection,
+) -> anyhow::Result<HashMap<String, String>> {
+    let rows = sqlx::query("SELECT name, value FROM global_variable")
+        .fetch_all(conn)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("name"), row.get("value")))
+        .collect())
+}
+
+/// Builds the [ServiceManagerOptions] for the current database contents.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+///
+/// # Returns
+///
+/// Options with the stored global variables, the active profile, the detached service state
+/// directory and the service log settings; on DB error the variables are empty and a warning is logged.
+pub(crate) async fn service_manager_options(app: &tauri::AppHandle) -> ServiceManagerOptions {
+    let variables = match crate::db::acquire_spindle_db_conn(app).await {
+        Some(mut db_conn) => match load_global_variables(db_conn.deref_mut()).await {
+            Ok(variables) => variables,
+            Err(e) => {
+                warn!("error" = ?e, "Failed to read global variables");
+                HashMap::new()
+            }
+        },
+        None => {
+            warn!("Failed to acquire database connection");
+            HashMap::new()
+        }
+    };
+    ServiceManagerOptions {
+        variables,
+        profile: crate::profile::load_active_profile(app),
+        state_dir: app
+            .path()
+            .app_data_dir()
+            .ok()
+            .map(|data_dir| data_dir.join("detached")),
+        service_logs: crate::logger::service_log_options(app),
+        run_records: None,
+    }
+}
+
+/// Inserts or replaces a global variable.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `name` - Variable name.
+/// * `value` - Variable value.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error.
+async fn set_global_variable(
+    app: &tauri::AppHandle,
+    name: &str,
+    value: &str,
+) -> anyhow::Result<()> {
+    if name.is_empty() || name.contains(['{', '}', '$']) {
+        anyhow::bail!("Invalid variable name: {:?}", name);
+    }
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    sqlx::query(
+        "INSERT INTO global_variable (name, value) VALUES ($1, $2)
+        ON CONFLICT (name) DO UPDATE SET value = excluded.value",
+    )
+    .bind(name)
+    .bind(value)
+    .execute(db_conn.deref_mut())
+    .await?;
+    Ok(())
+}
+
+/// Removes a global variable; removing an unknown variable is not an error.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle for DB access.
+/// * `name` - Variable name.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error.
+async fn remove_global_variable(app: &tauri::AppHandle, name: &str) -> anyhow::Result<()> {
+    let mut db_conn = crate::db::acquire_spindle_db_conn(app)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Failed to acquire database connection"))?;
+    sqlx::query("DELETE FROM global_variable WHERE name = $1")
+        .bind(name)
+        .execute(db_conn.deref_mut())
+        .await?;
+    Ok(())
+}
+
+/// Tauri commands exposed to the frontend: list, set and remove global variables.
+/// Reload the service manager afterwards to apply changes.
+pub mod tauri_cmd {
+    use std::{collections::HashMap, ops::DerefMut};
+
+    /// Returns all global variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(map)` of variable name to value, or `Err(message)` on DB error.
+    #[tauri::command]
+    pub async fn global_variables(
+        app: tauri::AppHandle,
+    ) -> Result<HashMap<String, String>, String> {
+        let mut db_conn = crate::db::acquire_spindle_db_conn(&app)
+            .await
+            .ok_or_else(|| "Failed to acquire database connection".to_string())?;
+        super::load_global_variables(db_conn.deref_mut())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Inserts or replaces a global variable, usable as `${name}` in service configs.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `name` - Variable name; must not contain `$`, `{` or `}`.
+    /// * `value` - Variable value.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(message)` on failure.
+    #[tauri::command]
+    pub async fn set_global_variable(
+        app: tauri::AppHandle,
+        name: String,
+        value: String,
+    ) -> Result<(), String> {
+        super::set_global_variable(&app, &name, &value)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Removes a global variable.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - Tauri app handle.
+    /// * `name` - Variable name.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(message)` on failure.
+    #[tauri::command]
+    pub async fn remove_global_variable(app: tauri::AppHandle, name: String) -> Result<(), String> {
+        super::remove_global_variable(&app, &name)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
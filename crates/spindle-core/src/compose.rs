@@ -0,0 +1,437 @@
+//! Import of `docker-compose.yml` files as [ServiceConfig]s for services run as local processes.
+//!
+//! Only `command`, `entrypoint`, `environment`, `env_file`, `working_dir` and `depends_on` are
+//! mapped; everything container-specific is reported in [ComposeImport::warnings].
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde_yaml::{Mapping, Value};
+
+use crate::service::ServiceConfig;
+
+/// Result of a compose import: the mapped services and everything that could not be mapped.
+#[derive(Debug, Clone, Default)]
+pub struct ComposeImport {
+    pub services: Vec<ServiceConfig>,
+    pub warnings: Vec<String>,
+}
+
+/// Service keys that are mapped onto [ServiceConfig].
+const SUPPORTED_SERVICE_KEYS: &[&str] = &[
+    "command",
+    "entrypoint",
+    "environment",
+    "env_file",
+    "working_dir",
+    "depends_on",
+];
+
+/// Top-level keys that are accepted without a warning.
+const IGNORED_TOP_LEVEL_KEYS: &[&str] = &["services", "version", "name"];
+
+/// Parses a compose file's content into [ServiceConfig]s.
+///
+/// Services without `command` or `entrypoint` (image-only) cannot run as local processes and are
+/// skipped. Dependencies on skipped services are dropped. `depends_on` conditions other than
+/// `service_started` are treated as `service_started`, since Spindle has no health checks.
+///
+/// # Arguments
+///
+/// * `content` - Compose file content (YAML).
+/// * `base_dir` - Directory of the compose file; relative `working_dir` and `env_file` paths
+///   resolve against it, and it is the default workspace.
+/// * `version` - Version assigned to every imported service.
+///
+/// # Returns
+///
+/// `Ok(ComposeImport)` on success, or an error if the content is not a valid compose document.
+pub fn parse_compose(
+    content: &str,
+    base_dir: &Path,
+    version: &str,
+) -> anyhow::Result<ComposeImport> {
+    let root: Value = serde_yaml::from_str(content)?;
+    let root = root
+        .as_mapping()
+        .ok_or_else(|| anyhow::anyhow!("Compose file is not a mapping"))?;
+    let mut ret = ComposeImport::default();
+    for key in root.keys() {
+        let key = value_to_string(key).unwrap_or_default();
+        if !IGNORED_TOP_LEVEL_KEYS.contains(&key.as_str()) {
+            ret.warnings
+                .push(format!("Unsupported top-level key `{key}` ignored"));
+        }
+    }
+    let services = match root.get("services") {
+        Some(Value::Mapping(services)) => services,
+        Some(_) => anyhow::bail!("`services` is not a mapping"),
+        None => anyhow::bail!("Compose file has no `services`"),
+    };
+
+    let mut parsed: Vec<(ServiceConfig, Vec<String>)> = Vec::with_capacity(services.len());
+    for (name, service) in services {
+        let name =
+            value_to_string(name).ok_or_else(|| anyhow::anyhow!("Service name is not a string"))?;
+        let service = match service {
+            Value::Mapping(service) => service,
+            Value::Null => &Mapping::new(),
+            _ => anyhow::bail!("Service `{name}` is not a mapping"),
+        };
+        if let Some((config, deps)) =
+            parse_compose_service(&name, service, base_dir, version, &mut ret.warnings)
+        {
+            parsed.push((config, deps));
+        }
+    }
+
+    let imported_names: Vec<String> = parsed.iter().map(|(c, _)| c.name.clone()).collect();
+    for (mut config, deps) in parsed {
+        for dep in deps {
+            if imported_names.contains(&dep) {
+                config.dependencies.push((dep, version.to_string()));
+            } else {
+                ret.warnings.push(format!(
+                    "Service `{}`: dependency `{dep}` is not imported, dependency dropped",
+                    config.name
+                ));
+            }
+        }
+        ret.services.push(config);
+    }
+    Ok(ret)
+}
+
+/// Reads a compose file and parses it with its directory as base, see [parse_compose].
+///
+/// # Arguments
+///
+/// * `path` - Path of the compose file.
+/// * `version` - Version assigned to every imported service.
+///
+/// # Returns
+///
+/// `Ok(ComposeImport)` on success, or an error if the file cannot be read or parsed.
+pub fn load_compose(path: &Path, version: &str) -> anyhow::Result<ComposeImport> {
+    let content = std::fs::read_to_string(path)?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    parse_compose(&content, base_dir, version)
+}
+
+/// Maps one compose service; returns the config (without dependencies) and its dependency names.
+fn parse_compose_service(
+    name: &str,
+    service: &Mapping,
+    base_dir: &Path,
+    version: &str,
+    warnings: &mut Vec<String>,
+) -> Option<(ServiceConfig, Vec<String>)> {
+    for key in service.keys() {
+        let key = value_to_string(key).unwrap_or_default();
+        if !SUPPORTED_SERVICE_KEYS.contains(&key.as_str()) {
+            warnings.push(format!("Service `{name}`: unsupported key `{key}` ignored"));
+        }
+    }
+
+    let entrypoint = parse_command(name, "entrypoint", service.get("entrypoint"), warnings)?;
+    let command = parse_command(name, "command", service.get("command"), warnings)?;
+    let mut argv = entrypoint;
+    argv.extend(command);
+    if argv.is_empty() {
+        warnings.push(format!(
+            "Service `{name}`: no `command` or `entrypoint`, image-only services are not run as local processes, skipped"
+        ));
+        return None;
+    }
+    let program = PathBuf::from(argv.remove(0));
+
+    let mut env = BTreeMap::new();
+    for env_file in string_or_list(service.get("env_file")) {
+        let env_file_path = base_dir.join(&env_file);
+        match crate::dotenv::load_dotenv(&env_file_path) {
+            Ok(file_env) => env.extend(file_env),
+            Err(e) => warnings.push(format!(
+                "Service `{name}`: failed to read env_file `{env_file}`: {e}"
+            )),
+        }
+    }
+    match service.get("environment") {
+        Some(Value::Mapping(map)) => {
+            for (key, value) in map {
+                let Some(key) = value_to_string(key) else {
+                    continue;
+                };
+                match value_to_string(value) {
+                    Some(value) => {
+                        env.insert(key, value);
+                    }
+                    // A bare key passes the host value through, which the process inherits anyway.
+                    None => continue,
+                }
+            }
+        }
+        Some(Value::Sequence(items)) => {
+            for item in items.iter().filter_map(value_to_string) {
+                if let Some((key, value)) = item.split_once('=') {
+                    env.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+        Some(Value::Null) | None => (),
+        Some(_) => warnings.push(format!(
+            "Service `{name}`: `environment` is neither a mapping nor a list, ignored"
+        )),
+    }
+
+    let workspace = match service.get("working_dir").and_then(value_to_string) {
+        Some(working_dir) => base_dir.join(working_dir),
+        None => base_dir.to_path_buf(),
+    };
+
+    let mut deps = Vec::new();
+    match service.get("depends_on") {
+        Some(Value::Sequence(items)) => deps.extend(items.iter().filter_map(value_to_string)),
+        Some(Value::Mapping(map)) => {
+            for (dep, spec) in map {
+                let Some(dep) = value_to_string(dep) else {
+                    continue;
+                };
+                let condition = spec
+                    .get("condition")
+                    .and_then(value_to_string)
+                    .unwrap_or_else(|| "service_started".to_string());
+                if condition != "service_started" {
+                    warnings.push(format!(
+                        "Service `{name}`: depends_on `{dep}` condition `{condition}` is not supported, treated as service_started"
+                    ));
+                }
+                deps.push(dep);
+            }
+        }
+        Some(Value::Null) | None => (),
+        Some(_) => warnings.push(format!(
+            "Service `{name}`: `depends_on` is neither a mapping nor a list, ignored"
+        )),
+    }
+
+    let config = ServiceConfig {
+        name: name.to_string(),
+        version: version.to_string(),
+        program,
+        args: argv,
+        env,
+        workspace: Some(workspace),
+        ..Default::default()
+    };
+    Some((config, deps))
+}
+
+/// Parses `command`/`entrypoint` in string (shell-split) or list form; `None` if unparseable.
+fn parse_command(
+    name: &str,
+    key: &str,
+    value: Option<&Value>,
+    warnings: &mut Vec<String>,
+) -> Option<Vec<String>> {
+    match value {
+        None | Some(Value::Null) => Some(Vec::new()),
+        Some(Value::String(command)) => match shlex::split(command) {
+            Some(argv) => Some(argv),
+            None => {
+                warnings.push(format!(
+                    "Service `{name}`: `{key}` has unbalanced quotes, skipped"
+                ));
+                None
+            }
+        },
+        Some(Value::Sequence(items)) => Some(items.iter().filter_map(value_to_string).collect()),
+        Some(_) => {
+            warnings.push(format!(
+                "Service `{name}`: `{key}` is neither a string nor a list, skipped"
+            ));
+            None
+        }
+    }
+}
+
+/// Returns the items of a string-or-list value.
+fn string_or_list(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Sequence(items)) => items.iter().filter_map(value_to_string).collect(),
+        Some(value) => value_to_string(value).into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Converts scalar YAML values to strings; `None` for null, mappings and sequences.
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> ComposeImport {
+        parse_compose(content, Path::new("/project"), "1").unwrap()
+    }
+
+    #[test]
+    fn command_entrypoint_env_and_workspace() {
+        let import = parse(
+            r#"
+services:
+  api:
+    entrypoint: ["node"]
+    command: "server.js --port '8080'"
+    environment:
+      PORT: 8080
+      DEBUG: true
+      PASSTHROUGH:
+    working_dir: api
+  worker:
+    command: [python, worker.py]
+    environment:
+      - QUEUE=jobs
+      - URL=redis://h:6379/0?a=b
+"#,
+        );
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+        let api = &import.services[0];
+        assert_eq!(api.name, "api");
+        assert_eq!(api.version, "1");
+        assert_eq!(api.program, PathBuf::from("node"));
+        assert_eq!(api.args, ["server.js", "--port", "8080"]);
+        assert_eq!(
+            api.env,
+            BTreeMap::from([
+                ("DEBUG".to_string(), "true".to_string()),
+                ("PORT".to_string(), "8080".to_string()),
+            ])
+        );
+        assert_eq!(api.workspace, Some(PathBuf::from("/project/api")));
+        let worker = &import.services[1];
+        assert_eq!(worker.program, PathBuf::from("python"));
+        assert_eq!(worker.args, ["worker.py"]);
+        assert_eq!(worker.env["URL"], "redis://h:6379/0?a=b");
+        assert_eq!(worker.workspace, Some(PathBuf::from("/project")));
+    }
+
+    #[test]
+    fn image_only_services_and_their_dependents() {
+        let import = parse(
+            r#"
+services:
+  db:
+    image: postgres
+  api:
+    command: ./api
+    depends_on: [db, cache]
+  cache:
+    command: redis-server
+"#,
+        );
+        let names: Vec<&str> = import.services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["api", "cache"]);
+        assert_eq!(
+            import.services[0].dependencies,
+            [("cache".to_string(), "1".to_string())]
+        );
+        assert!(import.warnings.iter().any(|w| w.contains("`image`")));
+        assert!(import.warnings.iter().any(|w| w.contains("no `command`")));
+        assert!(
+            import
+                .warnings
+                .iter()
+                .any(|w| w.contains("dependency `db`"))
+        );
+    }
+
+    #[test]
+    fn depends_on_conditions() {
+        let import = parse(
+            r#"
+services:
+  a:
+    command: a
+  b:
+    command: b
+    depends_on:
+      a:
+        condition: service_healthy
+"#,
+        );
+        assert_eq!(
+            import.services[1].dependencies,
+            [("a".to_string(), "1".to_string())]
+        );
+        assert_eq!(import.warnings.len(), 1);
+        assert!(import.warnings[0].contains("service_healthy"));
+    }
+
+    #[test]
+    fn unsupported_keys_and_bad_commands() {
+        let import = parse(
+            r#"
+version: "3"
+volumes: {}
+services:
+  quoted:
+    command: "echo 'unterminated"
+  ports:
+    command: ./run
+    ports: ["80:80"]
+"#,
+        );
+        let names: Vec<&str> = import.services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["ports"]);
+        assert!(import.warnings.iter().any(|w| w.contains("`volumes`")));
+        assert!(
+            import
+                .warnings
+                .iter()
+                .any(|w| w.contains("unbalanced quotes"))
+        );
+        assert!(import.warnings.iter().any(|w| w.contains("`ports`")));
+        assert!(!import.warnings.iter().any(|w| w.contains("`version`")));
+    }
+
+    #[test]
+    fn invalid_documents() {
+        for content in ["[]", "name: x", "services: []", "services:\n  a: 1"] {
+            assert!(
+                parse_compose(content, Path::new("."), "1").is_err(),
+                "{content:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn env_file_then_environment() {
+        let dir = std::env::temp_dir().join(format!("spindle-compose-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(".env"), "A=from_file\nB=from_file\n").unwrap();
+        let content = r#"
+services:
+  app:
+    command: ./app
+    env_file: [.env, missing.env]
+    environment:
+      B: from_environment
+"#;
+        let import = parse_compose(content, &dir, "1").unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let env = &import.services[0].env;
+        assert_eq!(env["A"], "from_file");
+        assert_eq!(env["B"], "from_environment");
+        assert_eq!(import.warnings.len(), 1);
+        assert!(import.warnings[0].contains("missing.env"));
+    }
+}
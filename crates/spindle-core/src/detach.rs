@@ -0,0 +1,224 @@
+//! Records of detached services, which keep running when the [crate::service::ServiceManager]
+//! is dropped and are adopted again by the next one.
+//!
+//! Each running detached service has a TOML record in the state directory with its PID and the
+//! kernel start time of that PID, so a reused PID is never mistaken for the service.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// What is recorded about a running detached service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetachedRecord {
+    /// Service name, including any replica suffix.
+    pub name: String,
+    /// Service version.
+    pub version: String,
+    /// PID of the service process, the leader of its own session.
+    pub pid: u32,
+    /// Start time of the process in clock ticks since boot (`/proc/<pid>/stat` field 22).
+    pub start_time: u64,
+    /// When the service was spawned, in milliseconds since the Unix epoch.
+    pub started_at: u64,
+    /// Ports allocated for the run, by port name.
+    #[serde(default)]
+    pub ports: BTreeMap<String, u16>,
+}
+
+/// Returns the file stem for a service key; characters other than `[A-Za-z0-9._@#-]` are
+/// escaped as `%XX` so that different keys never share a file.
+pub(crate) fn record_stem(name: &str, version: &str) -> String {
+    let escape = |value: &str| {
+        let mut ret = String::with_capacity(value.len());
+        for byte in value.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'_' | b'@' | b'#' | b'-' => {
+                    ret.push(byte as char)
+                }
+                _ => ret.push_str(&format!("%{byte:02X}")),
+            }
+        }
+        ret
+    };
+    format!("{}~{}", escape(name), escape(version))
+}
+
+/// Returns the path of the record of a service.
+pub fn record_path(state_dir: &Path, name: &str, version: &str) -> PathBuf {
+    state_dir.join(format!("{}.toml", record_stem(name, version)))
+}
+
+/// Writes the record of a service, creating the state directory if needed.
+///
+/// # Arguments
+///
+/// * `state_dir` - Directory holding the records.
+/// * `record` - Record to write; replaces an existing one for the same service.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error.
+pub fn write_record(state_dir: &Path, record: &DetachedRecord) -> anyhow::Result<()> {
+    std::fs::create_dir_all(state_dir)?;
+    let content = toml::to_string(record)?;
+    std::fs::write(
+        record_path(state_dir, &record.name, &record.version),
+        content,
+    )?;
+    Ok(())
+}
+
+/// Reads the record of a service.
+///
+/// # Returns
+///
+/// `Some(record)` if a valid record exists, `None` otherwise.
+pub fn read_record(state_dir: &Path, name: &str, version: &str) -> Option<DetachedRecord> {
+    let content = std::fs::read_to_string(record_path(state_dir, name, version)).ok()?;
+    match toml::from_str(&content) {
+        Ok(record) => Some(record),
+        Err(e) => {
+            tracing::warn!("error" = %e, "name" = name, "version" = version, "Invalid detached service record");
+            None
+        }
+    }
+}
+
+/// Reads all records in the state directory, whatever service they belong to.
+///
+/// # Returns
+///
+/// The valid records; unreadable or invalid files are skipped.
+pub fn list_records(state_dir: &Path) -> Vec<DetachedRecord> {
+    let Ok(entries) = std::fs::read_dir(state_dir) else {
+        return Vec::new();
+    };
+    let mut ret = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "toml") {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        match toml::from_str(&content) {
+            Ok(record) => ret.push(record),
+            Err(e) => {
+                tracing::warn!("error" = %e, "path" = ?path, "Invalid detached service record");
+            }
+        }
+    }
+    ret
+}
+
+/// Removes the record of a service; a missing record is ignored.
+pub fn remove_record(state_dir: &Path, name: &str, version: &str) {
+    let path = record_path(state_dir, name, version);
+    if let Err(e) = std::fs::remove_file(&path)
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!("error" = %e, "path" = ?path, "Failed to remove detached service record");
+    }
+}
+
+/// Returns the start time of a process in clock ticks since boot.
+///
+/// # Returns
+///
+/// `Some(ticks)` if the process exists, `None` if it does not or the platform has no `/proc`.
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name (field 2) is in parentheses and may contain spaces; fields after it start
+    // at field 3 (state), so the start time (field 22) is the 20th.
+    let (_, fields) = stat.rsplit_once(')')?;
+    let mut fields = fields.split_whitespace();
+    if fields.next()? == "Z" {
+        return None;
+    }
+    fields.nth(18)?.parse().ok()
+}
+
+/// Returns true if process start times can be read on this platform; without them a recorded
+/// PID cannot be told apart from a reused one, so services are not detached.
+pub fn start_times_available() -> bool {
+    process_start_time(std::process::id()).is_some()
+}
+
+/// Returns true if `pid` is still the process recorded with `start_time`.
+pub fn is_same_process(pid: u32, start_time: u64) -> bool {
+    process_start_time(pid) == Some(start_time)
+}
+
+/// Makes the child of `cmd` the leader of a new session, so it is not tied to Spindle's terminal
+/// and can be stopped through its process group.
+pub(crate) fn new_session(cmd: &mut tokio::process::Command) {
+    #[cfg(unix)]
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_stems_are_distinct() {
+        assert_eq!(record_stem("api#0", "1.2-rc_1"), "api#0~1.2-rc_1");
+        assert_eq!(record_stem("worker@emails", "1"), "worker@emails~1");
+        assert_eq!(record_stem("a/b", "1"), "a%2Fb~1");
+        assert_eq!(record_stem("a~b", "1"), "a%7Eb~1");
+        assert_eq!(record_stem("é", "1"), "%C3%A9~1");
+        // Separators inside a name or version are escaped, so keys cannot collide.
+        let keys = [
+            ("a-b", "1"),
+            ("a", "b-1"),
+            ("a~b", "1"),
+            ("a", "b~1"),
+            ("a%7Eb", "1"),
+        ];
+        let stems: std::collections::HashSet<String> = keys
+            .iter()
+            .map(|(name, version)| record_stem(name, version))
+            .collect();
+        assert_eq!(stems.len(), keys.len());
+    }
+
+    #[test]
+    fn record_round_trip() {
+        let state_dir = std::env::temp_dir().join(format!("spindle-detach-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&state_dir);
+        let record = DetachedRecord {
+            name: "api#1".to_string(),
+            version: "1/2".to_string(),
+            pid: 4242,
+            start_time: 123,
+            started_at: 1_700_000_000_000,
+            ports: BTreeMap::from([("http".to_string(), 8080)]),
+        };
+        write_record(&state_dir, &record).unwrap();
+        assert_eq!(
+            read_record(&state_dir, "api#1", "1/2"),
+            Some(record.clone())
+        );
+        assert_eq!(read_record(&state_dir, "api#1", "1"), None);
+        std::fs::write(state_dir.join("garbage.toml"), "pid = ").unwrap();
+        assert_eq!(list_records(&state_dir), [record]);
+        remove_record(&state_dir, "api#1", "1/2");
+        remove_record(&state_dir, "api#1", "1/2");
+        assert_eq!(read_record(&state_dir, "api#1", "1/2"), None);
+        std::fs::remove_dir_all(&state_dir).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+//! Minimal `.env` file parsing (`KEY=VALUE` lines) for service environments.
+
+use std::{collections::BTreeMap, path::Path};
+
+use tracing::warn;
+
+/// Parses `.env` content into a key/value map.
+///
+/// Supports blank lines, `#` comments, an optional `export ` prefix, single-quoted (literal)
+/// and double-quoted (with `\n`, `\t`, `\"` and `\\` escapes) values, and trailing ` #` comments
+/// on unquoted values. Malformed lines are skipped with a warning; later keys override earlier ones.
+///
+/// # Arguments
+///
+/// * `content` - File content.
+///
+/// # Returns
+///
+/// Map of variable name to value.
+pub fn parse_dotenv(content: &str) -> BTreeMap<String, String> {
+    let mut ret = BTreeMap::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, raw_value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => {
+                warn!("line" = line_idx + 1, "Malformed .env line, skipping");
+                continue;
+            }
+        };
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            warn!("line" = line_idx + 1, "Invalid .env key, skipping");
+            continue;
+        }
+        ret.insert(key.to_string(), parse_dotenv_value(raw_value));
+    }
+    ret
+}
+
+fn parse_dotenv_value(raw_value: &str) -> String {
+    if let Some(inner) = raw_value
+        .strip_prefix('\'')
+        .and_then(|v| v.strip_suffix('\''))
+    {
+        return inner.to_string();
+    }
+    if let Some(inner) = raw_value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+    {
+        let mut ret = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                ret.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => ret.push('\n'),
+                Some('t') => ret.push('\t'),
+                Some(other) => ret.push(other),
+                None => ret.push('\\'),
+            }
+        }
+        return ret;
+    }
+    match raw_value.find(" #") {
+        Some(idx) => raw_value[..idx].trim_end().to_string(),
+        None => raw_value.to_string(),
+    }
+}
+
+/// Reads and parses a `.env` file, see [parse_dotenv].
+///
+/// # Arguments
+///
+/// * `path` - Path of the `.env` file.
+///
+/// # Returns
+///
+/// `Ok(map)` on success, or an error if the file cannot be read.
+pub fn load_dotenv(path: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(parse_dotenv(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_blank_lines_and_export() {
+        let env = parse_dotenv("# comment\n\nexport A=1\n  B = two  \n");
+        assert_eq!(
+            env,
+            BTreeMap::from([
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "two".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn quoted_values() {
+        let env = parse_dotenv(
+            "SINGLE='a \\n # b'\nDOUBLE=\"a\\nb\\t\\\"c\\\"\\\\\"\nEMPTY=\"\"\nHASH=\"x # y\"",
+        );
+        assert_eq!(env["SINGLE"], "a \\n # b");
+        assert_eq!(env["DOUBLE"], "a\nb\t\"c\"\\");
+        assert_eq!(env["EMPTY"], "");
+        assert_eq!(env["HASH"], "x # y");
+    }
+
+    #[test]
+    fn trailing_comment_on_unquoted_value() {
+        let env = parse_dotenv("A=value # comment\nB=a#b\nC=");
+        assert_eq!(env["A"], "value");
+        assert_eq!(env["B"], "a#b");
+        assert_eq!(env["C"], "");
+    }
+
+    #[test]
+    fn malformed_lines_skipped_and_later_keys_win() {
+        let env = parse_dotenv("NOEQUALS\n=value\nBAD KEY=1\nA=1\nA=2");
+        assert_eq!(env, BTreeMap::from([("A".to_string(), "2".to_string())]));
+    }
+
+    #[test]
+    fn value_may_contain_equals() {
+        let env = parse_dotenv("URL=postgres://u:p@h/db?sslmode=require");
+        assert_eq!(env["URL"], "postgres://u:p@h/db?sslmode=require");
+    }
+}
@@ -0,0 +1,372 @@
+//! Commands run at points of a service's lifecycle: before it starts, after it started, before it
+//! is stopped and after it exited.
+//!
+//! A hook is a shell command line run with the service's environment, workspace and identity.
+//! Its output is captured and logged; only a failing `pre_start` hook affects the service, which
+//! is then not started and marked Failed with the hook's output.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::service::ServiceMeta;
+
+/// Time a hook may run when it sets no timeout.
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Max bytes of hook output kept in a failure reason; the end of the output is kept.
+const HOOK_OUTPUT_LIMIT: usize = 4096;
+
+/// Time the output pipes of a hook are still read after it exited. A process the hook left in
+/// the background (e.g. a daemon started by `pre_start`) keeps the pipes open, so their end is
+/// not waited for.
+const HOOK_OUTPUT_DRAIN: Duration = Duration::from_millis(200);
+
+/// A hook command and its timeout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookCommand {
+    /// Command line, run with `sh -c` (`cmd /C` on Windows).
+    pub command: String,
+    /// Seconds the hook may run before it is killed; defaults to [DEFAULT_HOOK_TIMEOUT].
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl HookCommand {
+    /// Returns the time the hook may run.
+    pub fn timeout(&self) -> Duration {
+        self.timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_HOOK_TIMEOUT)
+    }
+}
+
+/// Lifecycle hooks of a service; unset hooks are skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceHooks {
+    /// Run before the process is spawned; if it fails, the service is not started.
+    #[serde(default)]
+    pub pre_start: Option<HookCommand>,
+    /// Run once the process is spawned, alongside it.
+    #[serde(default)]
+    pub post_start: Option<HookCommand>,
+    /// Run when the service is stopped, before its processes are killed.
+    #[serde(default)]
+    pub pre_stop: Option<HookCommand>,
+    /// Run after the process exited, whether it was stopped or exited on its own.
+    #[serde(default)]
+    pub post_stop: Option<HookCommand>,
+}
+
+/// Point of the lifecycle a hook runs at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookStage {
+    PreStart,
+    PostStart,
+    PreStop,
+    PostStop,
+}
+
+impl HookStage {
+    /// All stages, in lifecycle order.
+    pub const ALL: [HookStage; 4] = [
+        HookStage::PreStart,
+        HookStage::PostStart,
+        HookStage::PreStop,
+        HookStage::PostStop,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PreStart => "pre_start",
+            Self::PostStart => "post_start",
+            Self::PreStop => "pre_stop",
+            Self::PostStop => "post_stop",
+        }
+    }
+
+    /// Parses a stage name as returned by [Self::as_str].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|stage| stage.as_str() == name)
+    }
+}
+
+impl ServiceHooks {
+    /// Returns the hook of `stage`, if set.
+    pub fn get(&self, stage: HookStage) -> Option<&HookCommand> {
+        match stage {
+            HookStage::PreStart => self.pre_start.as_ref(),
+            HookStage::PostStart => self.post_start.as_ref(),
+            HookStage::PreStop => self.pre_stop.as_ref(),
+            HookStage::PostStop => self.post_stop.as_ref(),
+        }
+    }
+
+    /// Returns the hook slot of `stage`.
+    pub fn get_mut(&mut self, stage: HookStage) -> &mut Option<HookCommand> {
+        match stage {
+            HookStage::PreStart => &mut self.pre_start,
+            HookStage::PostStart => &mut self.post_start,
+            HookStage::PreStop => &mut self.pre_stop,
+            HookStage::PostStop => &mut self.post_stop,
+        }
+    }
+
+    /// Applies `f` to every hook command line.
+    pub(crate) fn map_commands(&self, mut f: impl FnMut(&str) -> String) -> Self {
+        let mut ret = self.clone();
+        for stage in HookStage::ALL {
+            if let Some(hook) = ret.get_mut(stage) {
+                hook.command = f(&hook.command);
+            }
+        }
+        ret
+    }
+
+    /// Checks for empty commands and zero timeouts.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if valid, or `Err(message)` naming the invalid hook.
+    pub fn validate(&self) -> Result<(), String> {
+        for stage in HookStage::ALL {
+            let Some(hook) = self.get(stage) else {
+                continue;
+            };
+            if hook.command.trim().is_empty() {
+                return Err(format!("Invalid {} hook: empty command", stage.as_str()));
+            }
+            if hook.timeout_secs == Some(0) {
+                return Err(format!(
+                    "Invalid {} hook: timeout must be at least 1 second",
+                    stage.as_str()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the last [HOOK_OUTPUT_LIMIT] bytes of the hook's stdout and stderr, trimmed.
+fn output_tail(stdout: &[u8], stderr: &[u8]) -> String {
+    let mut output = String::from_utf8_lossy(stdout).into_owned();
+    output.push_str(&String::from_utf8_lossy(stderr));
+    let output = output.trim();
+    if output.len() <= HOOK_OUTPUT_LIMIT {
+        return output.to_string();
+    }
+    let mut start = output.len() - HOOK_OUTPUT_LIMIT;
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("...{}", &output[start..])
+}
+
+/// Appends what is read from a hook output pipe to `buf` until the pipe is closed.
+async fn read_pipe(mut pipe: impl AsyncRead + Unpin, buf: Arc<Mutex<Vec<u8>>>) {
+    let mut chunk = [0u8; 4096];
+    while let Ok(n) = pipe.read(&mut chunk).await
+        && n > 0
+    {
+        buf.lock().unwrap().extend_from_slice(&chunk[..n]);
+    }
+}
+
+/// Kills the process group of a hook that has not exited when it is dropped, which happens on
+/// timeout and when the hook is cancelled with its service.
+struct HookGroupGuard(Option<u32>);
+
+impl Drop for HookGroupGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0 {
+            let _ = crate::service::kill_process_group(pid);
+        }
+    }
+}
+
+/// Runs the hook of `stage` of a service and waits for it to exit, killing it after its timeout.
+///
+/// Only the hook process is waited for: processes it leaves in the background keep running and
+/// their output after the hook exited is not captured. Dropping the returned future kills the
+/// hook with its process group.
+///
+/// # Arguments
+///
+/// * `stage` - Lifecycle point, used in messages.
+/// * `hook` - Hook to run.
+/// * `meta` - Service whose env, workspace and identity the hook runs with.
+///
+/// # Returns
+///
+/// `Ok(output)` if the hook exited successfully, or `Err(reason)` with its output if it failed,
+/// timed out or could not be spawned.
+pub(crate) async fn run_hook(
+    stage: HookStage,
+    hook: &HookCommand,
+    meta: &ServiceMeta,
+) -> Result<String, String> {
+    #[cfg(unix)]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg(&hook.command);
+        cmd.process_group(0);
+        cmd
+    };
+    #[cfg(not(unix))]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C").arg(&hook.command);
+        cmd
+    };
+    cmd.envs(meta.env.iter().map(|(k, v)| (&**k, &**v)));
+    if let Some(workspace) = &meta.workspace
+        && workspace.exists()
+    {
+        cmd.current_dir(workspace);
+    }
+    crate::identity::apply_identity(&mut cmd, &meta.identity);
+    cmd.stdin(std::process::Stdio::null());
+    cmd.stdout(std::process::Stdio::piped());
+    cmd.stderr(std::process::Stdio::piped());
+    cmd.kill_on_drop(true);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {} hook: {e}", stage.as_str()))?;
+    // The shell is killed on drop; its children are reached through the group.
+    let mut guard = HookGroupGuard(child.id());
+    let stdout = Arc::new(Mutex::new(Vec::new()));
+    let stderr = Arc::new(Mutex::new(Vec::new()));
+    let mut readers = Vec::new();
+    if let Some(pipe) = child.stdout.take() {
+        readers.push(tokio::spawn(read_pipe(pipe, stdout.clone())));
+    }
+    if let Some(pipe) = child.stderr.take() {
+        readers.push(tokio::spawn(read_pipe(pipe, stderr.clone())));
+    }
+    let timeout = hook.timeout();
+    let result = tokio::time::timeout(timeout, child.wait()).await;
+    if matches!(result, Ok(Ok(_))) {
+        guard.0 = None;
+        let _ = tokio::time::timeout(HOOK_OUTPUT_DRAIN, async {
+            for reader in &mut readers {
+                let _ = reader.await;
+            }
+        })
+        .await;
+    }
+    for reader in &readers {
+        reader.abort();
+    }
+    let text = output_tail(&stdout.lock().unwrap(), &stderr.lock().unwrap());
+    match result {
+        Ok(Ok(status)) if status.success() => Ok(text),
+        Ok(Ok(status)) => Err(format!(
+            "{} hook failed with status: {status}: {text}",
+            stage.as_str()
+        )),
+        Ok(Err(e)) => Err(format!("{} hook failed: {e}", stage.as_str())),
+        Err(_) => Err(format!(
+            "{} hook timed out after {}s",
+            stage.as_str(),
+            timeout.as_secs()
+        )),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::service::{ServiceConfig, ServiceManager};
+
+    fn hook(command: &str, timeout_secs: Option<u64>) -> HookCommand {
+        HookCommand {
+            command: command.to_string(),
+            timeout_secs,
+        }
+    }
+
+    fn meta() -> ServiceMeta {
+        let config = ServiceConfig {
+            name: "api".to_string(),
+            version: "1".to_string(),
+            program: "true".into(),
+            env: [("GREETING".to_string(), "hello".to_string())].into(),
+            ..Default::default()
+        };
+        ServiceManager::from_configs(vec![config])
+            .service_meta("api", "1")
+            .unwrap()
+    }
+
+    #[test]
+    fn timeouts() {
+        assert_eq!(hook("true", None).timeout(), DEFAULT_HOOK_TIMEOUT);
+        assert_eq!(hook("true", Some(5)).timeout(), Duration::from_secs(5));
+        let mut hooks = ServiceHooks {
+            pre_start: Some(hook("true", Some(1))),
+            ..Default::default()
+        };
+        assert_eq!(hooks.validate(), Ok(()));
+        hooks.post_stop = Some(hook("true", Some(0)));
+        assert!(hooks.validate().unwrap_err().contains("post_stop"));
+        hooks.post_stop = Some(hook(" ", None));
+        assert!(hooks.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn results() {
+        let meta = meta();
+        let ok = run_hook(HookStage::PreStart, &hook("echo $GREETING", None), &meta).await;
+        assert_eq!(ok.as_deref(), Ok("hello"));
+        let err = run_hook(
+            HookStage::PostStop,
+            &hook("echo oops >&2; exit 3", None),
+            &meta,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.starts_with("post_stop hook failed"), "{err}");
+        assert!(err.ends_with("oops"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn timed_out_hooks_are_killed_with_their_group() {
+        let pid_file =
+            std::env::temp_dir().join(format!("spindle-hook-{}.pid", std::process::id()));
+        let command = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+        let started = std::time::Instant::now();
+        let err = run_hook(HookStage::PreStart, &hook(&command, Some(1)), &meta())
+            .await
+            .unwrap_err();
+        assert_eq!(err, "pre_start hook timed out after 1s");
+        assert!(started.elapsed() < Duration::from_secs(10));
+        let pid: u32 = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        let _ = std::fs::remove_file(&pid_file);
+        // The background process is killed with the group; it may stay a zombie until reaped.
+        let is_running = || {
+            std::fs::read_to_string(format!("/proc/{pid}/stat")).is_ok_and(|stat| {
+                !stat
+                    .rsplit_once(") ")
+                    .is_some_and(|(_, rest)| rest.starts_with('Z'))
+            })
+        };
+        let mut running = true;
+        for _ in 0..50 {
+            running = is_running();
+            if !running {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!running, "background process {pid} survived the hook");
+    }
+}
@@ -0,0 +1,319 @@
+//! User, group and umask of service processes.
+//!
+//! Names are resolved once when the services are loaded, so a missing user or group, or a switch
+//! that needs root while Spindle is unprivileged, is reported upfront; the resolved ids are
+//! applied in the child between `fork` and `exec`.
+
+/// Resolved user, group and umask a service process runs with.
+///
+/// Unset fields keep the value inherited from Spindle. A user or group equal to Spindle's own is
+/// left unset, so naming the desktop user needs no privileges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessIdentity {
+    /// User id to switch to.
+    pub uid: Option<u32>,
+    /// Primary group id to switch to.
+    pub gid: Option<u32>,
+    /// Supplementary groups to set; `None` keeps the inherited ones.
+    pub groups: Option<Vec<u32>>,
+    /// File mode creation mask.
+    pub umask: Option<u32>,
+}
+
+/// Parses an octal umask such as `022` or `0027`.
+///
+/// # Arguments
+///
+/// * `umask` - Octal umask string.
+///
+/// # Returns
+///
+/// `Ok(mask)` on success, or `Err(message)` if it is not an octal number up to `0777`.
+pub fn parse_umask(umask: &str) -> Result<u32, String> {
+    match u32::from_str_radix(umask, 8) {
+        Ok(mask) if mask <= 0o777 => Ok(mask),
+        _ => Err(format!(
+            "Invalid umask {umask:?}: expected an octal value up to 0777"
+        )),
+    }
+}
+
+/// Resolves user and group names (or numeric ids) and the umask of a service.
+///
+/// If `user` is set, the supplementary groups are those of the user, plus `group` if given.
+///
+/// # Arguments
+///
+/// * `user` - User name or numeric uid.
+/// * `group` - Group name or numeric gid; defaults to the user's primary group if `user` is set.
+/// * `umask` - Octal umask, see [parse_umask].
+///
+/// # Returns
+///
+/// `Ok(identity)` on success, or `Err(message)` if a name does not exist, switching to it needs
+/// privileges Spindle does not have, or the umask is invalid.
+pub fn resolve_identity(
+    user: Option<&str>,
+    group: Option<&str>,
+    umask: Option<&str>,
+) -> Result<ProcessIdentity, String> {
+    let umask = umask.map(parse_umask).transpose()?;
+    if user.is_none() && group.is_none() {
+        return Ok(ProcessIdentity {
+            umask,
+            ..Default::default()
+        });
+    }
+    imp::resolve_ids(user, group).map(|(uid, gid, groups)| ProcessIdentity {
+        uid,
+        gid,
+        groups,
+        umask,
+    })
+}
+
+/// Makes the child process of `cmd` switch to `identity` before `exec`.
+pub(crate) fn apply_identity(cmd: &mut tokio::process::Command, identity: &ProcessIdentity) {
+    if identity == &ProcessIdentity::default() {
+        return;
+    }
+    imp::apply(cmd, identity.clone());
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::ffi::{CStr, CString};
+
+    use super::ProcessIdentity;
+
+    /// Resolved (uid, gid, supplementary groups); ids equal to the current ones are `None`.
+    type ResolvedIds = (Option<u32>, Option<u32>, Option<Vec<u32>>);
+
+    /// Initial size of the buffer for user and group database entries.
+    const LOOKUP_BUFFER_LEN: usize = 16 * 1024;
+    /// Size at which a lookup failing with `ERANGE` is no longer retried with a larger buffer.
+    const MAX_LOOKUP_BUFFER_LEN: usize = 1024 * 1024;
+
+    /// Returns the error message for a failed user or group lookup; `rc` is the result of the
+    /// `get*_r` call, whose entry is null if there is no such name.
+    fn lookup_error(kind: &str, name: &str, rc: libc::c_int) -> String {
+        match rc {
+            0 | libc::ENOENT | libc::ESRCH => format!("{kind} {name:?} does not exist"),
+            _ => format!(
+                "Failed to look up {} {name:?}: {}",
+                kind.to_lowercase(),
+                std::io::Error::from_raw_os_error(rc)
+            ),
+        }
+    }
+
+    /// Looks up a user as (name, uid, primary gid) by name or numeric uid.
+    fn lookup_user(user: &str) -> Result<(CString, u32, u32), String> {
+        let name = CString::new(user).map_err(|_| lookup_error("User", user, 0))?;
+        let mut buf = vec![0 as libc::c_char; LOOKUP_BUFFER_LEN];
+        let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::passwd = std::ptr::null_mut();
+        let rc = loop {
+            let rc = match user.parse::<u32>() {
+                Ok(uid) => unsafe {
+                    libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result)
+                },
+                Err(_) => unsafe {
+                    libc::getpwnam_r(
+                        name.as_ptr(),
+                        &mut pwd,
+                        buf.as_mut_ptr(),
+                        buf.len(),
+                        &mut result,
+                    )
+                },
+            };
+            if rc != libc::ERANGE || buf.len() >= MAX_LOOKUP_BUFFER_LEN {
+                break rc;
+            }
+            buf.resize(buf.len() * 2, 0);
+        };
+        if rc != 0 || result.is_null() {
+            return Err(lookup_error("User", user, rc));
+        }
+        let name = unsafe { CStr::from_ptr(pwd.pw_name) }.to_owned();
+        Ok((name, pwd.pw_uid, pwd.pw_gid))
+    }
+
+    /// Looks up a group id by name or numeric gid.
+    fn lookup_group(group: &str) -> Result<u32, String> {
+        let name = CString::new(group).map_err(|_| lookup_error("Group", group, 0))?;
+        let mut buf = vec![0 as libc::c_char; LOOKUP_BUFFER_LEN];
+        let mut grp: libc::group = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::group = std::ptr::null_mut();
+        let rc = loop {
+            let rc = match group.parse::<u32>() {
+                Ok(gid) => unsafe {
+                    libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut result)
+                },
+                Err(_) => unsafe {
+                    libc::getgrnam_r(
+                        name.as_ptr(),
+                        &mut grp,
+                        buf.as_mut_ptr(),
+                        buf.len(),
+                        &mut result,
+                    )
+                },
+            };
+            if rc != libc::ERANGE || buf.len() >= MAX_LOOKUP_BUFFER_LEN {
+                break rc;
+            }
+            buf.resize(buf.len() * 2, 0);
+        };
+        if rc != 0 || result.is_null() {
+            return Err(lookup_error("Group", group, rc));
+        }
+        Ok(grp.gr_gid)
+    }
+
+    /// Returns the groups of `user` (including `gid`) as listed by the group database.
+    fn user_groups(user: &CStr, gid: u32) -> Vec<u32> {
+        let mut count: libc::c_int = 32;
+        loop {
+            let mut groups = vec![0 as libc::gid_t; count as usize];
+            let capacity = count;
+            let rc =
+                unsafe { libc::getgrouplist(user.as_ptr(), gid, groups.as_mut_ptr(), &mut count) };
+            if rc >= 0 {
+                groups.truncate(count as usize);
+                return groups;
+            }
+            // Some platforms do not report the needed size; grow until the list fits.
+            count = count.max(capacity * 2);
+        }
+    }
+
+    pub(super) fn resolve_ids(
+        user: Option<&str>,
+        group: Option<&str>,
+    ) -> Result<ResolvedIds, String> {
+        let current_uid = unsafe { libc::geteuid() };
+        let current_gid = unsafe { libc::getegid() };
+        // `setgid` to any other group and `setgroups` need root, whatever groups Spindle is in;
+        // checked here so that it is not only found out by the spawn failing with EPERM.
+        let needs_root = |what: String| {
+            if current_uid == 0 {
+                Ok(())
+            } else {
+                Err(format!("Running as {what} needs root privileges"))
+            }
+        };
+        let resolved_user = user.map(lookup_user).transpose()?;
+        let resolved_gid = group.map(lookup_group).transpose()?;
+        let Some((user_name, uid, user_gid)) = resolved_user else {
+            let gid = resolved_gid.filter(|gid| *gid != current_gid);
+            if let (Some(_), Some(group)) = (gid, group) {
+                needs_root(format!("group {group:?}"))?;
+            }
+            return Ok((None, gid, None));
+        };
+        let gid = resolved_gid.unwrap_or(user_gid);
+        if uid == current_uid && gid == current_gid {
+            return Ok((None, None, None));
+        }
+        if uid == current_uid
+            && let Some(group) = group
+        {
+            needs_root(format!("group {group:?}"))?;
+        }
+        needs_root(format!("user {:?}", user_name.to_string_lossy()))?;
+        let mut groups = user_groups(&user_name, user_gid);
+        if !groups.contains(&gid) {
+            groups.push(gid);
+        }
+        let uid = (uid != current_uid).then_some(uid);
+        Ok((uid, Some(gid), Some(groups)))
+    }
+
+    pub(super) fn apply(cmd: &mut tokio::process::Command, identity: ProcessIdentity) {
+        // Everything is done in one hook: std applies `uid`/`gid` before the hooks run, and the
+        // supplementary groups can only be set while still privileged.
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(groups) = &identity.groups
+                    && libc::setgroups(groups.len() as _, groups.as_ptr()) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if let Some(gid) = identity.gid
+                    && libc::setgid(gid) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if let Some(uid) = identity.uid
+                    && libc::setuid(uid) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if let Some(umask) = identity.umask {
+                    libc::umask(umask as libc::mode_t);
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use super::ProcessIdentity;
+
+    pub(super) fn resolve_ids(
+        _user: Option<&str>,
+        _group: Option<&str>,
+    ) -> Result<(Option<u32>, Option<u32>, Option<Vec<u32>>), String> {
+        Err("Running services as another user or group is only supported on Unix".to_string())
+    }
+
+    pub(super) fn apply(_cmd: &mut tokio::process::Command, identity: ProcessIdentity) {
+        if identity.umask.is_some() {
+            tracing::warn!("umask is only supported on Unix, ignored");
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn umask() {
+        assert_eq!(parse_umask("022"), Ok(0o022));
+        assert_eq!(parse_umask("0027"), Ok(0o027));
+        assert_eq!(parse_umask("777"), Ok(0o777));
+        for umask in ["", "1000", "8", "0x22", "-1", "02 2"] {
+            assert!(parse_umask(umask).is_err(), "{umask}");
+        }
+    }
+
+    #[test]
+    fn own_ids_need_no_switch() {
+        let uid = unsafe { libc::geteuid() }.to_string();
+        let gid = unsafe { libc::getegid() }.to_string();
+        assert_eq!(
+            resolve_identity(Some(&uid), Some(&gid), Some("077")),
+            Ok(ProcessIdentity {
+                umask: Some(0o077),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            resolve_identity(None, Some(&gid), None),
+            Ok(ProcessIdentity::default())
+        );
+    }
+
+    #[test]
+    fn unknown_names() {
+        let err = resolve_identity(Some("spindle-no-such-user"), None, None).unwrap_err();
+        assert!(err.contains("does not exist"), "{err}");
+        let err = resolve_identity(None, Some("spindle-no-such-group"), None).unwrap_err();
+        assert!(err.contains("does not exist"), "{err}");
+    }
+}
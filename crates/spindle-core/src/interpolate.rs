@@ -0,0 +1,246 @@
+//! `${VAR}` interpolation for service config values.
+//!
+//! Variables are written as `${NAME}`; `$$` produces a literal `$`, and a `$` not followed by
+//! `{` or `$` is kept as is. Names may contain anything except `}` (e.g. `service.name`).
+
+use std::collections::HashMap;
+
+/// Expands every `${NAME}` in `input` with `lookup`.
+///
+/// # Arguments
+///
+/// * `input` - Text to expand.
+/// * `lookup` - Returns the value of a variable, or `None` if it is not defined.
+///
+/// # Returns
+///
+/// `Ok(expanded)` if every variable resolved, else `Err(refs)` with the unresolved references
+/// as written (e.g. `${NAME}`, or the remaining text for an unterminated `${`).
+pub fn interpolate<F>(input: &str, lookup: F) -> Result<String, Vec<String>>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    expand_with(input, lookup, |ret, text| ret.push_str(text))
+}
+
+/// Expands every `${NAME}` in `input` into another template: `lookup` returns template text,
+/// inserted as is, and the literal text of `input` is escaped again.
+///
+/// Lets some variables be expanded now and others later, by returning `${NAME}` for them, in
+/// a final [interpolate] pass.
+///
+/// # Arguments
+///
+/// * `input` - Text to expand.
+/// * `lookup` - Returns the template a variable expands to, or `None` if it is not defined.
+///
+/// # Returns
+///
+/// `Ok(template)` if every variable resolved, else `Err(refs)` as for [interpolate].
+pub fn expand_template<F>(input: &str, lookup: F) -> Result<String, Vec<String>>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    expand_with(input, lookup, |ret, text| ret.push_str(&escape(text)))
+}
+
+/// Escapes every `$` in `value` as `$$`, so that [interpolate] turns it back into `value`.
+pub fn escape(value: &str) -> String {
+    value.replace('$', "$$")
+}
+
+/// Expands `input` with `lookup`, writing literal text with `push_literal`.
+fn expand_with<F, P>(input: &str, lookup: F, push_literal: P) -> Result<String, Vec<String>>
+where
+    F: Fn(&str) -> Option<String>,
+    P: Fn(&mut String, &str),
+{
+    let mut ret = String::with_capacity(input.len());
+    let mut unresolved = Vec::new();
+    let mut rest = input;
+    while let Some(idx) = rest.find('$') {
+        push_literal(&mut ret, &rest[..idx]);
+        let after = &rest[idx + 1..];
+        if let Some(after) = after.strip_prefix('$') {
+            push_literal(&mut ret, "$");
+            rest = after;
+        } else if let Some(after) = after.strip_prefix('{') {
+            match after.find('}') {
+                Some(end) => {
+                    let name = &after[..end];
+                    match lookup(name) {
+                        Some(value) => ret.push_str(&value),
+                        None => unresolved.push(format!("${{{name}}}")),
+                    }
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    unresolved.push(format!("${{{after}"));
+                    rest = "";
+                }
+            }
+        } else {
+            push_literal(&mut ret, "$");
+            rest = after;
+        }
+    }
+    push_literal(&mut ret, rest);
+    if unresolved.is_empty() {
+        Ok(ret)
+    } else {
+        Err(unresolved)
+    }
+}
+
+/// Variable sources for one service, looked up in order: built-ins, user globals, process environment.
+#[derive(Clone)]
+pub struct VariableScope<'a> {
+    builtins: HashMap<String, String>,
+    templates: HashMap<String, String>,
+    globals: &'a HashMap<String, String>,
+}
+
+impl<'a> VariableScope<'a> {
+    /// Creates a scope with the built-ins `service.name` and `service.version`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    /// * `globals` - User-defined global variables.
+    pub fn new(name: &str, version: &str, globals: &'a HashMap<String, String>) -> Self {
+        let mut builtins = HashMap::new();
+        builtins.insert("service.name".to_string(), name.to_string());
+        builtins.insert("service.version".to_string(), version.to_string());
+        Self {
+            builtins,
+            templates: HashMap::new(),
+            globals,
+        }
+    }
+
+    /// Defines a built-in variable.
+    pub fn set_builtin(&mut self, name: impl Into<String>, value: String) {
+        self.builtins.insert(name.into(), value);
+    }
+
+    /// Defines a built-in variable whose value is a template, inserted as is by
+    /// [VariableScope::expand_template] (e.g. `workspace` once it is expanded itself).
+    pub fn set_template(&mut self, name: impl Into<String>, template: String) {
+        self.templates.insert(name.into(), template);
+    }
+
+    /// Keeps `${NAME}` in the output of [VariableScope::expand_template], for a variable only
+    /// known later (e.g. a port picked at spawn time).
+    pub fn defer(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        let template = format!("${{{name}}}");
+        self.templates.insert(name, template);
+    }
+
+    /// Returns the value of a variable, or `None` if no source defines it.
+    pub fn lookup(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.builtins.get(name) {
+            return Some(value.clone());
+        }
+        if let Some(value) = self.globals.get(name) {
+            return Some(value.clone());
+        }
+        std::env::var(name).ok()
+    }
+
+    /// Expands `input` in this scope, see [interpolate].
+    pub fn expand(&self, input: &str) -> Result<String, Vec<String>> {
+        interpolate(input, |name| self.lookup(name))
+    }
+
+    /// Expands `input` in this scope into a template keeping the deferred variables, see
+    /// [expand_template].
+    pub fn expand_template(&self, input: &str) -> Result<String, Vec<String>> {
+        expand_template(input, |name| {
+            self.templates
+                .get(name)
+                .cloned()
+                .or_else(|| self.lookup(name).map(|value| escape(&value)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(input: &str) -> Result<String, Vec<String>> {
+        let vars = HashMap::from([
+            ("A".to_string(), "1".to_string()),
+            ("service.name".to_string(), "api".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ]);
+        interpolate(input, |name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(expand("x${A}y${A}").unwrap(), "x1y1");
+        assert_eq!(expand("${service.name}-${EMPTY}").unwrap(), "api-");
+        assert_eq!(expand("no variables").unwrap(), "no variables");
+    }
+
+    #[test]
+    fn dollar_escapes() {
+        assert_eq!(expand("$$").unwrap(), "$");
+        assert_eq!(expand("$${A}").unwrap(), "${A}");
+        assert_eq!(expand("$$$${A}").unwrap(), "$${A}");
+        assert_eq!(expand("$$${A}").unwrap(), "$1");
+        assert_eq!(expand("$A $1 a$ $").unwrap(), "$A $1 a$ $");
+    }
+
+    #[test]
+    fn unresolved_references() {
+        assert_eq!(expand("${B} ${A} ${C}").unwrap_err(), ["${B}", "${C}"]);
+        assert_eq!(expand("${}").unwrap_err(), ["${}"]);
+        assert_eq!(expand("a ${A").unwrap_err(), ["${A"]);
+        // Values are not expanded again.
+        let lookup = |name: &str| (name == "X").then(|| "${A}".to_string());
+        assert_eq!(interpolate("${X}", lookup).unwrap(), "${A}");
+    }
+
+    #[test]
+    fn scope_lookup_order() {
+        let globals = HashMap::from([
+            ("service.name".to_string(), "global".to_string()),
+            ("PATH".to_string(), "global-path".to_string()),
+            ("G".to_string(), "g".to_string()),
+        ]);
+        let mut scope = VariableScope::new("api", "2", &globals);
+        assert_eq!(scope.lookup("service.name").as_deref(), Some("api"));
+        assert_eq!(scope.lookup("service.version").as_deref(), Some("2"));
+        assert_eq!(scope.lookup("PATH").as_deref(), Some("global-path"));
+        scope.set_builtin("G", "builtin".to_string());
+        assert_eq!(scope.expand("${G}:${service.name}").unwrap(), "builtin:api");
+        let no_globals = HashMap::new();
+        let scope = VariableScope::new("api", "2", &no_globals);
+        assert_eq!(scope.lookup("PATH"), std::env::var("PATH").ok());
+    }
+
+    #[test]
+    fn deferred_variables_expand_in_a_later_pass() {
+        let globals = HashMap::from([("G".to_string(), "$x ${port.http}".to_string())]);
+        let mut scope = VariableScope::new("api", "2", &globals);
+        scope.defer("port.http");
+        scope.set_template("workspace", "/srv/$$${port.http}".to_string());
+        let template = scope
+            .expand_template("$${port.http} ${G} ${port.http} ${workspace} a$")
+            .unwrap();
+        assert_eq!(
+            template,
+            "$${port.http} $$x $${port.http} ${port.http} /srv/$$${port.http} a$$"
+        );
+        let ports = |name: &str| (name == "port.http").then(|| "8080".to_string());
+        assert_eq!(
+            interpolate(&template, ports).unwrap(),
+            "${port.http} $x ${port.http} 8080 /srv/$8080 a$"
+        );
+        assert_eq!(escape("a$$b"), "a$$$$b");
+    }
+}
@@ -1 +1,13 @@
+pub mod compose;
+pub mod detach;
+pub mod dotenv;
+pub mod hooks;
+pub mod identity;
+pub mod interpolate;
+pub mod limits;
+pub mod procfile;
+pub mod schedule;
 pub mod service;
+pub mod service_log;
+pub mod systemd;
+pub mod watch;
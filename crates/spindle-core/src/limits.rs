@@ -0,0 +1,346 @@
+//! Resource limits of service processes: rlimits and cgroup v2 controllers.
+//!
+//! Rlimits are set in the child before `exec`. Cgroup limits need a delegated cgroup v2
+//! subtree: Spindle creates `spindle/` next to its own cgroup and gives every running service
+//! its own child cgroup there, which also tracks all its descendants for stopping. Without
+//! delegation, cgroup limits are skipped with a warning.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Limits applied to a service process; unset fields are not limited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Max open file descriptors (`RLIMIT_NOFILE`).
+    #[serde(default)]
+    pub open_files: Option<u64>,
+    /// Max core dump size in bytes (`RLIMIT_CORE`); `0` disables core dumps.
+    #[serde(default)]
+    pub core_size: Option<u64>,
+    /// Max virtual address space in bytes (`RLIMIT_AS`).
+    #[serde(default)]
+    pub address_space: Option<u64>,
+    /// Max CPU time in seconds (`RLIMIT_CPU`); the process gets `SIGXCPU` when exceeded.
+    #[serde(default)]
+    pub cpu_seconds: Option<u64>,
+    /// Max memory in bytes of the service's cgroup (`memory.max`).
+    #[serde(default)]
+    pub memory_max: Option<u64>,
+    /// Max CPUs of the service's cgroup, e.g. `1.5` (`cpu.max`).
+    #[serde(default)]
+    pub cpu_max: Option<f64>,
+    /// Max processes and threads of the service's cgroup (`pids.max`).
+    #[serde(default)]
+    pub pids_max: Option<u64>,
+}
+
+/// Period of `cpu.max` in microseconds.
+const CPU_MAX_PERIOD_US: u64 = 100_000;
+
+impl ResourceLimits {
+    /// Returns true if any rlimit is set.
+    pub fn has_rlimits(&self) -> bool {
+        self.open_files.is_some()
+            || self.core_size.is_some()
+            || self.address_space.is_some()
+            || self.cpu_seconds.is_some()
+    }
+
+    /// Returns true if any cgroup limit is set.
+    pub fn has_cgroup_limits(&self) -> bool {
+        self.memory_max.is_some() || self.cpu_max.is_some() || self.pids_max.is_some()
+    }
+
+    /// Checks the limits for values the kernel would reject.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if valid, or `Err(message)` naming the invalid limit.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(cpu_max) = self.cpu_max
+            && !(cpu_max.is_finite() && cpu_max * CPU_MAX_PERIOD_US as f64 >= 1000.0)
+        {
+            return Err(format!(
+                "Invalid cpu_max {cpu_max}: expected at least 0.01 CPUs"
+            ));
+        }
+        if self.pids_max == Some(0) {
+            return Err("Invalid pids_max 0: the service could not start".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the `cpu.max` value (`<quota> <period>`) for `cpu_max`.
+    pub fn cpu_max_value(&self) -> Option<String> {
+        self.cpu_max.map(|cpus| {
+            let quota = (cpus * CPU_MAX_PERIOD_US as f64).round() as u64;
+            format!("{quota} {CPU_MAX_PERIOD_US}")
+        })
+    }
+}
+
+/// Returns the cgroup v2 directory Spindle places service cgroups in, if delegation is available.
+///
+/// The directory is `spindle/` in the parent of Spindle's own cgroup, with the memory, cpu and
+/// pids controllers enabled for its children. The result is computed once.
+pub fn cgroup_root() -> Option<&'static Path> {
+    static CGROUP_ROOT: std::sync::OnceLock<Option<PathBuf>> = std::sync::OnceLock::new();
+    CGROUP_ROOT
+        .get_or_init(|| match prepare_cgroup_root() {
+            Ok(root) => Some(root),
+            Err(e) => {
+                tracing::info!("error" = %e, "cgroup v2 delegation not available");
+                None
+            }
+        })
+        .as_deref()
+}
+
+fn prepare_cgroup_root() -> anyhow::Result<PathBuf> {
+    let own = std::fs::read_to_string("/proc/self/cgroup")?
+        .lines()
+        .find_map(|line| line.strip_prefix("0::").map(str::to_string))
+        .ok_or_else(|| anyhow::anyhow!("Not running under cgroup v2"))?;
+    let own = Path::new("/sys/fs/cgroup").join(own.trim_start_matches('/'));
+    let parent = own
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Spindle runs in the root cgroup"))?;
+    let controllers = std::fs::read_to_string(parent.join("cgroup.controllers"))?;
+    for controller in ["memory", "cpu", "pids"] {
+        if !controllers.split_whitespace().any(|c| c == controller) {
+            anyhow::bail!("Controller {controller} is not available");
+        }
+    }
+    std::fs::write(parent.join("cgroup.subtree_control"), "+memory +cpu +pids")?;
+    let root = parent.join("spindle");
+    if !root.exists() {
+        std::fs::create_dir(&root)?;
+    }
+    std::fs::write(root.join("cgroup.subtree_control"), "+memory +cpu +pids")?;
+    Ok(root)
+}
+
+/// Cgroup of one running service; removed on drop once its processes are gone.
+#[derive(Debug)]
+pub(crate) struct ServiceCgroup {
+    path: PathBuf,
+}
+
+impl ServiceCgroup {
+    /// Creates (or reuses) the cgroup of a service under [cgroup_root] and writes its limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Service name, including any replica suffix.
+    /// * `version` - Service version.
+    /// * `limits` - Limits to write.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(cgroup))` on success, `Ok(None)` without delegation, or an error if the cgroup
+    /// cannot be set up.
+    pub(crate) fn create(
+        name: &str,
+        version: &str,
+        limits: &ResourceLimits,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(root) = cgroup_root() else {
+            return Ok(None);
+        };
+        let path = Self::path_in(root, name, version);
+        if !path.exists() {
+            std::fs::create_dir(&path)?;
+        }
+        let cgroup = Self { path };
+        cgroup.write("memory.max", limits.memory_max.map(|v| v.to_string()))?;
+        cgroup.write("cpu.max", limits.cpu_max_value())?;
+        cgroup.write("pids.max", limits.pids_max.map(|v| v.to_string()))?;
+        Ok(Some(cgroup))
+    }
+
+    /// Returns the cgroup of a service left from an earlier run, if it still exists.
+    pub(crate) fn existing(name: &str, version: &str) -> Option<Self> {
+        let path = Self::path_in(cgroup_root()?, name, version);
+        path.exists().then_some(Self { path })
+    }
+
+    /// Named like the detached-service records, so different services never share a cgroup.
+    fn path_in(root: &Path, name: &str, version: &str) -> PathBuf {
+        root.join(crate::detach::record_stem(name, version))
+    }
+
+    fn write(&self, file: &str, value: Option<String>) -> anyhow::Result<()> {
+        let value = value.unwrap_or_else(|| "max".to_string());
+        std::fs::write(self.path.join(file), &value)
+            .map_err(|e| anyhow::anyhow!("Failed to write {file} = {value}: {e}"))
+    }
+
+    /// Opens `cgroup.procs` for moving the child into the cgroup before `exec`.
+    pub(crate) fn procs_file(&self) -> std::io::Result<std::fs::File> {
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(self.path.join("cgroup.procs"))
+    }
+
+    /// Kills every process in the cgroup with `SIGKILL`.
+    ///
+    /// Uses `cgroup.kill` where the kernel has it, and signals the listed processes otherwise.
+    pub(crate) fn kill(&self) -> std::io::Result<()> {
+        if std::fs::write(self.path.join("cgroup.kill"), "1").is_ok() {
+            return Ok(());
+        }
+        let procs = std::fs::read_to_string(self.path.join("cgroup.procs"))?;
+        for pid in procs
+            .lines()
+            .filter_map(|pid| pid.trim().parse::<i32>().ok())
+        {
+            imp::kill(pid);
+        }
+        Ok(())
+    }
+
+    /// Returns true while any process is left in the cgroup.
+    pub(crate) fn is_populated(&self) -> bool {
+        std::fs::read_to_string(self.path.join("cgroup.events"))
+            .map(|events| events.lines().any(|line| line.trim() == "populated 1"))
+            .unwrap_or_default()
+    }
+
+    /// Waits until the cgroup has no processes left, polling `cgroup.events`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Max duration to wait.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the cgroup is empty, `false` on timeout.
+    pub(crate) async fn wait_empty(&self, timeout: std::time::Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.is_populated() {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        true
+    }
+
+    /// Returns the number of OOM kills in the cgroup so far.
+    pub(crate) fn oom_kills(&self) -> u64 {
+        std::fs::read_to_string(self.path.join("memory.events"))
+            .ok()
+            .and_then(|events| {
+                events.lines().find_map(|line| {
+                    line.strip_prefix("oom_kill ")
+                        .and_then(|count| count.trim().parse().ok())
+                })
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for ServiceCgroup {
+    fn drop(&mut self) {
+        // Fails while processes remain (e.g. orphaned grandchildren); the cgroup is reused then.
+        let _ = std::fs::remove_dir(&self.path);
+    }
+}
+
+/// Sets up the rlimits and cgroup of `cmd`'s child process.
+///
+/// If `cgroup.procs` cannot be opened, the child runs outside the cgroup and a warning is logged.
+///
+/// # Arguments
+///
+/// * `cmd` - Command to spawn.
+/// * `limits` - Limits of the service.
+/// * `cgroup` - Cgroup to move the child into, if any.
+pub(crate) fn apply_limits(
+    cmd: &mut tokio::process::Command,
+    limits: &ResourceLimits,
+    cgroup: Option<&ServiceCgroup>,
+) {
+    let procs_file = cgroup.and_then(|cgroup| match cgroup.procs_file() {
+        Ok(procs_file) => Some(procs_file),
+        Err(e) => {
+            tracing::warn!("error" = %e, "path" = ?cgroup.path, "Failed to open cgroup.procs, running outside the cgroup");
+            None
+        }
+    });
+    if procs_file.is_none() && !limits.has_rlimits() {
+        return;
+    }
+    imp::apply(cmd, limits.clone(), procs_file);
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::os::fd::AsRawFd;
+
+    use super::ResourceLimits;
+
+    pub(super) fn kill(pid: i32) {
+        unsafe {
+            libc::kill(pid, libc::SIGKILL);
+        }
+    }
+
+    pub(super) fn apply(
+        cmd: &mut tokio::process::Command,
+        limits: ResourceLimits,
+        procs_file: Option<std::fs::File>,
+    ) {
+        // (resource, soft, hard); the CPU hard limit is one second later so that the process
+        // gets SIGXCPU rather than SIGKILL.
+        let rlimits = [
+            (libc::RLIMIT_NOFILE, limits.open_files, limits.open_files),
+            (libc::RLIMIT_CORE, limits.core_size, limits.core_size),
+            (libc::RLIMIT_AS, limits.address_space, limits.address_space),
+            (
+                libc::RLIMIT_CPU,
+                limits.cpu_seconds,
+                limits.cpu_seconds.map(|seconds| seconds.saturating_add(1)),
+            ),
+        ];
+        unsafe {
+            cmd.pre_exec(move || {
+                // Writing 0 moves the writing process, i.e. the child before `exec`.
+                if let Some(procs_file) = &procs_file
+                    && libc::write(procs_file.as_raw_fd(), b"0".as_ptr().cast(), 1) < 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+                for (resource, soft, hard) in rlimits {
+                    let (Some(soft), Some(hard)) = (soft, hard) else {
+                        continue;
+                    };
+                    let rlimit = libc::rlimit {
+                        rlim_cur: soft as libc::rlim_t,
+                        rlim_max: hard as libc::rlim_t,
+                    };
+                    if libc::setrlimit(resource, &rlimit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use super::ResourceLimits;
+
+    pub(super) fn kill(_pid: i32) {}
+
+    pub(super) fn apply(
+        _cmd: &mut tokio::process::Command,
+        _limits: ResourceLimits,
+        _procs_file: Option<std::fs::File>,
+    ) {
+        tracing::warn!("Resource limits are only supported on Unix, ignored");
+    }
+}
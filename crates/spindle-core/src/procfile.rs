@@ -0,0 +1,240 @@
+//! Import and export of foreman-style `Procfile`s (`name: command args` lines).
+//!
+//! A Procfile only describes processes, so versions, dependencies and environment variables are
+//! not represented in it; environment comes from an optional `.env` next to the Procfile.
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::service::ServiceConfig;
+
+/// Shell Procfile commands run with.
+const SHELL: &str = "sh";
+
+/// Parses Procfile content into [ServiceConfig]s.
+///
+/// Blank lines and `#` comments are skipped. Process names may contain ASCII letters, digits,
+/// `_` and `-`. Like foreman, each command runs as `sh -c command`, so `$PORT`, `&&`, pipes and
+/// redirections keep working; `$` is escaped as `$$` so that `${VAR}` interpolation leaves the
+/// command unchanged.
+///
+/// # Arguments
+///
+/// * `content` - Procfile content.
+/// * `base_dir` - Workspace assigned to every service (usually the Procfile's directory).
+/// * `version` - Version assigned to every service.
+/// * `env` - Environment variables assigned to every service.
+///
+/// # Returns
+///
+/// `Ok(configs)` in file order, or an error naming the first malformed line or duplicate process.
+pub fn parse_procfile(
+    content: &str,
+    base_dir: &Path,
+    version: &str,
+    env: &BTreeMap<String, String>,
+) -> anyhow::Result<Vec<ServiceConfig>> {
+    let mut ret: Vec<ServiceConfig> = Vec::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, command) = line
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Procfile line {line_no}: expected `name: command`"))?;
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            anyhow::bail!("Procfile line {line_no}: invalid process name `{name}`");
+        }
+        if ret.iter().any(|config| config.name == name) {
+            anyhow::bail!("Procfile line {line_no}: duplicate process `{name}`");
+        }
+        let command = command.trim();
+        if command.is_empty() {
+            anyhow::bail!("Procfile line {line_no}: process `{name}` has no command");
+        }
+        ret.push(ServiceConfig {
+            name: name.to_string(),
+            version: version.to_string(),
+            program: SHELL.into(),
+            args: vec!["-c".to_string(), command.replace('$', "$$")],
+            env: env.clone(),
+            workspace: Some(base_dir.to_path_buf()),
+            ..Default::default()
+        });
+    }
+    Ok(ret)
+}
+
+/// Reads a Procfile, using its directory as workspace and the `.env` in that directory, if any,
+/// as environment. See [parse_procfile].
+///
+/// # Arguments
+///
+/// * `path` - Path of the Procfile.
+/// * `version` - Version assigned to every service.
+///
+/// # Returns
+///
+/// `Ok(configs)` on success, or an error if a file cannot be read or the Procfile is malformed.
+pub fn load_procfile(path: &Path, version: &str) -> anyhow::Result<Vec<ServiceConfig>> {
+    let content = std::fs::read_to_string(path)?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let dotenv_path = base_dir.join(".env");
+    let env = if dotenv_path.is_file() {
+        crate::dotenv::load_dotenv(&dotenv_path)?
+    } else {
+        BTreeMap::new()
+    };
+    parse_procfile(&content, base_dir, version, &env)
+}
+
+/// Renders services as Procfile content, one `name: command` line per service.
+///
+/// Services run as `sh -c command`, as [parse_procfile] imports them, are written as `command`
+/// with `$$` turned back into `$`; others as their shell-quoted program and arguments.
+/// Versions, workspaces, environment variables and dependencies are not written.
+///
+/// # Arguments
+///
+/// * `configs` - Services to render.
+///
+/// # Returns
+///
+/// `Ok(content)` on success, or an error if two services share a name, a command spans lines or
+/// a program or argument cannot be shell-quoted (e.g. it contains a NUL byte).
+pub fn render_procfile(configs: &[ServiceConfig]) -> anyhow::Result<String> {
+    let mut ret = String::new();
+    for (idx, config) in configs.iter().enumerate() {
+        if configs[..idx].iter().any(|other| other.name == config.name) {
+            anyhow::bail!(
+                "Process name `{}` is used by more than one version",
+                config.name
+            );
+        }
+        let program = config.program.to_string_lossy();
+        let command = match config.args.as_slice() {
+            [flag, command] if program == SHELL && flag == "-c" => {
+                if command.contains(['\n', '\r']) {
+                    anyhow::bail!("Command of `{}` spans more than one line", config.name);
+                }
+                command.replace("$$", "$")
+            }
+            _ => shlex::try_join(
+                std::iter::once(program.as_ref()).chain(config.args.iter().map(String::as_str)),
+            )?,
+        };
+        ret.push_str(&config.name);
+        ret.push_str(": ");
+        ret.push_str(&command);
+        ret.push('\n');
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn parse(content: &str) -> anyhow::Result<Vec<ServiceConfig>> {
+        parse_procfile(content, Path::new("/app"), "1", &BTreeMap::new())
+    }
+
+    #[test]
+    fn processes_in_file_order() {
+        let env = BTreeMap::from([("A".to_string(), "1".to_string())]);
+        let configs = parse_procfile(
+            "# comment\n\nweb: bundle exec rails s -p 3000\nworker_1: echo \"a b\"; sleep 1\n",
+            Path::new("/app"),
+            "2",
+            &env,
+        )
+        .unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].name, "web");
+        assert_eq!(configs[0].version, "2");
+        assert_eq!(configs[0].program, PathBuf::from("sh"));
+        assert_eq!(configs[0].args, ["-c", "bundle exec rails s -p 3000"]);
+        assert_eq!(configs[0].env, env);
+        assert_eq!(configs[0].workspace, Some(PathBuf::from("/app")));
+        assert_eq!(configs[1].name, "worker_1");
+        assert_eq!(configs[1].args, ["-c", "echo \"a b\"; sleep 1"]);
+    }
+
+    #[test]
+    fn command_may_contain_colons() {
+        let configs = parse("web: server --listen 0.0.0.0:80").unwrap();
+        assert_eq!(configs[0].args, ["-c", "server --listen 0.0.0.0:80"]);
+    }
+
+    #[test]
+    fn shell_syntax_is_kept_and_dollars_escaped() {
+        let configs = parse("web: cd app && ./server -p $PORT ${HOST} 2>&1 | tee log").unwrap();
+        assert_eq!(
+            configs[0].args,
+            ["-c", "cd app && ./server -p $$PORT $${HOST} 2>&1 | tee log"]
+        );
+    }
+
+    #[test]
+    fn malformed_lines() {
+        for (content, message) in [
+            ("web ./server", "line 1: expected"),
+            ("\nweb.1: ./server", "line 2: invalid process name"),
+            (": ./server", "invalid process name"),
+            ("web: ./a\nweb: ./b", "line 2: duplicate process `web`"),
+            ("web:   ", "has no command"),
+        ] {
+            let e = parse(content).unwrap_err().to_string();
+            assert!(e.contains(message), "{content:?}: {e}");
+        }
+    }
+
+    #[test]
+    fn render_round_trip() {
+        let content = "web: ./server --name 'my app' -p $PORT && echo $$\nworker: ./worker\n";
+        let configs = parse(content).unwrap();
+        assert_eq!(render_procfile(&configs).unwrap(), content);
+    }
+
+    #[test]
+    fn render_quotes_other_programs() {
+        let config = ServiceConfig {
+            name: "web".to_string(),
+            version: "1".to_string(),
+            program: "./server".into(),
+            args: vec!["my app".to_string(), "it's".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            render_procfile(&[config]).unwrap(),
+            "web: ./server 'my app' \"it's\"\n"
+        );
+    }
+
+    #[test]
+    fn render_rejects_duplicate_names_and_nul() {
+        let config = |version: &str, arg: &str| ServiceConfig {
+            name: "web".to_string(),
+            version: version.to_string(),
+            program: "./server".into(),
+            args: vec![arg.to_string()],
+            ..Default::default()
+        };
+        assert!(render_procfile(&[config("1", "a"), config("2", "a")]).is_err());
+        assert!(render_procfile(&[config("1", "a\0b")]).is_err());
+        let multiline = ServiceConfig {
+            program: "sh".into(),
+            args: vec!["-c".to_string(), "a\nb".to_string()],
+            ..config("1", "a")
+        };
+        assert!(render_procfile(&[multiline]).is_err());
+    }
+}
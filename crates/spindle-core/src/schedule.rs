@@ -0,0 +1,433 @@
+//! Cron schedules of periodic services.
+//!
+//! Expressions have the five classic fields `minute hour day-of-month month day-of-week`, each a
+//! `*`, a value, a range `a-b` or a comma-separated list of those, optionally with a `/step`.
+//! Months and weekdays also take three-letter names (`jan`, `mon`); Sunday is `0` or `7`. As in
+//! Vixie cron, a day matches if either day field matches when both are restricted. The macros
+//! `@yearly`, `@annually`, `@monthly`, `@weekly`, `@daily`, `@midnight` and `@hourly` are
+//! accepted as well. Schedules are evaluated in local time.
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike,
+};
+use serde::{Deserialize, Serialize};
+
+/// What to do when a scheduled run is due while the previous run is still going.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlapPolicy {
+    /// Drop the due run.
+    #[default]
+    Skip,
+    /// Run once more as soon as the previous run finishes; further due runs are dropped while
+    /// one is queued.
+    Queue,
+}
+
+impl OverlapPolicy {
+    /// Returns the stable string form, as used in serialization.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Queue => "queue",
+        }
+    }
+
+    /// Parses a policy name as returned by [Self::as_str].
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "skip" => Some(Self::Skip),
+            "queue" => Some(Self::Queue),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed cron expression; each field is a bit set of the values it matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expr: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day-of-month field is not `*`.
+    dom_restricted: bool,
+    /// Whether the day-of-week field is not `*`.
+    dow_restricted: bool,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Years searched for the next run before a schedule is considered to never fire
+/// (e.g. `0 0 31 2 *`).
+const MAX_SEARCH_YEARS: i32 = 5;
+
+/// Parses one value of a field, by number or by name (`names[i]` is `min + i`).
+fn parse_value(value: &str, min: u32, max: u32, names: &[&str]) -> Result<u32, String> {
+    if let Some(idx) = names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(value))
+    {
+        return Ok(min + idx as u32);
+    }
+    match value.parse::<u32>() {
+        Ok(number) if (min..=max).contains(&number) => Ok(number),
+        _ => Err(format!("{value:?} is not in {min}-{max}")),
+    }
+}
+
+/// Parses one field into a bit set of the values it matches.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let mut ret = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("invalid step in {item:?}")),
+            },
+            None => (item, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, min, max, names)?,
+                parse_value(end, min, max, names)?,
+            )
+        } else {
+            let start = parse_value(range, min, max, names)?;
+            // `a/step` runs from `a` to the end of the field.
+            (start, if item.contains('/') { max } else { start })
+        };
+        if start > end {
+            return Err(format!("empty range {range:?}"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            ret |= 1 << value;
+        }
+    }
+    Ok(ret)
+}
+
+impl CronSchedule {
+    /// Parses a cron expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - Five-field cron expression or macro such as `@daily`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(schedule)` on success, or `Err(message)` describing the invalid field.
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expanded = match expr.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err(format!(
+                "Invalid schedule {expr:?}: expected 5 fields, got {}",
+                fields.len()
+            ));
+        };
+        let invalid = |field: &str, e: String| format!("Invalid schedule {expr:?}: {field}: {e}");
+        let mut days_of_week =
+            parse_field(dow, 0, 7, &WEEKDAY_NAMES).map_err(|e| invalid("day of week", e))?;
+        // Sunday may be given as 7.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            expr: expr.trim().to_string(),
+            minutes: parse_field(minute, 0, 59, &[]).map_err(|e| invalid("minute", e))?,
+            hours: parse_field(hour, 0, 23, &[]).map_err(|e| invalid("hour", e))?,
+            days_of_month: parse_field(dom, 1, 31, &[]).map_err(|e| invalid("day of month", e))?,
+            months: parse_field(month, 1, 12, &MONTH_NAMES).map_err(|e| invalid("month", e))?,
+            days_of_week,
+            dom_restricted: !dom.starts_with('*'),
+            dow_restricted: !dow.starts_with('*'),
+        })
+    }
+
+    /// Returns the expression the schedule was parsed from.
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = self.days_of_month & (1 << date.day()) != 0;
+        let dow = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            _ => dom && dow,
+        }
+    }
+
+    /// Returns the first time after `after` (exclusive) that the schedule fires.
+    ///
+    /// Times skipped by a DST change never fire; times repeated by one fire once.
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - Time to search from, in the time zone the schedule is evaluated in.
+    ///
+    /// # Returns
+    ///
+    /// `Some(time)` of the next run, or `None` if there is none within the next years.
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let start = after.naive_local();
+        let mut t: NaiveDateTime =
+            start.date().and_hms_opt(start.hour(), start.minute(), 0)? + Duration::minutes(1);
+        let last_year = start.year() + MAX_SEARCH_YEARS;
+        while t.year() <= last_year {
+            if self.months & (1 << t.month()) == 0 {
+                let (year, month) = match t.month() {
+                    12 => (t.year() + 1, 1),
+                    month => (t.year(), month + 1),
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if self.hours & (1 << t.hour()) == 0 {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + Duration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+                continue;
+            }
+            let candidate = match after.timezone().from_local_datetime(&t) {
+                LocalResult::Single(time) => Some(time),
+                LocalResult::Ambiguous(earliest, _) => Some(earliest),
+                LocalResult::None => None,
+            };
+            if let Some(time) = candidate
+                && time > *after
+            {
+                return Some(time);
+            }
+            t += Duration::minutes(1);
+        }
+        None
+    }
+}
+
+impl std::fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, Utc};
+
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn next(expr: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        CronSchedule::parse(expr).unwrap().next_after(&after)
+    }
+
+    #[test]
+    fn star_with_step() {
+        let after = utc(2025, 6, 2, 10, 7);
+        assert_eq!(next("*/15 * * * *", after), Some(utc(2025, 6, 2, 10, 15)));
+        let after = utc(2025, 6, 2, 10, 45);
+        assert_eq!(next("*/15 * * * *", after), Some(utc(2025, 6, 2, 11, 0)));
+    }
+
+    #[test]
+    fn value_with_step_runs_to_end_of_field() {
+        let schedule = CronSchedule::parse("5/20 * * * *").unwrap();
+        let mut after = utc(2025, 6, 2, 10, 0);
+        let mut minutes = Vec::new();
+        for _ in 0..4 {
+            after = schedule.next_after(&after).unwrap();
+            minutes.push(after.minute());
+        }
+        assert_eq!(minutes, [5, 25, 45, 5]);
+    }
+
+    #[test]
+    fn month_and_weekday_names() {
+        // 2025-01-04 is a Saturday.
+        let after = utc(2025, 1, 4, 12, 0);
+        assert_eq!(
+            next("0 9 * JAN-mar mon-fri", after),
+            Some(utc(2025, 1, 6, 9, 0))
+        );
+        assert!(CronSchedule::parse("0 9 * * funday").is_err());
+    }
+
+    #[test]
+    fn sunday_as_seven() {
+        // 2025-06-02 is a Monday.
+        let after = utc(2025, 6, 2, 0, 0);
+        assert_eq!(next("0 0 * * 7", after), Some(utc(2025, 6, 8, 0, 0)));
+        assert_eq!(next("0 0 * * 7", after), next("0 0 * * 0", after));
+        assert_eq!(next("0 0 * * 5-7", after), Some(utc(2025, 6, 6, 0, 0)));
+    }
+
+    #[test]
+    fn day_fields_match_either_when_both_restricted() {
+        // 2025-06-01 is a Sunday; 2025-06-06 the first Friday.
+        let after = utc(2025, 6, 1, 0, 0);
+        assert_eq!(next("0 0 13 * 5", after), Some(utc(2025, 6, 6, 0, 0)));
+        assert_eq!(next("0 0 13 * *", after), Some(utc(2025, 6, 13, 0, 0)));
+        assert_eq!(next("0 0 * * 5", after), Some(utc(2025, 6, 6, 0, 0)));
+        // A `*/n` day field is unrestricted, so both fields must match.
+        assert_eq!(next("0 0 */1 * 5", after), Some(utc(2025, 6, 6, 0, 0)));
+    }
+
+    #[test]
+    fn macros() {
+        let after = utc(2025, 6, 2, 10, 30);
+        assert_eq!(next("@hourly", after), Some(utc(2025, 6, 2, 11, 0)));
+        assert_eq!(next("@daily", after), Some(utc(2025, 6, 3, 0, 0)));
+        assert_eq!(next("@midnight", after), next("@daily", after));
+        assert_eq!(next("@weekly", after), Some(utc(2025, 6, 8, 0, 0)));
+        assert_eq!(next("@monthly", after), Some(utc(2025, 7, 1, 0, 0)));
+        assert_eq!(next("@yearly", after), Some(utc(2026, 1, 1, 0, 0)));
+        assert_eq!(next("@annually", after), next("@yearly", after));
+    }
+
+    #[test]
+    fn never_firing_schedule() {
+        assert_eq!(next("0 0 31 2 *", utc(2025, 1, 1, 0, 0)), None);
+        assert_eq!(next("0 0 30 2 *", utc(2025, 1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        for expr in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "30-10 * * * *",
+            "@reboot",
+        ] {
+            assert!(CronSchedule::parse(expr).is_err(), "{expr:?}");
+        }
+    }
+
+    /// UTC+1 with DST at UTC+2 in 2025, switching like Central European Time: local 02:00-03:00
+    /// is skipped on 2025-03-30 and repeated on 2025-10-26.
+    #[derive(Debug, Clone, Copy)]
+    struct DstZone;
+
+    impl DstZone {
+        fn winter() -> FixedOffset {
+            FixedOffset::east_opt(3600).unwrap()
+        }
+
+        fn summer() -> FixedOffset {
+            FixedOffset::east_opt(7200).unwrap()
+        }
+
+        fn local(month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2025, month, day)
+                .unwrap()
+                .and_hms_opt(hour, minute, 0)
+                .unwrap()
+        }
+    }
+
+    impl TimeZone for DstZone {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            DstZone
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let gap = Self::local(3, 30, 2, 0)..Self::local(3, 30, 3, 0);
+            let fold = Self::local(10, 26, 2, 0)..Self::local(10, 26, 3, 0);
+            if gap.contains(local) {
+                LocalResult::None
+            } else if fold.contains(local) {
+                LocalResult::Ambiguous(Self::summer(), Self::winter())
+            } else if (gap.end..fold.start).contains(local) {
+                LocalResult::Single(Self::summer())
+            } else {
+                LocalResult::Single(Self::winter())
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            if (Self::local(3, 30, 1, 0)..Self::local(10, 26, 1, 0)).contains(utc) {
+                Self::summer()
+            } else {
+                Self::winter()
+            }
+        }
+    }
+
+    fn dst(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<DstZone> {
+        DstZone
+            .from_local_datetime(&DstZone::local(month, day, hour, minute))
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn skipped_time_never_fires() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let next = schedule.next_after(&dst(3, 29, 12, 0)).unwrap();
+        assert_eq!(next.naive_local(), DstZone::local(3, 31, 2, 30));
+        // Times after the gap on the same day still fire.
+        let schedule = CronSchedule::parse("*/30 * * * *").unwrap();
+        let next = schedule.next_after(&dst(3, 30, 1, 45)).unwrap();
+        assert_eq!(next.naive_local(), DstZone::local(3, 30, 3, 0));
+        assert_eq!(next.offset(), &DstZone::summer());
+    }
+
+    #[test]
+    fn repeated_time_fires_once() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let first = schedule.next_after(&dst(10, 26, 0, 0)).unwrap();
+        assert_eq!(first.naive_local(), DstZone::local(10, 26, 2, 30));
+        assert_eq!(first.offset(), &DstZone::summer());
+        let second = schedule.next_after(&first).unwrap();
+        assert_eq!(second.naive_local(), DstZone::local(10, 27, 2, 30));
+        // An hourly schedule skips the repeated hour rather than firing at 02:00 twice.
+        let schedule = CronSchedule::parse("0 * * * *").unwrap();
+        let first = schedule.next_after(&dst(10, 26, 1, 30)).unwrap();
+        assert_eq!(first.naive_local(), DstZone::local(10, 26, 2, 0));
+        let second = schedule.next_after(&first).unwrap();
+        assert_eq!(second.naive_local(), DstZone::local(10, 26, 3, 0));
+        assert_eq!(second.offset(), &DstZone::winter());
+    }
+}
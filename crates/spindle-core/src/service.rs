@@ -1,54 +1,511 @@
 //! Service definitions, scanning, and lifecycle management (ServiceManager).
 
 use std::{
-    collections::HashMap,
-    path::PathBuf,
-    sync::{Arc, Weak},
-    time::Duration,
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use petgraph::{
     Graph,
     graph::{DiGraph, NodeIndex},
     prelude::StableDiGraph,
     unionfind::UnionFind,
-    visit::{EdgeRef, NodeIndexable},
+    visit::{EdgeFiltered, EdgeRef, NodeIndexable},
 };
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt},
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+};
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
+use crate::{
+    detach::DetachedRecord,
+    hooks::{HookStage, ServiceHooks},
+    identity::ProcessIdentity,
+    interpolate::{VariableScope, escape, interpolate},
+    limits::{ResourceLimits, ServiceCgroup},
+    schedule::{CronSchedule, OverlapPolicy},
+    service_log::{
+        DEFAULT_MAX_LOG_FILES, DetachedLogRotation, LogRotation, OutputStream, ServiceLogOptions,
+        ServiceLogWriter, ServiceOutputLine,
+    },
+    watch::{WatchConfig, WatchSpec},
+};
+
 /// Unique key for a service: (name, version).
 pub type ServiceKey = (Arc<str>, Arc<str>);
 
-/// Configuration for a single service (name, version, program, args, dependencies, workspace).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Configuration for a single service (name, version, program, args, env, dependencies, workspace).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ServiceConfig {
     pub name: String,
     pub version: String,
     pub program: PathBuf,
     #[serde(default)]
     pub args: Vec<String>,
+    /// Extra environment variables, added on top of the inherited environment.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
     #[serde(default)]
     pub dependencies: Vec<(String, String)>,
     pub workspace: Option<PathBuf>,
+    /// Instance names if this is a template (name ending in `@`); each instance runs as the
+    /// service `<name><instance>` with `${instance}` available for interpolation.
+    #[serde(default)]
+    pub instances: Vec<String>,
+    /// Number of copies to run; each replica gets its index as `SPINDLE_REPLICA` and `${replica}`.
+    /// `None` runs the service as a single process.
+    #[serde(default)]
+    pub replicas: Option<u32>,
+    /// Replicas that must be running for dependents to see the service as running; defaults to all.
+    #[serde(default)]
+    pub quorum: Option<u32>,
+    /// Named TCP ports, bound-checked or picked before each spawn. The service gets them as
+    /// `SPINDLE_PORT_<PORT>` and `${port.<port>}`, dependents as `SPINDLE_DEP_<NAME>_PORT_<PORT>`
+    /// and `${dep.<name>.port.<port>}`.
+    #[serde(default)]
+    pub ports: BTreeMap<String, PortSpec>,
+    /// Named overlays (e.g. `dev`, `test`) applied when the service's group runs with that profile.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileOverlay>,
+    /// User (name or uid) to run the process as; requires privileges unless it is Spindle's own user.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Group (name or gid) to run the process as; defaults to the primary group of `user`.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Octal file mode creation mask, e.g. `027`.
+    #[serde(default)]
+    pub umask: Option<String>,
+    /// Rlimits and cgroup limits of the process.
+    #[serde(default)]
+    pub limits: ResourceLimits,
+    /// Keep running when the service manager is dropped (e.g. the app exits) and be adopted by
+    /// the next one; needs [ServiceManagerOptions::state_dir] and `/proc` for process start
+    /// times.
+    #[serde(default)]
+    pub detached: bool,
+    /// Commands run before start, after start, before stop and after exit, see [crate::hooks].
+    #[serde(default)]
+    pub hooks: ServiceHooks,
+    /// Cron expression the service runs at, in local time, see [crate::schedule]. Scheduled
+    /// services run to completion: a successful exit stops them instead of failing them, and
+    /// they are not started with their group.
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// What to do when a scheduled run is due while the previous one still runs.
+    #[serde(default)]
+    pub overlap: OverlapPolicy,
+    /// Files whose changes restart the service while it runs, see [crate::watch].
+    #[serde(default)]
+    pub watch: Option<WatchConfig>,
+}
+
+/// Overrides applied to a service when a profile is active; unset fields keep the base value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileOverlay {
+    /// Replaces the args.
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    /// Added to the env, replacing variables of the same name.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Replaces the workspace; `${workspace}` refers to the base workspace.
+    #[serde(default)]
+    pub workspace: Option<PathBuf>,
+    /// Replaces the dependencies.
+    #[serde(default)]
+    pub dependencies: Option<Vec<(String, String)>>,
+}
+
+/// How a named port is chosen: a fixed number, or `"auto"` for any free port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "PortSpecRepr", into = "PortSpecRepr")]
+pub enum PortSpec {
+    Auto,
+    Fixed(u16),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PortSpecRepr {
+    Fixed(u16),
+    Keyword(String),
+}
+
+impl TryFrom<PortSpecRepr> for PortSpec {
+    type Error = String;
+
+    fn try_from(repr: PortSpecRepr) -> Result<Self, Self::Error> {
+        match repr {
+            PortSpecRepr::Fixed(port) => Ok(Self::Fixed(port)),
+            PortSpecRepr::Keyword(keyword) if keyword == "auto" => Ok(Self::Auto),
+            PortSpecRepr::Keyword(keyword) => Err(format!(
+                "expected a port number or \"auto\", got {keyword:?}"
+            )),
+        }
+    }
+}
+
+impl From<PortSpec> for PortSpecRepr {
+    fn from(spec: PortSpec) -> Self {
+        match spec {
+            PortSpec::Auto => Self::Keyword("auto".to_string()),
+            PortSpec::Fixed(port) => Self::Fixed(port),
+        }
+    }
+}
+
+/// Returns the env var form of a port or service name: uppercase, other characters as `_`.
+fn env_var_segment(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Checks the replica count and quorum of a service.
+///
+/// # Arguments
+///
+/// * `replicas` - Number of replicas; `None` for a single process.
+/// * `quorum` - Replicas required to count as running; `None` for all.
+///
+/// # Returns
+///
+/// `Ok(())` if valid, or `Err(message)` naming the invalid setting.
+pub fn validate_replicas(replicas: Option<u32>, quorum: Option<u32>) -> Result<(), String> {
+    if replicas == Some(0) {
+        return Err("Invalid replicas: must be at least 1".to_string());
+    }
+    match (quorum, replicas) {
+        (Some(0), _) => Err("Invalid quorum: must be at least 1".to_string()),
+        (Some(quorum), Some(replicas)) if quorum > replicas => Err(format!(
+            "Invalid quorum: {quorum} is more than the {replicas} replicas"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that replicas do not share a fixed port, which only one of them could bind.
+///
+/// # Arguments
+///
+/// * `replicas` - Number of replicas; `None` for a single process.
+/// * `ports` - Named ports of the service.
+///
+/// # Returns
+///
+/// `Ok(())` if valid, or `Err(message)` naming the first fixed port.
+fn validate_replica_ports<'a>(
+    replicas: Option<u32>,
+    mut ports: impl Iterator<Item = (&'a str, &'a PortSpec)>,
+) -> Result<(), String> {
+    if replicas.unwrap_or(1) <= 1 {
+        return Ok(());
+    }
+    match ports.find(|(_, spec)| matches!(spec, PortSpec::Fixed(_))) {
+        Some((port, _)) => Err(format!(
+            "Fixed port {port:?} cannot be bound by every replica; use \"auto\""
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Returns whether `name` is a valid port name: ASCII letters, digits, `_` and `-`.
+fn is_valid_port_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Binds every port of a service to check it is free, picking free ports for `auto`.
+///
+/// All listeners are held until every port is picked, so two `auto` ports never get the same number.
+/// They are closed on return; the service binds the ports itself.
+fn allocate_ports(ports: &BTreeMap<Arc<str>, PortSpec>) -> anyhow::Result<BTreeMap<String, u16>> {
+    let mut listeners = Vec::with_capacity(ports.len());
+    let mut ret = BTreeMap::new();
+    for (name, spec) in ports {
+        let port = match spec {
+            PortSpec::Auto => 0,
+            PortSpec::Fixed(port) => *port,
+        };
+        let listener = std::net::TcpListener::bind((std::net::Ipv4Addr::UNSPECIFIED, port))
+            .map_err(|e| match spec {
+                PortSpec::Auto => anyhow::anyhow!("Failed to allocate port {name}: {e}"),
+                PortSpec::Fixed(port) => {
+                    anyhow::anyhow!("Port {name} ({port}) is not available: {e}")
+                }
+            })?;
+        ret.insert(name.to_string(), listener.local_addr()?.port());
+        listeners.push(listener);
+    }
+    Ok(ret)
+}
+
+/// Returns a copy of `meta` with `substitute` applied to program, args, env values, workspace
+/// and hook commands.
+fn map_meta_values(meta: &ServiceMeta, substitute: impl Fn(&str) -> String) -> ServiceMeta {
+    ServiceMeta {
+        program: substitute(&meta.program.to_string_lossy()).into(),
+        args: meta.args.iter().map(|arg| substitute(arg).into()).collect(),
+        env: meta
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), substitute(value).into()))
+            .collect(),
+        workspace: meta
+            .workspace
+            .as_ref()
+            .map(|workspace| substitute(&workspace.to_string_lossy()).into()),
+        hooks: meta.hooks.map_commands(&substitute),
+        ..meta.clone()
+    }
+}
+
+/// Expands the templates of `meta` (see [ServiceMeta]) for use outside of spawning a process.
+///
+/// # Returns
+///
+/// `Ok(meta)`, or `Err(refs)` with the references only known at spawn time that `meta` uses.
+pub(crate) fn static_meta(meta: &ServiceMeta) -> Result<ServiceMeta, Vec<String>> {
+    let unresolved = std::cell::RefCell::new(Vec::new());
+    let ret = map_meta_values(meta, |value| {
+        interpolate(value, |_| None).unwrap_or_else(|names| {
+            unresolved.borrow_mut().extend(names);
+            value.to_string()
+        })
+    });
+    let mut unresolved = unresolved.into_inner();
+    if unresolved.is_empty() {
+        return Ok(ret);
+    }
+    unresolved.sort();
+    unresolved.dedup();
+    Err(unresolved)
+}
+
+/// Checks that `name` can be told apart from replica and instance names.
+///
+/// `#` is reserved for replica names (`api#0`); `@` may only end a template name (`worker@`)
+/// or separate it from an instance (`worker@emails`).
+///
+/// # Arguments
+///
+/// * `name` - Service, template or instance name.
+///
+/// # Returns
+///
+/// `Ok(())` if valid, or `Err(message)` naming the misused character.
+pub fn validate_service_name(name: &str) -> Result<(), String> {
+    if name.contains('#') {
+        return Err(format!(
+            "Invalid service name {name:?}: `#` is reserved for replicas"
+        ));
+    }
+    if name.starts_with('@') || name.matches('@').count() > 1 {
+        return Err(format!(
+            "Invalid service name {name:?}: `@` may only separate a template name from its instance"
+        ));
+    }
+    Ok(())
+}
+
+/// Splits an instance name such as `worker@emails` into its template name (`worker@`) and instance (`emails`).
+///
+/// # Arguments
+///
+/// * `name` - Service name.
+///
+/// # Returns
+///
+/// `Some((template, instance))` for instance names, or `None` for plain and template names.
+pub fn split_instance_name(name: &str) -> Option<(&str, &str)> {
+    let idx = name.find('@')?;
+    let (template, instance) = name.split_at(idx + 1);
+    if instance.is_empty() {
+        None
+    } else {
+        Some((template, instance))
+    }
+}
+
+/// Splits a replica name such as `api#2` into its service name (`api`) and replica index (`2`).
+///
+/// # Arguments
+///
+/// * `name` - Service name.
+///
+/// # Returns
+///
+/// `Some((name, index))` for replica names, or `None` otherwise.
+pub fn split_replica_name(name: &str) -> Option<(&str, u32)> {
+    let (base, index) = name.rsplit_once('#')?;
+    if base.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((base, index.parse().ok()?))
+}
+
+/// Returns the key of replica `index` of the service `key`.
+fn replica_key(key: &ServiceKey, index: u32) -> ServiceKey {
+    (format!("{}#{}", key.0, index).into(), key.1.clone())
+}
+
+/// Returns the meta of replica `index` with `SPINDLE_REPLICA` set; `${replica}` is expanded at
+/// spawn time by [ServiceManager::with_runtime_values].
+fn replica_meta(meta: &ServiceMeta, index: u32) -> ServiceMeta {
+    let mut ret = meta.clone();
+    ret.name = format!("{}#{}", meta.name, index).into();
+    ret.env
+        .insert("SPINDLE_REPLICA".into(), index.to_string().into());
+    ret.replicas = None;
+    ret.quorum = None;
+    ret
 }
 
-/// Immutable metadata for a service used at runtime (name, version, program, args, workspace).
+/// Immutable metadata for a service used at runtime (name, version, program, args, env, workspace).
+///
+/// Program, args, env values, workspace, hook commands and profile overlay values are `${VAR}`
+/// templates (see [crate::interpolate]) in which only the references known at spawn time are
+/// left: `${replica}`, `${port.*}` and `${dep.*.port.*}`.
 #[derive(Debug, Clone)]
 pub struct ServiceMeta {
     pub name: Arc<str>,
     pub version: Arc<str>,
     pub program: PathBuf,
     pub args: Vec<Arc<str>>,
+    pub env: BTreeMap<Arc<str>, Arc<str>>,
     pub workspace: Option<PathBuf>,
+    /// Number of replicas, see [ServiceConfig::replicas].
+    pub replicas: Option<u32>,
+    /// Replica quorum, see [ServiceConfig::quorum].
+    pub quorum: Option<u32>,
+    /// Named ports, see [ServiceConfig::ports].
+    pub ports: BTreeMap<Arc<str>, PortSpec>,
+    /// Profile overlays with expanded values, see [ServiceConfig::profiles].
+    pub profiles: BTreeMap<String, ProfileOverlay>,
+    /// Resolved user, group and umask, see [ServiceConfig::user].
+    pub identity: ProcessIdentity,
+    /// Resource limits, see [ServiceConfig::limits].
+    pub limits: ResourceLimits,
+    /// Detached run mode, see [ServiceConfig::detached].
+    pub detached: bool,
+    /// Lifecycle hooks, see [ServiceConfig::hooks].
+    pub hooks: ServiceHooks,
+    /// Parsed schedule, see [ServiceConfig::schedule].
+    pub schedule: Option<CronSchedule>,
+    /// Overlap policy of scheduled runs, see [ServiceConfig::overlap].
+    pub overlap: OverlapPolicy,
+    /// Watched files, see [ServiceConfig::watch].
+    pub watch: Option<WatchConfig>,
+}
+
+/// Returns `meta` with the overlay of `profile` applied and `SPINDLE_PROFILE` set.
+///
+/// Services without an overlay for `profile` run with their base values.
+fn profile_meta(meta: &ServiceMeta, profile: Option<&str>) -> ServiceMeta {
+    let mut ret = meta.clone();
+    let Some(profile) = profile else {
+        return ret;
+    };
+    if let Some(overlay) = meta.profiles.get(profile) {
+        if let Some(args) = &overlay.args {
+            ret.args = args.iter().map(|arg| arg.as_str().into()).collect();
+        }
+        ret.env.extend(
+            overlay
+                .env
+                .iter()
+                .map(|(key, value)| (key.as_str().into(), value.as_str().into())),
+        );
+        if let Some(workspace) = &overlay.workspace {
+            ret.workspace = Some(workspace.clone());
+        }
+    }
+    ret.env
+        .insert("SPINDLE_PROFILE".into(), escape(profile).into());
+    ret
 }
 
 struct ExtractedService {
     meta: ServiceMeta,
-    deps: Vec<ServiceKey>,
+    deps: Vec<(ServiceKey, DependencyEdge)>,
+}
+
+/// Profiles in which a dependency applies; the graph holds the union over all profiles.
+#[derive(Debug, Clone, Default)]
+struct DependencyEdge {
+    /// Whether the dependency is in the base config.
+    in_base: bool,
+    /// Whether the dependency is listed, for each profile that replaces the dependencies.
+    profiles: HashMap<String, bool>,
+}
+
+impl DependencyEdge {
+    fn is_active(&self, profile: Option<&str>) -> bool {
+        profile
+            .and_then(|profile| self.profiles.get(profile).copied())
+            .unwrap_or(self.in_base)
+    }
+}
+
+/// Returns `graph` restricted to the dependency edges active in `profile`.
+fn active_graph<'a>(
+    graph: &'a StableDiGraph<ServiceMeta, DependencyEdge>,
+    profile: Option<&'a str>,
+) -> EdgeFiltered<
+    &'a StableDiGraph<ServiceMeta, DependencyEdge>,
+    impl Fn(petgraph::stable_graph::EdgeReference<'a, DependencyEdge>) -> bool + 'a,
+> {
+    EdgeFiltered::from_fn(graph, move |edge| edge.weight().is_active(profile))
+}
+
+/// Returns every dependency of `config` in the base config or any profile, with where it applies.
+fn dependency_edges(config: &ServiceConfig) -> Vec<(ServiceKey, DependencyEdge)> {
+    let mut all_deps: Vec<&(String, String)> = Vec::new();
+    let profile_deps = config
+        .profiles
+        .values()
+        .filter_map(|overlay| overlay.dependencies.as_ref());
+    for dep in config.dependencies.iter().chain(profile_deps.flatten()) {
+        if !all_deps.contains(&dep) {
+            all_deps.push(dep);
+        }
+    }
+    all_deps
+        .into_iter()
+        .map(|dep| {
+            let edge = DependencyEdge {
+                in_base: config.dependencies.contains(dep),
+                profiles: config
+                    .profiles
+                    .iter()
+                    .filter_map(|(profile, overlay)| {
+                        let deps = overlay.dependencies.as_ref()?;
+                        Some((profile.clone(), deps.contains(dep)))
+                    })
+                    .collect(),
+            };
+            ((dep.0.as_str().into(), dep.1.as_str().into()), edge)
+        })
+        .collect()
 }
 
 /// Runtime state of a service.
@@ -77,11 +534,167 @@ impl ToString for ServiceState {
     }
 }
 
+/// Runtime record of a service's most recent run (PID, timestamps, exit status, restarts).
+///
+/// Timestamps are milliseconds since the Unix epoch. `exit_code` and `signal` are mutually
+/// exclusive: a process either exits with a code or is terminated by a signal (Unix only).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ServiceRuntimeInfo {
+    /// PID of the running process; `None` when not running.
+    pub pid: Option<u32>,
+    /// When the current or last run was spawned.
+    pub started_at: Option<u64>,
+    /// When the last run ended; `None` while running or if never run.
+    pub stopped_at: Option<u64>,
+    /// Exit code of the last run, if it exited normally.
+    pub exit_code: Option<i32>,
+    /// Signal that terminated the last run, if any.
+    pub signal: Option<i32>,
+    /// Number of times the service was launched again after its first launch.
+    pub restart_count: u32,
+    /// Reason of the most recent failure, kept across later successful runs.
+    pub last_failure_reason: Option<String>,
+    /// What triggered the current or last run; `None` if never launched.
+    pub triggered_by: Option<ServiceRunTrigger>,
+    /// Port numbers allocated for the current or last run, by port name.
+    pub ports: BTreeMap<String, u16>,
+}
+
+/// What caused a service run to be launched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceRunTrigger {
+    /// A single service launch via [ServiceManager::launch_service].
+    Manual,
+    /// A group launch via [ServiceManager::launch_group].
+    Group,
+    /// A detached service that was running already and got adopted by a new [ServiceManager].
+    Adopted,
+    /// A tick of the service's schedule, see [ServiceConfig::schedule].
+    Schedule,
+    /// A change to the service's watched files, see [ServiceConfig::watch].
+    Watch,
+}
+
+impl ServiceRunTrigger {
+    /// Returns the stable string form, as used in serialization.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Group => "group",
+            Self::Adopted => "adopted",
+            Self::Schedule => "schedule",
+            Self::Watch => "watch",
+        }
+    }
+}
+
+/// A finished service run, sent to [ServiceManagerOptions::run_records] when a run stops or crashes.
+#[derive(Debug, Clone)]
+pub struct ServiceRunRecord {
+    pub key: ServiceKey,
+    /// Spawn time in milliseconds since the Unix epoch; `None` if the process never spawned.
+    pub started_at: Option<u64>,
+    /// Stop time in milliseconds since the Unix epoch.
+    pub stopped_at: u64,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    /// Failure reason; `None` for runs stopped on request.
+    pub reason: Option<String>,
+    pub triggered_by: ServiceRunTrigger,
+}
+
+/// Builds the [ServiceRunRecord] of the run of `service_key` described by `runtime`.
+fn run_record(
+    service_key: &ServiceKey,
+    runtime: &ServiceRuntimeInfo,
+    reason: Option<String>,
+) -> ServiceRunRecord {
+    ServiceRunRecord {
+        key: service_key.clone(),
+        started_at: runtime.started_at,
+        stopped_at: runtime.stopped_at.unwrap_or_else(unix_millis_now),
+        exit_code: runtime.exit_code,
+        signal: runtime.signal,
+        reason,
+        triggered_by: runtime.triggered_by.unwrap_or(ServiceRunTrigger::Manual),
+    }
+}
+
+/// Returns the current time as milliseconds since the Unix epoch.
+fn unix_millis_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Signal a process gets when it exceeds `RLIMIT_CPU`.
+#[cfg(unix)]
+const SIGXCPU: i32 = libc::SIGXCPU;
+#[cfg(not(unix))]
+const SIGXCPU: i32 = 24;
+
+/// Splits an [std::process::ExitStatus] into (exit code, terminating signal).
+fn exit_status_parts(exit_status: &std::process::ExitStatus) -> (Option<i32>, Option<i32>) {
+    #[cfg(unix)]
+    let signal = std::os::unix::process::ExitStatusExt::signal(exit_status);
+    #[cfg(not(unix))]
+    let signal = None;
+    (exit_status.code(), signal)
+}
+
 struct ServiceGroup {
-    pub graph: StableDiGraph<ServiceMeta, ()>,
+    pub graph: StableDiGraph<ServiceMeta, DependencyEdge>,
     pub nodeidx_map: HashMap<ServiceKey, NodeIndex>,
 }
 
+impl ServiceGroup {
+    /// Returns the neighbours of `nodeidx` along the dependency edges active in `profile`.
+    fn active_neighbors(
+        &self,
+        nodeidx: NodeIndex,
+        direction: petgraph::Direction,
+        profile: Option<&str>,
+    ) -> Vec<NodeIndex> {
+        self.graph
+            .edges_directed(nodeidx, direction)
+            .filter(|edge| edge.weight().is_active(profile))
+            .map(|edge| match direction {
+                petgraph::Incoming => edge.source(),
+                petgraph::Outgoing => edge.target(),
+            })
+            .collect()
+    }
+
+    /// Returns the nodes in dependency order along the dependency edges active in `profile`.
+    fn toposort(&self, profile: Option<&str>) -> anyhow::Result<Vec<NodeIndex>> {
+        petgraph::algo::toposort(&active_graph(&self.graph, profile), None)
+            .map_err(|e| anyhow::anyhow!("Failed to get toposort: {:?}", e))
+    }
+}
+
+/// Computes the shutdown level of `node_idx` (see [ServiceManager::shutdown]) and of its
+/// dependents into `level_map`.
+fn group_shutdown_level(
+    group: &ServiceGroup,
+    node_idx: NodeIndex,
+    profile: Option<&str>,
+    level_map: &mut HashMap<NodeIndex, usize>,
+) -> usize {
+    if let Some(level) = level_map.get(&node_idx) {
+        return *level;
+    }
+    let level = group
+        .active_neighbors(node_idx, petgraph::Outgoing, profile)
+        .into_iter()
+        .map(|dependent| group_shutdown_level(group, dependent, profile, level_map) + 1)
+        .max()
+        .unwrap_or(0);
+    level_map.insert(node_idx, level);
+    level
+}
+
 /// Item in the dead-letter queue: a service that could not be started or was removed (key, reason, meta).
 pub struct DeadLetterQueueItem {
     pub key: ServiceKey,
@@ -89,19 +702,223 @@ pub struct DeadLetterQueueItem {
     pub meta: ServiceMeta,
 }
 
+/// Settings applied to all services of a [ServiceManager].
+#[derive(Debug, Clone, Default)]
+pub struct ServiceManagerOptions {
+    /// User-defined global variables for `${VAR}` interpolation, see [crate::interpolate].
+    pub variables: HashMap<String, String>,
+    /// Profile groups run with until another one is selected, see [ServiceConfig::profiles].
+    pub profile: Option<String>,
+    /// Directory for the records of detached services, and their output logs without
+    /// `service_logs`, see [ServiceConfig::detached]; without it, detached services run attached.
+    pub state_dir: Option<PathBuf>,
+    /// Where to write the captured output of services, see [crate::service_log]; without it,
+    /// services inherit Spindle's stdout and stderr. Detached services write to their log file
+    /// here too, or in the state directory without it, see [crate::service_log].
+    pub service_logs: Option<ServiceLogOptions>,
+    /// Receives a [ServiceRunRecord] whenever a service run stops or crashes. The manager waits
+    /// for room in the channel rather than dropping records, so its receiver must be drained.
+    pub run_records: Option<mpsc::Sender<ServiceRunRecord>>,
+}
+
+/// Expands `${VAR}` in workspace, program, args, env values, hook commands and watch patterns of
+/// `config`.
+///
+/// `${workspace}` refers to the expanded workspace, so the workspace itself cannot use it.
+/// `${replica}`, `${port.*}` and `${dep.*.port.*}` are only known at spawn time, so all values
+/// but watch patterns are left as templates that keep them, expanded in one pass by
+/// [ServiceManager::with_runtime_values]. Watch patterns cannot use them.
+/// On failure `config` is left unchanged and the unresolved references are returned.
+fn interpolate_config(
+    config: &mut ServiceConfig,
+    variables: &HashMap<String, String>,
+    port_names: &HashMap<(String, String), Vec<String>>,
+) -> Vec<String> {
+    let mut scope = VariableScope::new(&config.name, &config.version, variables);
+    if let Some((_, instance)) = split_instance_name(&config.name) {
+        scope.set_builtin("instance", instance.to_string());
+    }
+    if config.replicas.is_some() {
+        scope.defer("replica");
+    }
+    for port in config.ports.keys() {
+        scope.defer(format!("port.{port}"));
+    }
+    let profile_deps = config
+        .profiles
+        .values()
+        .filter_map(|overlay| overlay.dependencies.as_ref());
+    for (dep_name, dep_version) in config.dependencies.iter().chain(profile_deps.flatten()) {
+        let key = (dep_name.clone(), dep_version.clone());
+        for port in port_names.get(&key).into_iter().flatten() {
+            scope.defer(format!("dep.{dep_name}.port.{port}"));
+        }
+    }
+    let mut unresolved = Vec::new();
+    let mut expand = |scope: &VariableScope, value: &str| match scope.expand_template(value) {
+        Ok(expanded) => expanded,
+        Err(names) => {
+            unresolved.extend(names);
+            value.to_string()
+        }
+    };
+    let workspace = config
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.to_str())
+        .map(|workspace| expand(&scope, workspace));
+    if let Some(workspace) = &workspace {
+        scope.set_template("workspace", workspace.clone());
+    }
+    let program = config
+        .program
+        .to_str()
+        .map(|program| expand(&scope, program));
+    let args: Vec<String> = config.args.iter().map(|arg| expand(&scope, arg)).collect();
+    let env: BTreeMap<String, String> = config
+        .env
+        .iter()
+        .map(|(key, value)| (key.clone(), expand(&scope, value)))
+        .collect();
+    let hooks = config.hooks.map_commands(|command| expand(&scope, command));
+    let watch = config
+        .watch
+        .as_ref()
+        .map(|watch| watch.map_patterns(|pattern| expand(&scope, pattern)));
+    let mut profiles = config.profiles.clone();
+    for overlay in profiles.values_mut() {
+        let overlay_workspace = overlay
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.to_str())
+            .map(|workspace| expand(&scope, workspace));
+        let mut overlay_scope = scope.clone();
+        if let Some(workspace) = &overlay_workspace {
+            overlay_scope.set_template("workspace", workspace.clone());
+            overlay.workspace = Some(workspace.into());
+        }
+        if let Some(args) = &mut overlay.args {
+            for arg in args.iter_mut() {
+                *arg = expand(&overlay_scope, arg);
+            }
+        }
+        for value in overlay.env.values_mut() {
+            *value = expand(&overlay_scope, value);
+        }
+    }
+    // Watch patterns are used outside of spawning, so their templates are expanded right away.
+    let watch = watch.map(|watch| {
+        watch.map_patterns(|pattern| {
+            interpolate(pattern, |_| None).unwrap_or_else(|names| {
+                unresolved.extend(names);
+                pattern.to_string()
+            })
+        })
+    });
+    if !unresolved.is_empty() {
+        unresolved.sort();
+        unresolved.dedup();
+        return unresolved;
+    }
+    if let Some(workspace) = workspace {
+        config.workspace = Some(workspace.into());
+    }
+    if let Some(program) = program {
+        config.program = program.into();
+    }
+    config.args = args;
+    config.env = env;
+    config.hooks = hooks;
+    config.watch = watch;
+    config.profiles = profiles;
+    unresolved
+}
+
 fn validate_service_name_unique(
     service_configs: Vec<ServiceConfig>,
+    options: &ServiceManagerOptions,
     dlq: &mut Vec<DeadLetterQueueItem>,
 ) -> HashMap<ServiceKey, ExtractedService> {
     let mut ret: HashMap<ServiceKey, ExtractedService> = HashMap::new();
-    for config in service_configs.into_iter() {
+    let port_names: HashMap<(String, String), Vec<String>> = service_configs
+        .iter()
+        .map(|config| {
+            (
+                (config.name.clone(), config.version.clone()),
+                config.ports.keys().cloned().collect(),
+            )
+        })
+        .collect();
+    for mut config in service_configs.into_iter() {
         let key: ServiceKey = (config.name.clone().into(), config.version.clone().into());
+        let invalid_name = validate_service_name(&config.name).err();
+        let unresolved = interpolate_config(&mut config, &options.variables, &port_names);
+        let invalid_port = config
+            .ports
+            .keys()
+            .find(|port| !is_valid_port_name(port))
+            .cloned();
+        let identity = crate::identity::resolve_identity(
+            config.user.as_deref(),
+            config.group.as_deref(),
+            config.umask.as_deref(),
+        );
+        let invalid_replicas = validate_replicas(config.replicas, config.quorum)
+            .and_then(|_| {
+                validate_replica_ports(
+                    config.replicas,
+                    config
+                        .ports
+                        .iter()
+                        .map(|(port, spec)| (port.as_str(), spec)),
+                )
+            })
+            .err();
+        let invalid_limits = config.limits.validate().err();
+        let invalid_hooks = config.hooks.validate().err();
+        let invalid_watch = config
+            .watch
+            .as_ref()
+            .and_then(|watch| watch.validate(config.workspace.as_deref()).err());
+        let schedule = config
+            .schedule
+            .as_deref()
+            .map(CronSchedule::parse)
+            .transpose();
+        let invalid_schedule = match &schedule {
+            Err(e) => Some(e.clone()),
+            Ok(Some(_)) if config.replicas.is_some() => {
+                Some("A scheduled service cannot have replicas".to_string())
+            }
+            _ => None,
+        };
+        let deps = dependency_edges(&config);
         let meta = ServiceMeta {
             name: key.0.clone(),
             version: key.1.clone(),
             program: config.program.into(),
             args: config.args.into_iter().map(|s| s.into()).collect(),
+            env: config
+                .env
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
             workspace: config.workspace,
+            replicas: config.replicas,
+            quorum: config.quorum,
+            ports: config
+                .ports
+                .into_iter()
+                .map(|(port, spec)| (port.into(), spec))
+                .collect(),
+            profiles: config.profiles,
+            identity: identity.clone().unwrap_or_default(),
+            limits: config.limits,
+            detached: config.detached,
+            hooks: config.hooks,
+            schedule: schedule.unwrap_or_default(),
+            overlap: config.overlap,
+            watch: config.watch,
         };
         if ret.contains_key(&key) {
             let reason = format!("Service {}:v{} is not unique", &*key.0, &*key.1);
@@ -113,11 +930,42 @@ fn validate_service_name_unique(
             });
             continue;
         }
-        let deps = config
-            .dependencies
-            .into_iter()
-            .map(|(n, v)| (n.into(), v.into()))
-            .collect();
+        if let Some(port) = invalid_port {
+            let reason = format!("Invalid port name: {port:?}");
+            warn!("name" = &*key.0, "version" = &*key.1, "{}", reason.clone());
+            dlq.push(DeadLetterQueueItem {
+                key: key.clone(),
+                reason,
+                meta,
+            });
+            continue;
+        }
+        if !unresolved.is_empty() {
+            let reason = format!("Unresolved variables: {}", unresolved.join(", "));
+            warn!("name" = &*key.0, "version" = &*key.1, "{}", reason.clone());
+            dlq.push(DeadLetterQueueItem {
+                key: key.clone(),
+                reason,
+                meta,
+            });
+            continue;
+        }
+        if let Some(reason) = invalid_name
+            .or(identity.err())
+            .or(invalid_replicas)
+            .or(invalid_limits)
+            .or(invalid_hooks)
+            .or(invalid_schedule)
+            .or(invalid_watch)
+        {
+            warn!("name" = &*key.0, "version" = &*key.1, "{}", reason.clone());
+            dlq.push(DeadLetterQueueItem {
+                key: key.clone(),
+                reason,
+                meta,
+            });
+            continue;
+        }
         ret.insert(key, ExtractedService { meta, deps });
     }
     ret
@@ -132,7 +980,7 @@ fn validate_service_dependencies(
         is_changed = false;
         let mut removed_services: Vec<(ServiceKey, ServiceKey)> = Vec::new();
         for (service_key, ExtractedService { deps, .. }) in service_infos.iter() {
-            for dep in deps.iter() {
+            for (dep, _) in deps.iter() {
                 if !service_infos.contains_key(dep) {
                     removed_services.push((service_key.clone(), dep.clone()));
                     break;
@@ -193,7 +1041,7 @@ fn split_services(service_infos: &HashMap<ServiceKey, ExtractedService>) -> Vec<
                 continue;
             }
         };
-        for dep in deps.iter() {
+        for (dep, _) in deps.iter() {
             let dep_nodeidx = match all_nodes_nodeidx_map.get(dep) {
                 Some(idx) => *idx,
                 None => {
@@ -277,10 +1125,10 @@ fn build_service_group(
         return None;
     }
 
-    let mut graph: StableDiGraph<ServiceMeta, ()> = StableDiGraph::new();
+    let mut graph: StableDiGraph<ServiceMeta, DependencyEdge> = StableDiGraph::new();
     let mut nodeidx_map: HashMap<ServiceKey, NodeIndex> =
         HashMap::with_capacity(extracted_services.len());
-    let mut edge_construction_data: Vec<(NodeIndex, Vec<ServiceKey>)> =
+    let mut edge_construction_data: Vec<(NodeIndex, Vec<(ServiceKey, DependencyEdge)>)> =
         Vec::with_capacity(extracted_services.len());
     for ExtractedService { meta, deps } in extracted_services {
         let key = (meta.name.clone(), meta.version.clone());
@@ -289,7 +1137,7 @@ fn build_service_group(
         edge_construction_data.push((nodeidx, deps));
     }
     for (cur_nodeidx, deps) in edge_construction_data {
-        for dep in deps {
+        for (dep, edge) in deps {
             let dep_nodeidx = match nodeidx_map.get(&dep) {
                 Some(idx) => *idx,
                 None => {
@@ -301,25 +1149,81 @@ fn build_service_group(
                     continue;
                 }
             };
-            graph.add_edge(dep_nodeidx, cur_nodeidx, ());
+            graph.add_edge(dep_nodeidx, cur_nodeidx, edge);
         }
     }
 
-    if petgraph::algo::is_cyclic_directed(&graph) {
-        let (nodes, _) = graph.into_nodes_edges_iters();
-        for node in nodes {
-            let meta = node.weight;
+    // The graph holds the edges of all profiles, which may form a cycle that no single profile
+    // has; each profile is checked on its own edges, the ones without overlays like the base.
+    let mut profiles: Vec<Option<String>> = vec![None];
+    for edge in graph.edge_weights() {
+        for profile in edge.profiles.keys() {
+            if !profiles.contains(&Some(profile.clone())) {
+                profiles.push(Some(profile.clone()));
+            }
+        }
+    }
+    profiles[1..].sort();
+    let cyclic_profile = profiles.into_iter().find(|profile| {
+        petgraph::algo::is_cyclic_directed(&active_graph(&graph, profile.as_deref()))
+    });
+    if let Some(profile) = cyclic_profile {
+        let profile = profile.as_deref();
+        let is_self_loop = |nodeidx: NodeIndex| {
+            graph
+                .edges_connecting(nodeidx, nodeidx)
+                .any(|edge| edge.weight().is_active(profile))
+        };
+        // A strongly connected component is cyclic if it has several nodes or a self-loop.
+        let cycles: Vec<Vec<NodeIndex>> =
+            petgraph::algo::tarjan_scc(&active_graph(&graph, profile))
+                .into_iter()
+                .filter(|scc| scc.len() > 1 || is_self_loop(scc[0]))
+                .collect();
+        let profile_desc = profile
+            .map(|profile| format!(" in profile {profile}"))
+            .unwrap_or_default();
+        let cycle_descs: Vec<String> = cycles
+            .iter()
+            .map(|scc| {
+                let members: Vec<String> = scc
+                    .iter()
+                    .filter_map(|nodeidx| graph.node_weight(*nodeidx))
+                    .map(|meta| format!("{}:v{}", meta.name, meta.version))
+                    .collect();
+                format!("cycle among [{}]{profile_desc}", members.join(", "))
+            })
+            .collect();
+        let mut node_cycle_map: HashMap<NodeIndex, usize> = HashMap::new();
+        for (cycle_idx, scc) in cycles.iter().enumerate() {
+            for nodeidx in scc {
+                node_cycle_map.insert(*nodeidx, cycle_idx);
+            }
+        }
+        let nodeidxs: Vec<NodeIndex> = graph.node_indices().collect();
+        for nodeidx in nodeidxs {
+            let meta = match graph.remove_node(nodeidx) {
+                Some(meta) => meta,
+                None => continue,
+            };
             let key = (meta.name.clone(), meta.version.clone());
+            let reason = match node_cycle_map.get(&nodeidx) {
+                Some(cycle_idx) => format!(
+                    "Service group dependency is cyclic: {}",
+                    cycle_descs[*cycle_idx]
+                ),
+                None => format!(
+                    "Service group dependency is cyclic: {}",
+                    cycle_descs.join("; ")
+                ),
+            };
             warn!(
                 "name" = %meta.name,
                 "version" = %meta.version,
+                "reason" = reason,
                 "Service group dependency is cyclic"
             );
-            dlq.push(DeadLetterQueueItem {
-                key,
-                reason: "Service group dependency is cyclic".into(),
-                meta,
-            });
+            dlq.push(DeadLetterQueueItem { key, reason, meta });
         }
         None
     } else {
@@ -327,12 +1231,58 @@ fn build_service_group(
     }
 }
 
+/// Replaces every template config (name ending in `@`) by one config per instance.
+///
+/// Instances share the template's program, args, env, workspace and dependencies; templates
+/// without instances produce no service.
+fn expand_templates(service_configs: Vec<ServiceConfig>) -> Vec<ServiceConfig> {
+    let mut ret = Vec::with_capacity(service_configs.len());
+    for mut config in service_configs {
+        if !config.name.ends_with('@') {
+            if !config.instances.is_empty() {
+                warn!(
+                    "name" = config.name,
+                    "version" = config.version,
+                    "Instances ignored on a service that is not a template"
+                );
+            }
+            ret.push(config);
+            continue;
+        }
+        let instances = std::mem::take(&mut config.instances);
+        if instances.is_empty() {
+            info!(
+                "name" = config.name,
+                "version" = config.version,
+                "Template has no instances"
+            );
+        }
+        for instance in instances {
+            if instance.is_empty() {
+                warn!(
+                    "name" = config.name,
+                    "version" = config.version,
+                    "Empty instance name skipped"
+                );
+                continue;
+            }
+            ret.push(ServiceConfig {
+                name: format!("{}{}", config.name, instance),
+                ..config.clone()
+            });
+        }
+    }
+    ret
+}
+
 fn build_groups_from_configs(
     service_configs: Vec<ServiceConfig>,
+    options: &ServiceManagerOptions,
     dlq: &mut Vec<DeadLetterQueueItem>,
 ) -> Vec<ServiceGroup> {
     let mut ret = Vec::new();
-    let mut service_infos = validate_service_name_unique(service_configs, dlq);
+    let service_configs = expand_templates(service_configs);
+    let mut service_infos = validate_service_name_unique(service_configs, options, dlq);
     validate_service_dependencies(&mut service_infos, dlq);
     let service_key_groups = split_services(&service_infos);
     for service_keys in service_key_groups {
@@ -343,6 +1293,94 @@ fn build_groups_from_configs(
     ret
 }
 
+/// A problem found by [validate_configs] for one service.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationIssue {
+    pub name: String,
+    pub version: String,
+    pub reason: String,
+}
+
+/// Result of [validate_configs]: every service that would end up in the dead-letter queue.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no issue was found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the issues reported for the service (name, version); for a template name
+    /// (ending in `@`), the issues of all its instances.
+    pub fn issues_for<'a>(
+        &'a self,
+        name: &'a str,
+        version: &'a str,
+    ) -> impl Iterator<Item = &'a ValidationIssue> + 'a {
+        self.issues.iter().filter(move |issue| {
+            let name_matches = issue.name == name
+                || (name.ends_with('@')
+                    && split_instance_name(&issue.name)
+                        .is_some_and(|(template, _)| template == name));
+            name_matches && issue.version == version
+        })
+    }
+}
+
+/// Validates service configs the same way [ServiceManager::from_configs] does, without starting anything.
+///
+/// Reports invalid names, duplicate (name, version) pairs, unresolved variables, invalid ports,
+/// replicas, limits, hooks, watches, schedules and identities, missing dependencies (including
+/// services whose dependencies were rejected themselves) and dependency cycles.
+///
+/// Not a pure check: `user` and `group` are resolved through the system user database (NSS),
+/// which may block on a directory service, and the result depends on the users and groups of
+/// the machine at call time. Ports are not probed; whether a fixed port is free is only
+/// checked when the service is spawned.
+///
+/// # Arguments
+///
+/// * `service_configs` - Service configs to validate.
+///
+/// # Returns
+///
+/// A [ValidationReport] listing every rejected service with its reason.
+pub fn validate_configs(service_configs: &[ServiceConfig]) -> ValidationReport {
+    validate_configs_with_options(service_configs, &ServiceManagerOptions::default())
+}
+
+/// Validates service configs the same way [ServiceManager::from_configs_with_options] does.
+///
+/// Resolves users and groups like [validate_configs].
+///
+/// # Arguments
+///
+/// * `service_configs` - Service configs to validate.
+/// * `options` - Manager options (e.g. global variables) the configs would be built with.
+///
+/// # Returns
+///
+/// A [ValidationReport] listing every rejected service with its reason.
+pub fn validate_configs_with_options(
+    service_configs: &[ServiceConfig],
+    options: &ServiceManagerOptions,
+) -> ValidationReport {
+    let mut dlq = Vec::new();
+    build_groups_from_configs(service_configs.to_vec(), options, &mut dlq);
+    let issues = dlq
+        .into_iter()
+        .map(|item| ValidationIssue {
+            name: item.key.0.to_string(),
+            version: item.key.1.to_string(),
+            reason: item.reason,
+        })
+        .collect();
+    ValidationReport { issues }
+}
+
 fn build_service_groupidx_map(groups: &[ServiceGroup]) -> HashMap<ServiceKey, usize> {
     let mut ret = HashMap::new();
     for (groupidx, group) in groups.iter().enumerate() {
@@ -353,39 +1391,310 @@ fn build_service_groupidx_map(groups: &[ServiceGroup]) -> HashMap<ServiceKey, us
     ret
 }
 
-fn build_service_state_map(groups: &[ServiceGroup]) -> DashMap<ServiceKey, ServiceState> {
-    let ret = DashMap::new();
+/// Returns the keys of all services and of their replicas.
+fn all_service_and_replica_keys(groups: &[ServiceGroup]) -> Vec<ServiceKey> {
+    let mut ret = Vec::new();
     for group in groups {
-        for service_key in group.nodeidx_map.keys() {
-            ret.insert(service_key.clone(), ServiceState::Pending);
+        for meta in group.graph.node_weights() {
+            let key: ServiceKey = (meta.name.clone(), meta.version.clone());
+            for index in 0..meta.replicas.unwrap_or(0) {
+                ret.push(replica_key(&key, index));
+            }
+            ret.push(key);
         }
     }
     ret
 }
 
-#[derive(Debug)]
-enum ServiceManagerEvent {
-    ServiceStarted {
-        service_key: ServiceKey,
-    },
-    ServiceStopped {
-        service_key: ServiceKey,
-    },
-    ServiceCrashed {
-        service_key: ServiceKey,
-        reason: String,
-    },
+fn build_service_state_map(groups: &[ServiceGroup]) -> DashMap<ServiceKey, ServiceState> {
+    let ret = DashMap::new();
+    for service_key in all_service_and_replica_keys(groups) {
+        ret.insert(service_key, ServiceState::Pending);
+    }
+    ret
+}
+
+fn build_service_runtime_map(groups: &[ServiceGroup]) -> DashMap<ServiceKey, ServiceRuntimeInfo> {
+    let ret = DashMap::new();
+    for service_key in all_service_and_replica_keys(groups) {
+        ret.insert(service_key, ServiceRuntimeInfo::default());
+    }
+    ret
+}
+
+/// Replica settings of a replicated service; `count` changes when the service is scaled.
+#[derive(Debug, Clone, Copy)]
+struct ReplicaSet {
+    count: u32,
+    quorum: Option<u32>,
+}
+
+fn build_service_replica_map(groups: &[ServiceGroup]) -> DashMap<ServiceKey, ReplicaSet> {
+    let ret = DashMap::new();
+    for group in groups {
+        for meta in group.graph.node_weights() {
+            if let Some(count) = meta.replicas {
+                ret.insert(
+                    (meta.name.clone(), meta.version.clone()),
+                    ReplicaSet {
+                        count,
+                        quorum: meta.quorum,
+                    },
+                );
+            }
+        }
+    }
+    ret
+}
+
+#[derive(Debug)]
+enum ServiceManagerEvent {
+    ServiceStarted {
+        service_key: ServiceKey,
+        pid: Option<u32>,
+    },
+    ServiceStopped {
+        service_key: ServiceKey,
+        exit_code: Option<i32>,
+        signal: Option<i32>,
+    },
+    ServiceCrashed {
+        service_key: ServiceKey,
+        reason: String,
+        exit_code: Option<i32>,
+        signal: Option<i32>,
+    },
+    /// A scheduled service exited successfully on its own.
+    ServiceFinished {
+        service_key: ServiceKey,
+        exit_code: Option<i32>,
+        signal: Option<i32>,
+    },
+}
+
+/// How a detached service run is recorded and left behind.
+struct DetachContext {
+    /// Directory for the record.
+    state_dir: PathBuf,
+    /// Where the service writes its output, see [ServiceManager::detached_log_options].
+    log: ServiceLogOptions,
+    /// Cancelled when the service manager is dropped; the service then keeps running.
+    detach_token: CancellationToken,
+    /// Ports allocated for the run, kept in the record.
+    ports: BTreeMap<String, u16>,
+}
+
+/// Interval at which the log file of a running detached service is checked for rotation.
+const DETACHED_LOG_ROTATION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Rotates the log file of a running detached service whenever it is due; never completes.
+async fn rotate_detached_log(rotation: Option<&mut DetachedLogRotation>, service_key: &ServiceKey) {
+    let Some(rotation) = rotation else {
+        return std::future::pending().await;
+    };
+    loop {
+        tokio::time::sleep(DETACHED_LOG_ROTATION_INTERVAL).await;
+        if let Err(e) = rotation.rotate_if_due() {
+            warn!("error" = %e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to rotate detached service log");
+        }
+    }
+}
+
+/// Completes when the service manager lets go of a detached service; never for attached ones.
+async fn detach_requested(detach: Option<&DetachContext>) {
+    match detach {
+        Some(detach) => detach.detach_token.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// How the output of an attached service run is captured.
+#[derive(Clone)]
+struct OutputCapture {
+    /// Log file settings.
+    log_options: ServiceLogOptions,
+    /// Publishes each captured line.
+    output_tx: broadcast::Sender<ServiceOutputLine>,
+}
+
+/// Max bytes of a captured output line; the rest of a longer line is captured as further lines.
+const MAX_OUTPUT_LINE: u64 = 64 * 1024;
+
+/// Reads lines from a pipe of a service until it closes, appending them to the service's log
+/// file and publishing them.
+async fn pump_output(
+    reader: impl tokio::io::AsyncRead + Unpin,
+    stream: OutputStream,
+    service_key: ServiceKey,
+    writer: Arc<std::sync::Mutex<Option<ServiceLogWriter>>>,
+    output_tx: broadcast::Sender<ServiceOutputLine>,
+) {
+    let mut reader = tokio::io::BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match (&mut reader)
+            .take(MAX_OUTPUT_LINE)
+            .read_until(b'\n', &mut buf)
+            .await
+        {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                warn!("error" = %e, "name" = &*service_key.0, "version" = &*service_key.1, "stream" = stream.as_str(), "Failed to read service output");
+                break;
+            }
+        }
+        let line = String::from_utf8_lossy(&buf)
+            .trim_end_matches(['\n', '\r'])
+            .to_string();
+        let timestamp = unix_millis_now();
+        if let Ok(mut writer) = writer.lock()
+            && let Some(log) = writer.as_mut()
+            && let Err(e) = log.write_line(stream, &line, timestamp)
+        {
+            warn!("error" = %e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to write service log, further output is not written");
+            *writer = None;
+        }
+        // No receivers is not an error: nobody is following the output live.
+        let _ = output_tx.send(ServiceOutputLine {
+            key: service_key.clone(),
+            stream,
+            line,
+            timestamp,
+        });
+    }
+}
+
+/// Sends the output of a detached service straight to its log file, since it outlives
+/// Spindle's own stdout and stderr.
+fn redirect_detached_output(
+    cmd: &mut tokio::process::Command,
+    log_path: &Path,
+) -> std::io::Result<()> {
+    if let Some(directory) = log_path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    // Opened for appending, so the file can be truncated by [DetachedLogRotation].
+    let log_file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)?;
+    cmd.stdin(std::process::Stdio::null());
+    cmd.stderr(log_file.try_clone()?);
+    cmd.stdout(log_file);
+    Ok(())
+}
+
+/// Runs the hook of `stage` if the service has one, logging its output.
+///
+/// # Returns
+///
+/// `Ok(())` if there is no hook or it succeeded, or `Err(reason)` if it failed.
+async fn run_service_hook(meta: &ServiceMeta, stage: HookStage) -> Result<(), String> {
+    let Some(hook) = meta.hooks.get(stage) else {
+        return Ok(());
+    };
+    info!(
+        "name" = &*meta.name,
+        "version" = &*meta.version,
+        "hook" = stage.as_str(),
+        "Running service hook"
+    );
+    match crate::hooks::run_hook(stage, hook, meta).await {
+        Ok(output) => {
+            info!(
+                "name" = &*meta.name,
+                "version" = &*meta.version,
+                "hook" = stage.as_str(),
+                "output" = output,
+                "Service hook finished"
+            );
+            Ok(())
+        }
+        Err(reason) => {
+            warn!(
+                "name" = &*meta.name,
+                "version" = &*meta.version,
+                "hook" = stage.as_str(),
+                "reason" = reason,
+                "Service hook failed"
+            );
+            Err(reason)
+        }
+    }
 }
 
 async fn service_task(
     meta: ServiceMeta,
     event_tx: mpsc::Sender<ServiceManagerEvent>,
     cancel_token: CancellationToken,
+    detach: Option<DetachContext>,
+    capture: Option<OutputCapture>,
 ) {
-    let mut cmd = tokio::process::Command::new(&*meta.program);
     let service_key: ServiceKey = (meta.name.clone(), meta.version.clone());
+    let pre_start = tokio::select! {
+        result = run_service_hook(&meta, HookStage::PreStart) => result,
+        _ = cancel_token.cancelled() => {
+            info!("name" = &*service_key.0, "version" = &*service_key.1, "Service task cancelled before start");
+            let event = ServiceManagerEvent::ServiceStopped {
+                service_key: service_key.clone(),
+                exit_code: None,
+                signal: None,
+            };
+            if let Err(e) = event_tx.send(event).await {
+                warn!("error" = ?e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to send ServiceStopped event");
+            }
+            return;
+        }
+    };
+    if let Err(reason) = pre_start {
+        let event = ServiceManagerEvent::ServiceCrashed {
+            service_key: service_key.clone(),
+            reason,
+            exit_code: None,
+            signal: None,
+        };
+        if let Err(e) = event_tx.send(event).await {
+            warn!("error" = ?e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to send ServiceCrashed event");
+        }
+        return;
+    }
+    let mut cmd = tokio::process::Command::new(&*meta.program);
     cmd.args(meta.args.iter().map(|s| &**s));
-    if let Some(workspace) = meta.workspace {
+    cmd.envs(meta.env.iter().map(|(k, v)| (&**k, &**v)));
+    // A process group of its own lets a stop reach grandchildren (shell wrappers, `npm run`).
+    // Detached services get a session of their own, which also makes them a group leader.
+    let mut log_rotation = detach
+        .as_ref()
+        .map(|detach| DetachedLogRotation::new(&detach.log, &service_key.0, &service_key.1));
+    if let Some(rotation) = &mut log_rotation
+        && let Err(e) = rotation.rotate_if_due()
+    {
+        warn!("error" = %e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to rotate detached service log");
+    }
+    if let Some(rotation) = &log_rotation {
+        crate::detach::new_session(&mut cmd);
+        if let Err(e) = redirect_detached_output(&mut cmd, rotation.path()) {
+            warn!("error" = %e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to open detached service log, output is discarded");
+            cmd.stdin(std::process::Stdio::null());
+            cmd.stdout(std::process::Stdio::null());
+            cmd.stderr(std::process::Stdio::null());
+        }
+    } else {
+        #[cfg(unix)]
+        cmd.process_group(0);
+        if capture.is_some() {
+            cmd.stdout(std::process::Stdio::piped());
+            cmd.stderr(std::process::Stdio::piped());
+        }
+    }
+    // The cgroup move and rlimits need the privileges the identity switch gives up.
+    let cgroup = service_cgroup(&service_key, &meta.limits);
+    let oom_kills_before = cgroup.as_ref().map(ServiceCgroup::oom_kills);
+    crate::limits::apply_limits(&mut cmd, &meta.limits, cgroup.as_ref());
+    crate::identity::apply_identity(&mut cmd, &meta.identity);
+    if let Some(workspace) = &meta.workspace {
         if workspace.exists() {
             cmd.current_dir(workspace);
         } else {
@@ -398,6 +1707,8 @@ async fn service_task(
             let event = ServiceManagerEvent::ServiceCrashed {
                 service_key: service_key.clone(),
                 reason: format!("Failed to spawn service: {e}"),
+                exit_code: None,
+                signal: None,
             };
             warn!("Failed to spawn service: {e}");
             if let Err(e) = event_tx.send(event).await {
@@ -406,25 +1717,125 @@ async fn service_task(
             return;
         }
     };
+    let pid = child.id();
     info!(
         "name" = &*service_key.0,
         "version" = &*service_key.1,
+        "pid" = pid,
         "Service task running"
     );
+    if let Some(capture) = &capture {
+        let writer = match ServiceLogWriter::open(
+            &capture.log_options,
+            &service_key.0,
+            &service_key.1,
+        ) {
+            Ok(writer) => Some(writer),
+            Err(e) => {
+                warn!("error" = %e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to open service log, output is not written");
+                None
+            }
+        };
+        let writer = Arc::new(std::sync::Mutex::new(writer));
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(pump_output(
+                stdout,
+                OutputStream::Stdout,
+                service_key.clone(),
+                writer.clone(),
+                capture.output_tx.clone(),
+            ));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(pump_output(
+                stderr,
+                OutputStream::Stderr,
+                service_key.clone(),
+                writer,
+                capture.output_tx.clone(),
+            ));
+        }
+    }
+    if let Some(detach) = &detach
+        && let Some(pid) = pid
+    {
+        match crate::detach::process_start_time(pid) {
+            Some(start_time) => {
+                let record = DetachedRecord {
+                    name: service_key.0.to_string(),
+                    version: service_key.1.to_string(),
+                    pid,
+                    start_time,
+                    started_at: unix_millis_now(),
+                    ports: detach.ports.clone(),
+                };
+                if let Err(e) = crate::detach::write_record(&detach.state_dir, &record) {
+                    warn!("error" = %e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to record detached service, it cannot be adopted later");
+                }
+            }
+            // A record without a start time could adopt whatever process reuses the PID.
+            None => {
+                warn!(
+                    "name" = &*service_key.0,
+                    "version" = &*service_key.1,
+                    "pid" = pid,
+                    "Start time of detached service unknown, it cannot be adopted later"
+                );
+            }
+        }
+    }
+    // Removed before the stop is reported, so it cannot race with the record of a relaunch.
+    let forget_record = || {
+        if let Some(detach) = &detach {
+            crate::detach::remove_record(&detach.state_dir, &service_key.0, &service_key.1);
+        }
+    };
     let event = ServiceManagerEvent::ServiceStarted {
         service_key: service_key.clone(),
+        pid,
     };
     if let Err(e) = event_tx.send(event).await {
         warn!("error" = ?e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to send ServiceStarted event");
     }
+    // Cancels a post_start hook still running when the service is stopped, exits or is left
+    // behind on shutdown.
+    let hook_token = cancel_token.child_token();
+    let _hook_guard = hook_token.clone().drop_guard();
+    if meta.hooks.post_start.is_some() {
+        let meta = meta.clone();
+        // Runs alongside the service; a failure is only logged.
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = hook_token.cancelled() => {
+                    info!("name" = &*meta.name, "version" = &*meta.version, "hook" = HookStage::PostStart.as_str(), "Service hook cancelled");
+                }
+                _ = run_service_hook(&meta, HookStage::PostStart) => {}
+            }
+        });
+    }
     tokio::select! {
+        _ = detach_requested(detach.as_ref()) => {
+            info!("name" = &*service_key.0, "version" = &*service_key.1, "pid" = pid, "Service manager dropped, detached service left running");
+            return;
+        }
+        _ = rotate_detached_log(log_rotation.as_mut(), &service_key) => {}
         _ = cancel_token.cancelled() => {
             info!("name" = &*service_key.0, "version" = &*service_key.1, "Service task cancelled");
-            match child.kill().await {
+            let _ = run_service_hook(&meta, HookStage::PreStop).await;
+            let killed = kill_process_tree(&mut child, cgroup.as_ref()).await;
+            forget_record();
+            let _ = run_service_hook(&meta, HookStage::PostStop).await;
+            match killed {
                 Ok(_) => {
                     info!("name" = &*service_key.0, "version" = &*service_key.1, "Service task killed");
+                    let (exit_code, signal) = match child.try_wait() {
+                        Ok(Some(exit_status)) => exit_status_parts(&exit_status),
+                        _ => (None, None),
+                    };
                     let event = ServiceManagerEvent::ServiceStopped {
                         service_key: service_key.clone(),
+                        exit_code,
+                        signal,
                     };
                     if let Err(e) = event_tx.send(event).await {
                         warn!("error" = ?e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to send ServiceStopped event");
@@ -435,6 +1846,8 @@ async fn service_task(
                     let event = ServiceManagerEvent::ServiceCrashed {
                         service_key: service_key.clone(),
                         reason: format!("Service task killed with error: {e}"),
+                        exit_code: None,
+                        signal: None,
                     };
                     if let Err(e) = event_tx.send(event).await {
                         warn!("error" = ?e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to send ServiceCrashed event");
@@ -443,17 +1856,61 @@ async fn service_task(
             }
         }
         exit_status_rs = child.wait() => {
-            warn!("name" = &*service_key.0, "version" = &*service_key.1, "Service task exited unexpectedly");
-            let reason = match exit_status_rs {
-                Ok(exit_status) => format!("Service task exited with status: {exit_status}"),
-                Err(e) => format!("Service task exited with error: {e}"),
+            // A scheduled run that succeeds is done, not crashed.
+            let finished = meta.schedule.is_some()
+                && exit_status_rs
+                    .as_ref()
+                    .is_ok_and(|exit_status| exit_status.success());
+            if finished {
+                info!("name" = &*service_key.0, "version" = &*service_key.1, "Scheduled run finished");
+            } else {
+                warn!("name" = &*service_key.0, "version" = &*service_key.1, "Service task exited unexpectedly");
+            }
+            let (reason, (exit_code, signal)) = match exit_status_rs {
+                Ok(exit_status) => {
+                    let (exit_code, signal) = exit_status_parts(&exit_status);
+                    let oom_killed = cgroup
+                        .as_ref()
+                        .zip(oom_kills_before)
+                        .is_some_and(|(cgroup, before)| cgroup.oom_kills() > before);
+                    let reason = if oom_killed {
+                        match meta.limits.memory_max {
+                            Some(memory_max) => format!(
+                                "Out of memory: killed by the kernel at memory.max = {memory_max} bytes"
+                            ),
+                            None => "Out of memory: killed by the kernel OOM killer".to_string(),
+                        }
+                    } else if let Some(cpu_seconds) = meta.limits.cpu_seconds
+                        && signal == Some(SIGXCPU)
+                    {
+                        format!("CPU time limit exceeded: {cpu_seconds}s")
+                    } else {
+                        format!("Service task exited with status: {exit_status}")
+                    };
+                    (reason, (exit_code, signal))
+                }
+                Err(e) => (format!("Service task exited with error: {e}"), (None, None)),
             };
-            let event = ServiceManagerEvent::ServiceCrashed {
-                service_key: service_key.clone(),
-                reason,
+            // Leftover descendants would keep holding ports and files across a restart.
+            kill_leftover_processes(pid, cgroup.as_ref()).await;
+            forget_record();
+            let _ = run_service_hook(&meta, HookStage::PostStop).await;
+            let event = if finished {
+                ServiceManagerEvent::ServiceFinished {
+                    service_key: service_key.clone(),
+                    exit_code,
+                    signal,
+                }
+            } else {
+                ServiceManagerEvent::ServiceCrashed {
+                    service_key: service_key.clone(),
+                    reason,
+                    exit_code,
+                    signal,
+                }
             };
             if let Err(e) = event_tx.send(event).await {
-                warn!("error" = ?e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to send ServiceCrashed event");
+                warn!("error" = ?e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to send service exit event");
             }
         }
     }
@@ -464,15 +1921,326 @@ async fn service_task(
     );
 }
 
+/// Triggers the runs of a scheduled service at the ticks of `schedule` and keeps its next run
+/// time up to date, until cancelled or the manager is dropped.
+async fn schedule_task(
+    manager: Weak<ServiceManager>,
+    service_key: ServiceKey,
+    schedule: CronSchedule,
+    cancel_token: CancellationToken,
+) {
+    let mut after = chrono::Local::now();
+    loop {
+        let Some(next_run) = schedule.next_after(&after) else {
+            warn!(
+                "name" = &*service_key.0,
+                "version" = &*service_key.1,
+                "schedule" = schedule.expr(),
+                "Schedule never fires again"
+            );
+            if let Some(manager) = manager.upgrade() {
+                manager.next_run_map.remove(&service_key);
+            }
+            return;
+        };
+        match manager.upgrade() {
+            Some(manager) => {
+                manager
+                    .next_run_map
+                    .insert(service_key.clone(), next_run.timestamp_millis() as u64);
+            }
+            None => return,
+        }
+        let delay = (next_run - chrono::Local::now())
+            .to_std()
+            .unwrap_or_default();
+        tokio::select! {
+            _ = cancel_token.cancelled() => return,
+            _ = tokio::time::sleep(delay) => {}
+        }
+        let Some(manager) = manager.upgrade() else {
+            return;
+        };
+        info!(
+            "name" = &*service_key.0,
+            "version" = &*service_key.1,
+            "Schedule tick"
+        );
+        manager.run_scheduled(&service_key).await;
+        after = next_run.max(chrono::Local::now());
+    }
+}
+
+/// Time a restart may take to stop the service, and to get it running before its dependents are
+/// relaunched.
+const RESTART_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Restarts a watched service whenever its watched files change, once the changes settle,
+/// until cancelled or the manager is dropped.
+async fn watch_task(
+    manager: Weak<ServiceManager>,
+    service_key: ServiceKey,
+    spec: WatchSpec,
+    cancel_token: CancellationToken,
+) {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |event| {
+        let _ = event_tx.send(event);
+    });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!("name" = &*service_key.0, "version" = &*service_key.1, "error" = ?e, "Failed to create file watcher");
+            return;
+        }
+    };
+    for (root, mode) in spec.roots() {
+        if let Err(e) = notify::Watcher::watch(&mut watcher, root, *mode) {
+            warn!("name" = &*service_key.0, "version" = &*service_key.1, "path" = ?root, "error" = ?e, "Failed to watch path");
+        }
+    }
+    loop {
+        // Waits for a relevant change, then until no relevant change came for the debounce time.
+        let mut changed = None;
+        let mut deadline = None;
+        loop {
+            let event = match deadline {
+                None => tokio::select! {
+                    _ = cancel_token.cancelled() => return,
+                    event = event_rx.recv() => event,
+                },
+                Some(deadline) => tokio::select! {
+                    _ = cancel_token.cancelled() => return,
+                    event = tokio::time::timeout_at(deadline, event_rx.recv()) => match event {
+                        Ok(event) => event,
+                        Err(_) => break,
+                    },
+                },
+            };
+            match event {
+                Some(Ok(event)) => {
+                    if let Some(path) = spec.relevant_path(&event) {
+                        changed = Some(path.to_path_buf());
+                        deadline = Some(tokio::time::Instant::now() + spec.debounce);
+                    }
+                }
+                Some(Err(e)) => {
+                    warn!("name" = &*service_key.0, "version" = &*service_key.1, "error" = ?e, "File watcher error");
+                }
+                None => return,
+            }
+        }
+        // A binary being rebuilt may be missing for a moment; its creation is a change as well.
+        if let Some(program) = spec.program()
+            && !program.exists()
+        {
+            info!("name" = &*service_key.0, "version" = &*service_key.1, "program" = ?program, "Program is missing, waiting for it to be rebuilt");
+            continue;
+        }
+        let Some(manager) = manager.upgrade() else {
+            return;
+        };
+        info!("name" = &*service_key.0, "version" = &*service_key.1, "path" = ?changed, "Watched file changed");
+        if let Err(e) = manager
+            .restart_service(
+                &service_key.0,
+                &service_key.1,
+                spec.restart_dependents,
+                ServiceRunTrigger::Watch,
+            )
+            .await
+        {
+            warn!("name" = &*service_key.0, "version" = &*service_key.1, "error" = ?e, "Failed to restart service after change");
+        }
+    }
+}
+
+/// Interval at which an adopted detached service is checked for having exited.
+const ADOPTED_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watches a detached service adopted from an earlier [ServiceManager], which cannot be waited
+/// on as it is not a child of this process. Stops it on cancellation, like [service_task].
+async fn adopted_task(
+    meta: ServiceMeta,
+    record: DetachedRecord,
+    state_dir: PathBuf,
+    mut log_rotation: DetachedLogRotation,
+    event_tx: mpsc::Sender<ServiceManagerEvent>,
+    cancel_token: CancellationToken,
+    detach_token: CancellationToken,
+) {
+    let service_key: ServiceKey = (record.name.as_str().into(), record.version.as_str().into());
+    let cgroup = ServiceCgroup::existing(&record.name, &record.version);
+    let rotate_log = rotate_detached_log(Some(&mut log_rotation), &service_key);
+    tokio::pin!(rotate_log);
+    let event = loop {
+        tokio::select! {
+            _ = &mut rotate_log => {}
+            _ = detach_token.cancelled() => {
+                info!("name" = &*service_key.0, "version" = &*service_key.1, "pid" = record.pid, "Service manager dropped, detached service left running");
+                return;
+            }
+            _ = cancel_token.cancelled() => {
+                info!("name" = &*service_key.0, "version" = &*service_key.1, "Adopted service task cancelled");
+                let _ = run_service_hook(&meta, HookStage::PreStop).await;
+                kill_leftover_processes(Some(record.pid), cgroup.as_ref()).await;
+                let deadline = tokio::time::Instant::now() + CGROUP_DRAIN_TIMEOUT;
+                while crate::detach::is_same_process(record.pid, record.start_time)
+                    && tokio::time::Instant::now() < deadline
+                {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                crate::detach::remove_record(&state_dir, &record.name, &record.version);
+                let _ = run_service_hook(&meta, HookStage::PostStop).await;
+                break ServiceManagerEvent::ServiceStopped {
+                    service_key: service_key.clone(),
+                    exit_code: None,
+                    signal: None,
+                };
+            }
+            _ = tokio::time::sleep(ADOPTED_POLL_INTERVAL) => {
+                if crate::detach::is_same_process(record.pid, record.start_time) {
+                    continue;
+                }
+                warn!("name" = &*service_key.0, "version" = &*service_key.1, "pid" = record.pid, "Adopted service exited");
+                kill_leftover_processes(Some(record.pid), cgroup.as_ref()).await;
+                crate::detach::remove_record(&state_dir, &record.name, &record.version);
+                let _ = run_service_hook(&meta, HookStage::PostStop).await;
+                break ServiceManagerEvent::ServiceCrashed {
+                    service_key: service_key.clone(),
+                    reason: "Detached service exited; its exit status is unknown after adoption"
+                        .to_string(),
+                    exit_code: None,
+                    signal: None,
+                };
+            }
+        }
+    };
+    if let Err(e) = event_tx.send(event).await {
+        warn!("error" = ?e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to send adopted service event");
+    }
+}
+
+/// Creates the cgroup of a service for its limits and to track its descendants; `None` without
+/// delegation.
+fn service_cgroup(service_key: &ServiceKey, limits: &ResourceLimits) -> Option<ServiceCgroup> {
+    match ServiceCgroup::create(&service_key.0, &service_key.1, limits) {
+        Ok(Some(cgroup)) => Some(cgroup),
+        Ok(None) => {
+            if limits.has_cgroup_limits() {
+                warn!(
+                    "name" = &*service_key.0,
+                    "version" = &*service_key.1,
+                    "cgroup v2 delegation not available, cgroup limits skipped"
+                );
+            }
+            None
+        }
+        Err(e) => {
+            warn!("error" = %e, "name" = &*service_key.0, "version" = &*service_key.1, "Failed to set up cgroup, cgroup limits skipped");
+            None
+        }
+    }
+}
+
+/// Max duration to wait for the descendants in a service cgroup to exit after `SIGKILL`.
+const CGROUP_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sends `SIGKILL` to the process group led by `pid`; a group that is already gone is ignored.
+#[cfg(unix)]
+pub(crate) fn kill_process_group(pid: u32) -> std::io::Result<()> {
+    if unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) } == 0 {
+        return Ok(());
+    }
+    let e = std::io::Error::last_os_error();
+    match e.raw_os_error() {
+        Some(libc::ESRCH) => Ok(()),
+        _ => Err(e),
+    }
+}
+
+/// Kills a service process with all its descendants and waits for it to exit.
+///
+/// The process group is signalled; with a cgroup, processes that left the group (e.g. via
+/// `setsid`) are killed too and the stop only completes once the cgroup is empty.
+///
+/// # Arguments
+///
+/// * `child` - Service process, the leader of its process group.
+/// * `cgroup` - Cgroup of the service, if any.
+///
+/// # Returns
+///
+/// `Ok(())` once the service process has exited, or an error if it could not be killed.
+async fn kill_process_tree(
+    child: &mut tokio::process::Child,
+    cgroup: Option<&ServiceCgroup>,
+) -> std::io::Result<()> {
+    if let Some(cgroup) = cgroup
+        && let Err(e) = cgroup.kill()
+    {
+        warn!("error" = %e, "Failed to kill service cgroup");
+    }
+    #[cfg(unix)]
+    let killed = match child.id() {
+        Some(pid) => kill_process_group(pid),
+        None => Ok(()),
+    };
+    #[cfg(not(unix))]
+    let killed = child.start_kill();
+    if let Err(e) = killed {
+        warn!("error" = %e, "Failed to kill process group, killing the service process only");
+        child.start_kill()?;
+    }
+    child.wait().await?;
+    if let Some(cgroup) = cgroup
+        && !cgroup.wait_empty(CGROUP_DRAIN_TIMEOUT).await
+    {
+        warn!("timeout" = ?CGROUP_DRAIN_TIMEOUT, "Processes left in service cgroup after kill");
+    }
+    Ok(())
+}
+
+/// Kills what is left of a service's process tree after its main process exited.
+async fn kill_leftover_processes(pid: Option<u32>, cgroup: Option<&ServiceCgroup>) {
+    #[cfg(unix)]
+    if let Some(pid) = pid
+        && let Err(e) = kill_process_group(pid)
+    {
+        warn!("error" = %e, "pid" = pid, "Failed to kill leftover process group");
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+    if let Some(cgroup) = cgroup
+        && cgroup.is_populated()
+    {
+        if let Err(e) = cgroup.kill() {
+            warn!("error" = %e, "Failed to kill leftover processes in service cgroup");
+        }
+        cgroup.wait_empty(CGROUP_DRAIN_TIMEOUT).await;
+    }
+}
+
 /// Manages service groups, lifecycle (launch/stop), and state; built from a list of [ServiceConfig].
 pub struct ServiceManager {
     service_groups: Vec<ServiceGroup>,
     service_groupidx_map: HashMap<ServiceKey, usize>,
     service_state_map: DashMap<ServiceKey, ServiceState>,
+    service_runtime_map: DashMap<ServiceKey, ServiceRuntimeInfo>,
+    service_replica_map: DashMap<ServiceKey, ReplicaSet>,
+    group_profile_map: DashMap<usize, Arc<str>>,
     dlq: Vec<DeadLetterQueueItem>,
     service_canceltoken_map: DashMap<ServiceKey, CancellationToken>,
+    service_task_map: DashMap<ServiceKey, JoinHandle<()>>,
+    next_run_map: DashMap<ServiceKey, u64>,
+    queued_runs: DashSet<ServiceKey>,
     cancel_token: CancellationToken,
+    shutting_down: AtomicBool,
     event_tx: mpsc::Sender<ServiceManagerEvent>,
+    run_record_tx: Option<mpsc::Sender<ServiceRunRecord>>,
+    output_tx: broadcast::Sender<ServiceOutputLine>,
+    service_logs: Option<ServiceLogOptions>,
+    state_dir: Option<PathBuf>,
 }
 
 impl ServiceManager {
@@ -486,25 +2254,65 @@ impl ServiceManager {
     ///
     /// An [Arc] to the new [ServiceManager].
     pub fn from_configs(service_configs: Vec<ServiceConfig>) -> Arc<Self> {
+        Self::from_configs_with_options(service_configs, ServiceManagerOptions::default())
+    }
+
+    /// Builds a new [ServiceManager] from the given configs and [ServiceManagerOptions].
+    ///
+    /// # Arguments
+    ///
+    /// * `service_configs` - List of service configs to load.
+    /// * `options` - Settings applied to all services (e.g. global variables).
+    ///
+    /// # Returns
+    ///
+    /// An [Arc] to the new [ServiceManager].
+    pub fn from_configs_with_options(
+        service_configs: Vec<ServiceConfig>,
+        options: ServiceManagerOptions,
+    ) -> Arc<Self> {
         let mut dlq = Vec::new();
-        let groups = build_groups_from_configs(service_configs, &mut dlq);
+        let groups = build_groups_from_configs(service_configs, &options, &mut dlq);
         let service_groupidx_map = build_service_groupidx_map(&groups);
         let service_state_map = build_service_state_map(&groups);
+        let service_runtime_map = build_service_runtime_map(&groups);
+        let service_replica_map = build_service_replica_map(&groups);
+        let group_profile_map = DashMap::new();
+        if let Some(profile) = &options.profile {
+            for groupidx in 0..groups.len() {
+                group_profile_map.insert(groupidx, Arc::from(profile.as_str()));
+            }
+        }
         let (event_tx, event_rx) = mpsc::channel(16);
+        let (output_tx, _) = broadcast::channel(1024);
         let manager = Self {
             service_groups: groups,
             service_groupidx_map,
             service_state_map,
+            service_runtime_map,
+            service_replica_map,
+            group_profile_map,
             dlq,
             service_canceltoken_map: DashMap::new(),
+            service_task_map: DashMap::new(),
+            next_run_map: DashMap::new(),
+            queued_runs: DashSet::new(),
             cancel_token: CancellationToken::new(),
+            shutting_down: AtomicBool::new(false),
             event_tx,
+            run_record_tx: options.run_records,
+            output_tx,
+            service_logs: options.service_logs,
+            state_dir: options.state_dir,
         };
         let manager_arc = Arc::new(manager);
         tokio::spawn(handle_service_manager_event(
             event_rx,
             Arc::downgrade(&manager_arc),
         ));
+        manager_arc.reconcile_detached();
+        manager_arc.start_schedulers();
+        manager_arc.start_watchers();
         manager_arc
     }
 
@@ -535,16 +2343,22 @@ impl ServiceManager {
         self.service_state_map.insert(key, state);
     }
 
-    /// Returns the dead-letter queue: services that could not be started or were removed.
+    /// Returns the [ServiceRuntimeInfo] for the service (name, version).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Service name.
+    /// * `version` - Service version.
     ///
     /// # Returns
     ///
-    /// Slice of [DeadLetterQueueItem].
-    pub fn dead_letter_queue(&self) -> &[DeadLetterQueueItem] {
-        &self.dlq
+    /// `Some(info)` if the service is known, else `None`.
+    pub fn service_runtime_info(&self, name: &str, version: &str) -> Option<ServiceRuntimeInfo> {
+        let key: ServiceKey = (name.into(), version.into());
+        self.service_runtime_map.get(&key).map(|info| info.clone())
     }
 
-    /// Returns [ServiceMeta] for the service (name, version) if it exists.
+    /// Returns when the scheduled service (name, version) runs next.
     ///
     /// # Arguments
     ///
@@ -553,12 +2367,48 @@ impl ServiceManager {
     ///
     /// # Returns
     ///
-    /// `Some(meta)` if the service is in a group, else `None`.
-    pub fn service_meta(&self, name: &str, version: &str) -> Option<ServiceMeta> {
+    /// `Some(millis)` since the Unix epoch, or `None` if the service is not scheduled or its
+    /// schedule never fires again.
+    pub fn next_scheduled_run(&self, name: &str, version: &str) -> Option<u64> {
         let key: ServiceKey = (name.into(), version.into());
-        let groupidx = match self.service_groupidx_map.get(&key) {
-            Some(groupidx) => *groupidx,
-            None => return None,
+        self.next_run_map.get(&key).map(|next_run| *next_run)
+    }
+
+    /// Subscribes to the lines of output captured from services, see
+    /// [ServiceManagerOptions::service_logs].
+    ///
+    /// # Returns
+    ///
+    /// A broadcast receiver; lines published before subscribing are not replayed, but are in
+    /// the log files.
+    pub fn subscribe_output(&self) -> broadcast::Receiver<ServiceOutputLine> {
+        self.output_tx.subscribe()
+    }
+
+    /// Returns the dead-letter queue: services that could not be started or were removed.
+    ///
+    /// # Returns
+    ///
+    /// Slice of [DeadLetterQueueItem].
+    pub fn dead_letter_queue(&self) -> &[DeadLetterQueueItem] {
+        &self.dlq
+    }
+
+    /// Returns [ServiceMeta] for the service (name, version) if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    ///
+    /// # Returns
+    ///
+    /// `Some(meta)` if the service is in a group, else `None`.
+    pub fn service_meta(&self, name: &str, version: &str) -> Option<ServiceMeta> {
+        let key: ServiceKey = (name.into(), version.into());
+        let groupidx = match self.service_groupidx_map.get(&key) {
+            Some(groupidx) => *groupidx,
+            None => return None,
         };
         let group = &self.service_groups[groupidx];
         let nodeidx = match group.nodeidx_map.get(&key) {
@@ -571,6 +2421,124 @@ impl ServiceManager {
         }
     }
 
+    fn group_profile_arc(&self, groupidx: usize) -> Option<Arc<str>> {
+        self.group_profile_map
+            .get(&groupidx)
+            .map(|profile| profile.clone())
+    }
+
+    /// Returns `meta` resolved for the active profile of its group.
+    fn effective_meta(&self, meta: &ServiceMeta) -> ServiceMeta {
+        let key: ServiceKey = (meta.name.clone(), meta.version.clone());
+        let profile = self
+            .service_groupidx_map
+            .get(&key)
+            .and_then(|groupidx| self.group_profile_arc(*groupidx));
+        profile_meta(meta, profile.as_deref())
+    }
+
+    /// Returns the [ServiceMeta] of the service (name, version) with the overlay of `profile` applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    /// * `profile` - Profile to resolve; `None` for the base config.
+    ///
+    /// # Returns
+    ///
+    /// `Some(meta)` if the service is in a group, else `None`.
+    pub fn service_meta_for_profile(
+        &self,
+        name: &str,
+        version: &str,
+        profile: Option<&str>,
+    ) -> Option<ServiceMeta> {
+        self.service_meta(name, version)
+            .map(|meta| profile_meta(&meta, profile))
+    }
+
+    /// Returns the names of all profiles defined by any service, sorted.
+    ///
+    /// # Returns
+    ///
+    /// Vector of profile names.
+    pub fn profiles(&self) -> Vec<String> {
+        let mut ret: Vec<String> = self
+            .service_groups
+            .iter()
+            .flat_map(|group| group.graph.node_weights())
+            .flat_map(|meta| meta.profiles.keys().cloned())
+            .collect();
+        ret.sort();
+        ret.dedup();
+        ret
+    }
+
+    /// Returns the active profile of a group.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_idx` - Index of the group.
+    ///
+    /// # Returns
+    ///
+    /// `Some(profile)` if the group runs with a profile, `None` for the base config or an invalid index.
+    pub fn group_profile(&self, group_idx: usize) -> Option<String> {
+        self.group_profile_arc(group_idx)
+            .map(|profile| profile.to_string())
+    }
+
+    /// Selects the profile a group runs with from its next launch on.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_idx` - Index of the group.
+    /// * `profile` - Profile to select; `None` for the base config.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success; `Err` if the index is invalid, or if a service of the group is still
+    /// running (or starting/stopping) with another profile.
+    pub fn set_group_profile(&self, group_idx: usize, profile: Option<&str>) -> anyhow::Result<()> {
+        let group = self
+            .service_groups
+            .get(group_idx)
+            .ok_or_else(|| anyhow::anyhow!("Group index out of bounds"))?;
+        if self.group_profile_arc(group_idx).as_deref() == profile {
+            return Ok(());
+        }
+        let is_up = group.nodeidx_map.keys().any(|key| {
+            self.service_state_map.get(key).is_some_and(|state| {
+                matches!(
+                    *state,
+                    ServiceState::Running | ServiceState::Starting | ServiceState::Stopping
+                )
+            })
+        });
+        if is_up {
+            anyhow::bail!(
+                "Group {} is running with profile {:?}; stop it before switching profiles",
+                group_idx,
+                self.group_profile(group_idx)
+            );
+        }
+        match profile {
+            Some(profile) => {
+                self.group_profile_map.insert(group_idx, profile.into());
+            }
+            None => {
+                self.group_profile_map.remove(&group_idx);
+            }
+        }
+        info!(
+            "group_idx" = group_idx,
+            "profile" = profile,
+            "Group profile selected"
+        );
+        Ok(())
+    }
+
     fn deps_running(&self, key: &ServiceKey) -> bool {
         let groupidx = match self.service_groupidx_map.get(key) {
             Some(groupidx) => *groupidx,
@@ -595,9 +2563,9 @@ impl ServiceManager {
                 return false;
             }
         };
-        for dep_nodeidx in group
-            .graph
-            .neighbors_directed(cur_nodeidx, petgraph::Incoming)
+        let profile = self.group_profile_arc(groupidx);
+        for dep_nodeidx in
+            group.active_neighbors(cur_nodeidx, petgraph::Incoming, profile.as_deref())
         {
             let dep_meta = match group.graph.node_weight(dep_nodeidx) {
                 Some(meta) => meta,
@@ -636,7 +2604,29 @@ impl ServiceManager {
     ///
     /// `Ok(())` on success or if already running / deps not running (no error); `Err` on invalid state.
     pub async fn launch_service(&self, meta: &ServiceMeta) -> anyhow::Result<()> {
+        self.launch_service_with_trigger(meta, ServiceRunTrigger::Manual)
+            .await
+    }
+
+    /// Same as [Self::launch_service], recording `trigger` as the cause of the run.
+    ///
+    /// # Arguments
+    ///
+    /// * `meta` - [ServiceMeta] of the service to launch.
+    /// * `trigger` - What caused this launch; reported in [ServiceRunRecord].
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success or if already running / deps not running (no error); `Err` on invalid state.
+    pub async fn launch_service_with_trigger(
+        &self,
+        meta: &ServiceMeta,
+        trigger: ServiceRunTrigger,
+    ) -> anyhow::Result<()> {
         let service_key: ServiceKey = (meta.name.clone(), meta.version.clone());
+        if self.shutting_down.load(Ordering::Acquire) {
+            anyhow::bail!("Service manager is shutting down");
+        }
         if !self.deps_running(&service_key) {
             warn!(
                 "name" = &*service_key.0,
@@ -645,6 +2635,11 @@ impl ServiceManager {
             );
             return Ok(());
         }
+        let meta = &self.effective_meta(meta);
+        if let Some(replica_set) = self.replica_set(&service_key) {
+            self.launch_replicas(meta, 0..replica_set.count, trigger);
+            return Ok(());
+        }
         let mut entry = match self.service_state_map.get_mut(&service_key) {
             Some(entry) => entry,
             None => {
@@ -686,19 +2681,664 @@ impl ServiceManager {
             }
         }
         drop(entry);
+        self.spawn_service_task(service_key.clone(), &service_key, meta, trigger)
+    }
+
+    /// Allocates the ports of `meta`, resets the runtime info of `service_key` and spawns its [service_task].
+    ///
+    /// `graph_key` is the service in the dependency graph (differs from `service_key` for replicas).
+    /// If a port is unavailable, the service is marked `Failed` and nothing is spawned.
+    fn spawn_service_task(
+        &self,
+        service_key: ServiceKey,
+        graph_key: &ServiceKey,
+        meta: &ServiceMeta,
+        trigger: ServiceRunTrigger,
+    ) -> anyhow::Result<()> {
+        let ports = allocate_ports(&meta.ports);
+        if let Some(mut runtime) = self.service_runtime_map.get_mut(&service_key) {
+            if runtime.triggered_by.is_some() {
+                runtime.restart_count += 1;
+            }
+            runtime.triggered_by = Some(trigger);
+            runtime.started_at = None;
+            runtime.stopped_at = None;
+            runtime.exit_code = None;
+            runtime.signal = None;
+            runtime.ports = ports.as_ref().cloned().unwrap_or_default();
+        }
+        let ports = match ports {
+            Ok(ports) => ports,
+            Err(e) => {
+                let reason = e.to_string();
+                warn!(
+                    "name" = &*service_key.0,
+                    "version" = &*service_key.1,
+                    "reason" = reason,
+                    "Service not started"
+                );
+                if let Some(mut runtime) = self.service_runtime_map.get_mut(&service_key) {
+                    runtime.stopped_at = Some(unix_millis_now());
+                    runtime.last_failure_reason = Some(reason.clone());
+                    let record = run_record(&service_key, &runtime, Some(reason.clone()));
+                    // Not async here: the record is handed to a task that waits for room.
+                    if let Some(run_record_tx) = self.run_record_tx.clone() {
+                        tokio::spawn(async move {
+                            let _ = run_record_tx.send(record).await;
+                        });
+                    }
+                }
+                self.service_state_map
+                    .insert(service_key, ServiceState::Failed(reason));
+                return Err(e);
+            }
+        };
+        let meta = self.with_runtime_values(&service_key, graph_key, meta, &ports);
+
+        let event_tx = self.event_tx.clone();
+        // A detached service is only stopped through its own token; dropping the manager
+        // cancels the root token, which leaves it running.
+        let detach = match (&self.state_dir, meta.detached) {
+            (Some(_), true) if !crate::detach::start_times_available() => {
+                warn!(
+                    "name" = &*service_key.0,
+                    "version" = &*service_key.1,
+                    "Process start times unavailable for detached services, running attached"
+                );
+                None
+            }
+            (Some(state_dir), true) => Some(DetachContext {
+                state_dir: state_dir.clone(),
+                log: self.detached_log_options(state_dir),
+                detach_token: self.cancel_token.clone(),
+                ports,
+            }),
+            (None, true) => {
+                warn!(
+                    "name" = &*service_key.0,
+                    "version" = &*service_key.1,
+                    "No state directory for detached services, running attached"
+                );
+                None
+            }
+            (_, false) => None,
+        };
+        let cancel_token = match detach {
+            Some(_) => CancellationToken::new(),
+            None => self.cancel_token.child_token(),
+        };
+        self.service_canceltoken_map
+            .insert(service_key.clone(), cancel_token.clone());
+        info!(
+            "name" = &*service_key.0,
+            "version" = &*service_key.1,
+            "Starting service"
+        );
+        let capture = match (&detach, &self.service_logs) {
+            (None, Some(log_options)) => Some(OutputCapture {
+                log_options: log_options.clone(),
+                output_tx: self.output_tx.clone(),
+            }),
+            _ => None,
+        };
+        let handle = tokio::spawn(service_task(meta, event_tx, cancel_token, detach, capture));
+        self.service_task_map.insert(service_key, handle);
+        Ok(())
+    }
+
+    /// Spawns a [schedule_task] for every scheduled service.
+    fn start_schedulers(self: &Arc<Self>) {
+        for group in &self.service_groups {
+            for meta in group.graph.node_weights() {
+                let Some(schedule) = &meta.schedule else {
+                    continue;
+                };
+                tokio::spawn(schedule_task(
+                    Arc::downgrade(self),
+                    (meta.name.clone(), meta.version.clone()),
+                    schedule.clone(),
+                    self.cancel_token.child_token(),
+                ));
+            }
+        }
+    }
+
+    /// Spawns a [watch_task] for every service with watched files.
+    fn start_watchers(self: &Arc<Self>) {
+        for group in &self.service_groups {
+            for meta in group.graph.node_weights() {
+                let Some(watch) = &meta.watch else {
+                    continue;
+                };
+                let spec = static_meta(meta)
+                    .map_err(|names| {
+                        format!("Uses values only known at spawn time: {}", names.join(", "))
+                    })
+                    .and_then(|meta| {
+                        let path_var = meta.env.get("PATH").map(|path| &**path);
+                        WatchSpec::new(watch, meta.workspace.as_deref(), &meta.program, path_var)
+                    });
+                let spec = match spec {
+                    Ok(spec) => spec,
+                    Err(e) => {
+                        warn!(
+                            "name" = &*meta.name,
+                            "version" = &*meta.version,
+                            "error" = e,
+                            "Files of service not watched"
+                        );
+                        continue;
+                    }
+                };
+                tokio::spawn(watch_task(
+                    Arc::downgrade(self),
+                    (meta.name.clone(), meta.version.clone()),
+                    spec,
+                    self.cancel_token.child_token(),
+                ));
+            }
+        }
+    }
+
+    /// Runs a scheduled service for a tick of its schedule, applying its [OverlapPolicy] if the
+    /// previous run is still going.
+    async fn run_scheduled(&self, service_key: &ServiceKey) {
+        let Some(meta) = self.service_meta(&service_key.0, &service_key.1) else {
+            return;
+        };
+        let busy = matches!(
+            self.service_state(&service_key.0, &service_key.1),
+            Some(ServiceState::Running | ServiceState::Starting | ServiceState::Stopping)
+        );
+        if busy {
+            match meta.overlap {
+                OverlapPolicy::Skip => info!(
+                    "name" = &*service_key.0,
+                    "version" = &*service_key.1,
+                    "Previous run still going, scheduled run skipped"
+                ),
+                OverlapPolicy::Queue => {
+                    if self.queued_runs.insert(service_key.clone()) {
+                        info!(
+                            "name" = &*service_key.0,
+                            "version" = &*service_key.1,
+                            "Previous run still going, scheduled run queued"
+                        );
+                    } else {
+                        info!(
+                            "name" = &*service_key.0,
+                            "version" = &*service_key.1,
+                            "A run is queued already, scheduled run skipped"
+                        );
+                    }
+                }
+            }
+            return;
+        }
+        if !self.deps_running(service_key) {
+            warn!(
+                "name" = &*service_key.0,
+                "version" = &*service_key.1,
+                "Dependencies not running, scheduled run skipped"
+            );
+            return;
+        }
+        if let Err(e) = self
+            .launch_service_with_trigger(&meta, ServiceRunTrigger::Schedule)
+            .await
+        {
+            warn!("name" = &*service_key.0, "version" = &*service_key.1, "error" = ?e, "Scheduled run not started");
+        }
+    }
+
+    /// Starts the run queued for `service_key` while its previous run was going, if any.
+    fn launch_queued_run(self: &Arc<Self>, service_key: &ServiceKey) {
+        if self.queued_runs.remove(service_key).is_none() {
+            return;
+        }
+        let manager = self.clone();
+        let service_key = service_key.clone();
+        tokio::spawn(async move {
+            info!(
+                "name" = &*service_key.0,
+                "version" = &*service_key.1,
+                "Starting queued scheduled run"
+            );
+            manager.run_scheduled(&service_key).await;
+        });
+    }
+
+    /// Adopts the detached services recorded in the state directory that are still running and
+    /// marks the recorded ones that are gone as Stopped. Records of services no longer in the
+    /// configuration are removed, and their processes stopped, as nothing else would stop them.
+    /// Services still configured but not adopted, e.g. dead-lettered ones, are left running.
+    fn reconcile_detached(&self) {
+        let Some(state_dir) = &self.state_dir else {
+            return;
+        };
+        let mut known: HashSet<ServiceKey> = HashSet::new();
+        let mut configured: HashSet<ServiceKey> = self.dlq.iter().map(|i| i.key.clone()).collect();
+        for group in &self.service_groups {
+            for meta in group.graph.node_weights() {
+                let key: ServiceKey = (meta.name.clone(), meta.version.clone());
+                configured.insert(key.clone());
+                let meta = self.effective_meta(meta);
+                let processes: Vec<(ServiceKey, ServiceMeta)> = match meta.replicas {
+                    Some(count) => (0..count)
+                        .map(|index| (replica_key(&key, index), replica_meta(&meta, index)))
+                        .collect(),
+                    None => vec![(key.clone(), meta.clone())],
+                };
+                let mut found = false;
+                for (process_key, process_meta) in processes {
+                    known.insert(process_key.clone());
+                    let Some(record) =
+                        crate::detach::read_record(state_dir, &process_key.0, &process_key.1)
+                    else {
+                        continue;
+                    };
+                    found = true;
+                    if crate::detach::is_same_process(record.pid, record.start_time) {
+                        let process_meta = self.with_runtime_values(
+                            &process_key,
+                            &key,
+                            &process_meta,
+                            &record.ports,
+                        );
+                        self.adopt_detached(process_key, process_meta, record, state_dir);
+                    } else {
+                        info!(
+                            "name" = &*process_key.0,
+                            "version" = &*process_key.1,
+                            "pid" = record.pid,
+                            "Detached service is no longer running"
+                        );
+                        crate::detach::remove_record(state_dir, &process_key.0, &process_key.1);
+                        self.service_state_map
+                            .insert(process_key, ServiceState::Stopped);
+                    }
+                }
+                if found && meta.replicas.is_some() {
+                    self.refresh_replica_aggregate(&key);
+                }
+            }
+        }
+        for record in crate::detach::list_records(state_dir) {
+            let record_key: ServiceKey =
+                (record.name.as_str().into(), record.version.as_str().into());
+            if known.contains(&record_key) {
+                continue;
+            }
+            if !crate::detach::is_same_process(record.pid, record.start_time) {
+                crate::detach::remove_record(state_dir, &record.name, &record.version);
+                continue;
+            }
+            let base_name =
+                split_replica_name(&record.name).map_or(&*record.name, |(base, _)| base);
+            if configured.contains(&(base_name.into(), record_key.1.clone())) {
+                // Still configured but not adopted (dead-lettered or a replica past the
+                // current count): never kill a service the user has not removed.
+                warn!(
+                    "name" = &*record.name,
+                    "version" = &*record.version,
+                    "pid" = record.pid,
+                    "Detached service cannot be adopted, leaving it running"
+                );
+                continue;
+            }
+            warn!(
+                "name" = &*record.name,
+                "version" = &*record.version,
+                "pid" = record.pid,
+                "Detached service is not in the configuration, stopping it"
+            );
+            let state_dir = state_dir.clone();
+            tokio::spawn(async move {
+                let cgroup = ServiceCgroup::existing(&record.name, &record.version);
+                kill_leftover_processes(Some(record.pid), cgroup.as_ref()).await;
+                crate::detach::remove_record(&state_dir, &record.name, &record.version);
+            });
+        }
+    }
+
+    /// Returns where detached services write their output: the service logs, so it can be read
+    /// back like captured output, or the state directory without them.
+    fn detached_log_options(&self, state_dir: &Path) -> ServiceLogOptions {
+        self.service_logs
+            .clone()
+            .unwrap_or_else(|| ServiceLogOptions {
+                directory: state_dir.to_path_buf(),
+                rotation: LogRotation::default(),
+                max_files: DEFAULT_MAX_LOG_FILES,
+            })
+    }
+
+    /// Marks a running detached service as Running and watches it with [adopted_task].
+    fn adopt_detached(
+        &self,
+        service_key: ServiceKey,
+        meta: ServiceMeta,
+        record: DetachedRecord,
+        state_dir: &Path,
+    ) {
+        info!(
+            "name" = &*service_key.0,
+            "version" = &*service_key.1,
+            "pid" = record.pid,
+            "Adopting detached service"
+        );
+        if let Some(mut runtime) = self.service_runtime_map.get_mut(&service_key) {
+            runtime.pid = Some(record.pid);
+            runtime.started_at = Some(record.started_at);
+            runtime.triggered_by = Some(ServiceRunTrigger::Adopted);
+            runtime.ports = record.ports.clone();
+        }
+        self.service_state_map
+            .insert(service_key.clone(), ServiceState::Running);
+        let cancel_token = CancellationToken::new();
+        self.service_canceltoken_map
+            .insert(service_key.clone(), cancel_token.clone());
+        let log_rotation = DetachedLogRotation::new(
+            &self.detached_log_options(state_dir),
+            &service_key.0,
+            &service_key.1,
+        );
+        let handle = tokio::spawn(adopted_task(
+            meta,
+            record,
+            state_dir.to_path_buf(),
+            log_rotation,
+            self.event_tx.clone(),
+            cancel_token,
+            self.cancel_token.clone(),
+        ));
+        self.service_task_map.insert(service_key, handle);
+    }
+
+    /// Expands the templates of `meta` (see [ServiceMeta]) in one pass, filling in `${replica}`
+    /// from `service_key`, and `${port.*}` and `${dep.*.port.*}` from `ports` and the ports of the
+    /// running dependencies; adds the `SPINDLE_PORT_*` and `SPINDLE_DEP_*_PORT_*` env vars.
+    ///
+    /// A dependency port that is not known (the dependency is not running) is left as written.
+    fn with_runtime_values(
+        &self,
+        service_key: &ServiceKey,
+        graph_key: &ServiceKey,
+        meta: &ServiceMeta,
+        ports: &BTreeMap<String, u16>,
+    ) -> ServiceMeta {
+        let mut values: Vec<(String, String, u16)> = ports
+            .iter()
+            .map(|(port, number)| {
+                (
+                    format!("port.{port}"),
+                    format!("SPINDLE_PORT_{}", env_var_segment(port)),
+                    *number,
+                )
+            })
+            .collect();
+        for dep_key in self.dep_keys(graph_key) {
+            // A replicated dependency is reached through its first replica.
+            let port_key = if self.service_replica_map.contains_key(&dep_key) {
+                replica_key(&dep_key, 0)
+            } else {
+                dep_key.clone()
+            };
+            let Some(dep_ports) = self
+                .service_runtime_map
+                .get(&port_key)
+                .map(|runtime| runtime.ports.clone())
+            else {
+                continue;
+            };
+            for (port, number) in dep_ports {
+                values.push((
+                    format!("dep.{}.port.{port}", dep_key.0),
+                    format!(
+                        "SPINDLE_DEP_{}_PORT_{}",
+                        env_var_segment(&dep_key.0),
+                        env_var_segment(&port)
+                    ),
+                    number,
+                ));
+            }
+        }
+        let mut variables: HashMap<&str, String> = values
+            .iter()
+            .map(|(name, _, number)| (name.as_str(), number.to_string()))
+            .collect();
+        if service_key != graph_key
+            && let Some((_, index)) = split_replica_name(&service_key.0)
+        {
+            variables.insert("replica", index.to_string());
+        }
+        let mut ret = map_meta_values(meta, |value| {
+            interpolate(value, |name| {
+                Some(
+                    variables
+                        .get(name)
+                        .cloned()
+                        .unwrap_or_else(|| format!("${{{name}}}")),
+                )
+            })
+            .unwrap_or_else(|_| value.to_string())
+        });
+        for (_, env_name, number) in values {
+            ret.env.insert(env_name.into(), number.to_string().into());
+        }
+        ret
+    }
+
+    /// Returns the keys of the direct dependencies of `key`.
+    fn dep_keys(&self, key: &ServiceKey) -> Vec<ServiceKey> {
+        let Some(&groupidx) = self.service_groupidx_map.get(key) else {
+            return Vec::new();
+        };
+        let group = &self.service_groups[groupidx];
+        let Some(nodeidx) = group.nodeidx_map.get(key) else {
+            return Vec::new();
+        };
+        let profile = self.group_profile_arc(groupidx);
+        group
+            .active_neighbors(*nodeidx, petgraph::Incoming, profile.as_deref())
+            .into_iter()
+            .filter_map(|dep_idx| group.graph.node_weight(dep_idx))
+            .map(|dep_meta| (dep_meta.name.clone(), dep_meta.version.clone()))
+            .collect()
+    }
+
+    fn replica_set(&self, key: &ServiceKey) -> Option<ReplicaSet> {
+        self.service_replica_map.get(key).map(|set| *set)
+    }
+
+    /// Returns the key of the replicated service `key` belongs to, if `key` is a replica.
+    fn replica_base_key(&self, key: &ServiceKey) -> Option<ServiceKey> {
+        let (base, _) = split_replica_name(&key.0)?;
+        let base_key: ServiceKey = (base.into(), key.1.clone());
+        self.service_replica_map
+            .contains_key(&base_key)
+            .then_some(base_key)
+    }
+
+    /// Starts the replicas in `indices` that are not already running or in a mid-state.
+    fn launch_replicas(
+        &self,
+        meta: &ServiceMeta,
+        indices: std::ops::Range<u32>,
+        trigger: ServiceRunTrigger,
+    ) {
+        let service_key: ServiceKey = (meta.name.clone(), meta.version.clone());
+        for index in indices {
+            let key = replica_key(&service_key, index);
+            let mut entry = self
+                .service_state_map
+                .entry(key.clone())
+                .or_insert(ServiceState::Pending);
+            match entry.value() {
+                ServiceState::Running | ServiceState::Starting | ServiceState::Stopping => {
+                    info!(
+                        "name" = &*key.0,
+                        "version" = &*key.1,
+                        "state" = ?entry.value(),
+                        "Replica is running or in a mid-state, ignoring launch request"
+                    );
+                    continue;
+                }
+                _ => *entry.value_mut() = ServiceState::Starting,
+            }
+            drop(entry);
+            self.service_runtime_map.entry(key.clone()).or_default();
+            if let Err(e) = self.spawn_service_task(
+                key.clone(),
+                &service_key,
+                &replica_meta(meta, index),
+                trigger,
+            ) {
+                warn!("name" = &*key.0, "version" = &*key.1, "error" = ?e, "Failed to start replica");
+            }
+        }
+        self.refresh_replica_aggregate(&service_key);
+    }
+
+    /// Recomputes the state of a replicated service from its replicas.
+    ///
+    /// The service is `Running` once a quorum of replicas runs (at least one); otherwise any
+    /// starting, stopping or failed replica makes it `Starting`, `Stopping` or `Failed`, in that order.
+    fn refresh_replica_aggregate(&self, service_key: &ServiceKey) {
+        let Some(replica_set) = self.replica_set(service_key) else {
+            return;
+        };
+        let (mut running, mut starting, mut stopping, mut failed, mut stopped) =
+            (0u32, false, false, false, false);
+        for index in 0..replica_set.count {
+            let state = self
+                .service_state_map
+                .get(&replica_key(service_key, index))
+                .map(|state| state.clone());
+            match state {
+                Some(ServiceState::Running) => running += 1,
+                Some(ServiceState::Starting) => starting = true,
+                Some(ServiceState::Stopping) => stopping = true,
+                Some(ServiceState::Failed(_)) => failed = true,
+                Some(ServiceState::Stopped) => stopped = true,
+                _ => (),
+            }
+        }
+        let quorum = replica_set
+            .quorum
+            .unwrap_or(replica_set.count)
+            .clamp(1, replica_set.count.max(1));
+        let state = if running >= quorum {
+            ServiceState::Running
+        } else if starting {
+            ServiceState::Starting
+        } else if stopping {
+            ServiceState::Stopping
+        } else if failed {
+            ServiceState::Failed(format!(
+                "Quorum lost: {running} of {} replicas running, {quorum} required",
+                replica_set.count
+            ))
+        } else if stopped {
+            ServiceState::Stopped
+        } else {
+            ServiceState::Pending
+        };
+        self.service_state_map.insert(service_key.clone(), state);
+    }
+
+    /// Returns the current replica count of a replicated service.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    ///
+    /// # Returns
+    ///
+    /// `Some(count)` if the service is replicated, else `None`.
+    pub fn replica_count(&self, name: &str, version: &str) -> Option<u32> {
+        let key: ServiceKey = (name.into(), version.into());
+        self.replica_set(&key).map(|set| set.count)
+    }
 
-        let event_tx = self.event_tx.clone();
-        let cancel_token = self.cancel_token.child_token();
-        self.service_canceltoken_map
-            .insert(service_key.clone(), cancel_token.clone());
-        let meta = meta.clone();
+    /// Changes the number of replicas of a replicated service.
+    ///
+    /// If the service is up, only the added replicas are started and only the removed ones
+    /// (highest indices) are stopped; otherwise the new count applies at the next launch.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    /// * `replicas` - New number of replicas.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success; `Err` if the service is unknown or not replicated, or if `replicas`
+    /// is 0, below the quorum, or more than one for a service with fixed ports.
+    pub fn scale_service(&self, name: &str, version: &str, replicas: u32) -> anyhow::Result<()> {
+        if self.shutting_down.load(Ordering::Acquire) {
+            anyhow::bail!("Service manager is shutting down");
+        }
+        let key: ServiceKey = (name.into(), version.into());
+        let meta = self
+            .service_meta(name, version)
+            .map(|meta| self.effective_meta(&meta))
+            .ok_or_else(|| anyhow::anyhow!("Unknown service: {name}:v{version}"))?;
+        validate_replicas(Some(replicas), meta.quorum)
+            .and_then(|_| {
+                validate_replica_ports(
+                    Some(replicas),
+                    meta.ports.iter().map(|(port, spec)| (&**port, spec)),
+                )
+            })
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let old_count = match self.service_replica_map.get_mut(&key) {
+            Some(mut set) => std::mem::replace(&mut set.count, replicas),
+            None => anyhow::bail!("Service {name}:v{version} is not replicated"),
+        };
+        let is_up = (0..old_count).any(|index| {
+            self.service_state_map
+                .get(&replica_key(&key, index))
+                .is_some_and(|state| {
+                    matches!(*state, ServiceState::Running | ServiceState::Starting)
+                })
+        });
         info!(
-            "name" = &*service_key.0,
-            "version" = &*service_key.1,
-            "Starting service"
+            "name" = name,
+            "version" = version,
+            "from" = old_count,
+            "to" = replicas,
+            "Scaling service"
         );
-        tokio::spawn(service_task(meta, event_tx, cancel_token));
-        return Ok(());
+        if replicas < old_count {
+            for index in replicas..old_count {
+                self.stop_replica(&replica_key(&key, index));
+            }
+            self.refresh_replica_aggregate(&key);
+        } else if is_up {
+            self.launch_replicas(&meta, old_count..replicas, ServiceRunTrigger::Manual);
+        } else {
+            self.refresh_replica_aggregate(&key);
+        }
+        Ok(())
+    }
+
+    /// Marks a running or starting replica as stopping and cancels its task.
+    fn stop_replica(&self, key: &ServiceKey) {
+        if let Some(mut entry) = self.service_state_map.get_mut(key) {
+            if !matches!(
+                entry.value(),
+                ServiceState::Running | ServiceState::Starting
+            ) {
+                return;
+            }
+            *entry.value_mut() = ServiceState::Stopping;
+        }
+        if let Some((_, canceltoken)) = self.service_canceltoken_map.remove(key) {
+            canceltoken.cancel();
+        }
     }
 
     async fn wait_service_running(&self, name: &str, version: &str) -> anyhow::Result<()> {
@@ -756,9 +3396,9 @@ impl ServiceManager {
             .get(groupidx)
             .ok_or_else(|| anyhow::anyhow!("Group index out of bounds"))?;
 
-        let sorted_nodes = petgraph::algo::toposort(&group.graph, None).map_err(|e| {
+        let profile = self.group_profile_arc(groupidx);
+        let sorted_nodes = group.toposort(profile.as_deref()).inspect_err(|e| {
             warn!("groupidx" = groupidx, "error" = ?e, "Failed to get toposort");
-            anyhow::anyhow!("Failed to get toposort: {:?}", e)
         })?;
 
         let start_meta_order: Vec<&ServiceMeta> = sorted_nodes
@@ -772,7 +3412,16 @@ impl ServiceManager {
             .collect::<Result<_, _>>()?;
 
         for meta in start_meta_order {
-            self.launch_service(meta).await?;
+            if meta.schedule.is_some() {
+                info!(
+                    "name" = &*meta.name,
+                    "version" = &*meta.version,
+                    "Scheduled service is not started with its group"
+                );
+                continue;
+            }
+            self.launch_service_with_trigger(meta, ServiceRunTrigger::Group)
+                .await?;
             let start_rs = tokio::time::timeout(
                 service_start_timeout,
                 self.wait_service_running(&meta.name, &meta.version),
@@ -795,6 +3444,27 @@ impl ServiceManager {
         Ok(())
     }
 
+    /// Selects `profile` for the group (see [Self::set_group_profile]) and launches it (see [Self::launch_group]).
+    ///
+    /// # Arguments
+    ///
+    /// * `groupidx` - Index of the group to launch.
+    /// * `service_start_timeout` - Max duration to wait for each service to reach Running.
+    /// * `profile` - Profile to run with; `None` for the base config.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success; `Err` if the profile cannot be selected or the launch fails.
+    pub async fn launch_group_with_profile(
+        &self,
+        groupidx: usize,
+        service_start_timeout: Duration,
+        profile: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.set_group_profile(groupidx, profile)?;
+        self.launch_group(groupidx, service_start_timeout).await
+    }
+
     fn rev_dep_keys(&self, name: &str, version: &str) -> anyhow::Result<Vec<ServiceKey>> {
         let key: ServiceKey = (name.into(), version.into());
         let groupidx = match self.service_groupidx_map.get(&key) {
@@ -821,9 +3491,9 @@ impl ServiceManager {
             }
         };
         let mut ret = Vec::new();
-        for rev_dep_nodeidx in group
-            .graph
-            .neighbors_directed(cur_nodeidx, petgraph::Outgoing)
+        let profile = self.group_profile_arc(groupidx);
+        for rev_dep_nodeidx in
+            group.active_neighbors(cur_nodeidx, petgraph::Outgoing, profile.as_deref())
         {
             let rev_dep_meta = match group.graph.node_weight(rev_dep_nodeidx) {
                 Some(meta) => meta,
@@ -852,6 +3522,9 @@ impl ServiceManager {
     /// `Ok(())` on success; `Err` if state or cancel token is invalid.
     pub async fn stop_service(&self, name: &str, version: &str) -> anyhow::Result<()> {
         let key: ServiceKey = (name.into(), version.into());
+        if let Some(replica_set) = self.replica_set(&key) {
+            return self.stop_replicas(&key, replica_set.count).await;
+        }
         let mut entry = match self.service_state_map.get_mut(&key) {
             Some(entry) => entry,
             None => {
@@ -914,6 +3587,358 @@ impl ServiceManager {
         Ok(())
     }
 
+    /// Stops all replicas of a replicated service after stopping its reverse dependencies.
+    async fn stop_replicas(&self, key: &ServiceKey, count: u32) -> anyhow::Result<()> {
+        let mut stopping = Vec::new();
+        for index in 0..count {
+            let replica = replica_key(key, index);
+            if let Some(mut entry) = self.service_state_map.get_mut(&replica)
+                && matches!(
+                    entry.value(),
+                    ServiceState::Running | ServiceState::Starting
+                )
+            {
+                *entry.value_mut() = ServiceState::Stopping;
+                stopping.push(replica);
+            }
+        }
+        if stopping.is_empty() {
+            info!(
+                "name" = &*key.0,
+                "version" = &*key.1,
+                "Service is already stopped, ignoring stop request"
+            );
+            return Ok(());
+        }
+        self.refresh_replica_aggregate(key);
+
+        let rev_dep_keys = self.rev_dep_keys(&key.0, &key.1)?;
+        for (dep_name, dep_version) in rev_dep_keys {
+            Box::pin(self.stop_service(&dep_name, &dep_version)).await?;
+        }
+
+        for replica in stopping {
+            if let Some((_, canceltoken)) = self.service_canceltoken_map.remove(&replica) {
+                canceltoken.cancel();
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops a service (all its replicas if replicated) without stopping its dependents.
+    ///
+    /// # Returns
+    ///
+    /// Keys of the services and replicas being stopped.
+    fn stop_without_dependents(&self, key: &ServiceKey) -> Vec<ServiceKey> {
+        let keys = match self.replica_set(key) {
+            Some(replica_set) => (0..replica_set.count)
+                .map(|index| replica_key(key, index))
+                .collect(),
+            None => vec![key.clone()],
+        };
+        let mut stopping = Vec::new();
+        for key in keys {
+            if let Some(mut entry) = self.service_state_map.get_mut(&key)
+                && matches!(
+                    entry.value(),
+                    ServiceState::Running | ServiceState::Starting
+                )
+            {
+                *entry.value_mut() = ServiceState::Stopping;
+                stopping.push(key);
+            }
+        }
+        if self.replica_set(key).is_some() {
+            self.refresh_replica_aggregate(key);
+        }
+        for key in &stopping {
+            if let Some((_, canceltoken)) = self.service_canceltoken_map.remove(key) {
+                canceltoken.cancel();
+            }
+        }
+        stopping
+    }
+
+    /// Returns the running services that depend on `key`, directly or not, in dependency order.
+    fn running_dependents(&self, key: &ServiceKey) -> anyhow::Result<Vec<ServiceKey>> {
+        let mut found = HashSet::new();
+        let mut pending = vec![key.clone()];
+        while let Some(cur) = pending.pop() {
+            for dep_key in self.rev_dep_keys(&cur.0, &cur.1)? {
+                if matches!(
+                    self.service_state(&dep_key.0, &dep_key.1),
+                    Some(ServiceState::Running)
+                ) && found.insert(dep_key.clone())
+                {
+                    pending.push(dep_key);
+                }
+            }
+        }
+        if found.is_empty() {
+            return Ok(Vec::new());
+        }
+        let groupidx = *self
+            .service_groupidx_map
+            .get(key)
+            .ok_or_else(|| anyhow::anyhow!("service groupidx not found: {}/{}", key.0, key.1))?;
+        let group = &self.service_groups[groupidx];
+        let profile = self.group_profile_arc(groupidx);
+        let sorted_nodes = group.toposort(profile.as_deref())?;
+        Ok(sorted_nodes
+            .into_iter()
+            .filter_map(|idx| group.graph.node_weight(idx))
+            .map(|meta| (meta.name.clone(), meta.version.clone()))
+            .filter(|key| found.contains(key))
+            .collect())
+    }
+
+    /// Waits until none of `keys` is stopping anymore.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` once all stopped, or `Err` if some are still stopping after `timeout`.
+    async fn wait_services_stopped(
+        &self,
+        keys: &[ServiceKey],
+        timeout: Duration,
+    ) -> anyhow::Result<()> {
+        const POLLING_INTERVAL: Duration = Duration::from_millis(100);
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let stopping = keys.iter().any(|key| {
+                matches!(
+                    self.service_state_map.get(key).as_deref(),
+                    Some(ServiceState::Stopping)
+                )
+            });
+            if !stopping {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                anyhow::bail!("Services still stopping after {timeout:?}");
+            }
+            tokio::time::sleep(POLLING_INTERVAL).await;
+        }
+    }
+
+    /// Restarts a running service through the stop and launch machinery; a service that is not
+    /// running is left alone.
+    ///
+    /// With `with_dependents`, the service is stopped along with its dependents (as by
+    /// [Self::stop_service]) and the dependents that were running are launched again once it
+    /// runs. Otherwise only the service is restarted and its dependents keep running.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Service name.
+    /// * `version` - Service version.
+    /// * `with_dependents` - Whether to restart the running dependents too.
+    /// * `trigger` - What caused the restart; reported in [ServiceRunRecord].
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success or if the service is not running; `Err` if stopping or relaunching
+    /// fails or times out.
+    pub async fn restart_service(
+        &self,
+        name: &str,
+        version: &str,
+        with_dependents: bool,
+        trigger: ServiceRunTrigger,
+    ) -> anyhow::Result<()> {
+        let key: ServiceKey = (name.into(), version.into());
+        let meta = self
+            .service_meta(name, version)
+            .ok_or_else(|| anyhow::anyhow!("service meta not found: {name}/{version}"))?;
+        if !matches!(
+            self.service_state(name, version),
+            Some(ServiceState::Running)
+        ) {
+            info!(
+                "name" = name,
+                "version" = version,
+                "Service is not running, ignoring restart request"
+            );
+            return Ok(());
+        }
+        info!(
+            "name" = name,
+            "version" = version,
+            "with_dependents" = with_dependents,
+            "Restarting service"
+        );
+        let dependents = if with_dependents {
+            self.running_dependents(&key)?
+        } else {
+            Vec::new()
+        };
+        let mut stopping = dependents.clone();
+        if with_dependents {
+            self.stop_service(name, version).await?;
+            match self.replica_set(&key) {
+                Some(replica_set) => {
+                    stopping.extend((0..replica_set.count).map(|index| replica_key(&key, index)))
+                }
+                None => stopping.push(key.clone()),
+            }
+        } else {
+            stopping.extend(self.stop_without_dependents(&key));
+        }
+        self.wait_services_stopped(&stopping, RESTART_TIMEOUT)
+            .await?;
+        // Like launch_group: each service is running before the next one in dependency order is
+        // launched, so dependents of dependents find their dependencies running.
+        let mut launch_order = vec![meta];
+        for (dep_name, dep_version) in dependents {
+            if let Some(dep_meta) = self.service_meta(&dep_name, &dep_version) {
+                launch_order.push(dep_meta);
+            }
+        }
+        for meta in &launch_order {
+            self.launch_service_with_trigger(meta, trigger).await?;
+            tokio::time::timeout(
+                RESTART_TIMEOUT,
+                self.wait_service_running(&meta.name, &meta.version),
+            )
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!("Service start timeout: {}/{}", meta.name, meta.version)
+            })??;
+        }
+        Ok(())
+    }
+
+    /// Stops every service, dependents before their dependencies, and waits for all service
+    /// tasks to finish. Further launches are refused.
+    ///
+    /// Services are stopped in levels: first those no running service depends on, then those
+    /// only the first level depends on, and so on; each level is stopped at once and awaited
+    /// before the next one. Detached services are left running for the next manager to adopt.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Time allowed for the whole shutdown.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` once all service tasks finished; `Err` listing the services whose tasks were
+    /// still running when `timeout` elapsed.
+    pub async fn shutdown(&self, timeout: Duration) -> anyhow::Result<()> {
+        info!("timeout" = ?timeout, "Shutting down service manager");
+        self.shutting_down.store(true, Ordering::Release);
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut unfinished = Vec::new();
+        for level in self.shutdown_levels() {
+            let mut stopping = Vec::new();
+            for meta in &level {
+                if meta.detached && self.state_dir.is_some() {
+                    continue;
+                }
+                let key: ServiceKey = (meta.name.clone(), meta.version.clone());
+                let process_keys: Vec<ServiceKey> = match self.replica_set(&key) {
+                    Some(replica_set) => (0..replica_set.count)
+                        .map(|index| replica_key(&key, index))
+                        .collect(),
+                    None => vec![key.clone()],
+                };
+                for process_key in process_keys {
+                    if let Some(mut entry) = self.service_state_map.get_mut(&process_key)
+                        && matches!(
+                            entry.value(),
+                            ServiceState::Running | ServiceState::Starting
+                        )
+                    {
+                        *entry.value_mut() = ServiceState::Stopping;
+                    }
+                    if let Some((_, canceltoken)) =
+                        self.service_canceltoken_map.remove(&process_key)
+                    {
+                        canceltoken.cancel();
+                    }
+                    stopping.push(process_key);
+                }
+                if self.replica_set(&key).is_some() {
+                    self.refresh_replica_aggregate(&key);
+                }
+            }
+            for key in stopping {
+                if !self.join_service_task(&key, deadline).await {
+                    unfinished.push(key);
+                }
+            }
+        }
+        // Detached service tasks return once the root token is cancelled; so would any task
+        // spawned while the levels were being stopped.
+        self.cancel_token.cancel();
+        let remaining: Vec<ServiceKey> = self
+            .service_task_map
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+        for key in remaining {
+            if !self.join_service_task(&key, deadline).await {
+                unfinished.push(key);
+            }
+        }
+        if !unfinished.is_empty() {
+            let names: Vec<String> = unfinished
+                .iter()
+                .map(|(name, version)| format!("{name}:v{version}"))
+                .collect();
+            warn!("services" = ?names, "Service manager shutdown timed out");
+            anyhow::bail!(
+                "Services still stopping after {timeout:?}: {}",
+                names.join(", ")
+            );
+        }
+        info!("Service manager shut down");
+        Ok(())
+    }
+
+    /// Groups the services of all groups into shutdown levels under each group's active profile:
+    /// a service is in level 0 if nothing depends on it, otherwise one level after its
+    /// deepest dependent.
+    fn shutdown_levels(&self) -> Vec<Vec<ServiceMeta>> {
+        let mut levels: Vec<Vec<ServiceMeta>> = Vec::new();
+        for (groupidx, group) in self.service_groups.iter().enumerate() {
+            let profile = self.group_profile_arc(groupidx);
+            let mut level_map: HashMap<NodeIndex, usize> = HashMap::new();
+            for node_idx in group.graph.node_indices() {
+                group_shutdown_level(group, node_idx, profile.as_deref(), &mut level_map);
+            }
+            for (node_idx, level) in level_map {
+                let Some(meta) = group.graph.node_weight(node_idx) else {
+                    continue;
+                };
+                if levels.len() <= level {
+                    levels.resize_with(level + 1, Vec::new);
+                }
+                levels[level].push(meta.clone());
+            }
+        }
+        levels
+    }
+
+    /// Waits until the task of `key` finishes or `deadline` passes.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the task finished (or there was none), `false` on timeout.
+    async fn join_service_task(&self, key: &ServiceKey, deadline: tokio::time::Instant) -> bool {
+        let Some((_, handle)) = self.service_task_map.remove(key) else {
+            return true;
+        };
+        match tokio::time::timeout_at(deadline, handle).await {
+            Ok(Ok(())) => true,
+            Ok(Err(e)) => {
+                warn!("name" = &*key.0, "version" = &*key.1, "error" = ?e, "Service task failed");
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
     /// Returns the number of service groups.
     ///
     /// # Returns
@@ -947,7 +3972,46 @@ impl ServiceManager {
         ret
     }
 
-    /// Returns (name, version) for services in the group that have in-degree 0 (no dependencies within the group).
+    /// Returns the [ServiceMeta] of every service in the group with the (name, version) of its
+    /// dependencies, both resolved for the group's active profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_idx` - Index of the group.
+    ///
+    /// # Returns
+    ///
+    /// Vector of (meta, dependency keys); empty if `group_idx` is invalid.
+    pub fn group_service_metas(
+        &self,
+        group_idx: usize,
+    ) -> Vec<(ServiceMeta, Vec<(String, String)>)> {
+        let group = match self.service_groups.get(group_idx) {
+            Some(group) => group,
+            None => {
+                warn!("group_idx" = group_idx, "group not found");
+                return Vec::new();
+            }
+        };
+        let profile = self.group_profile_arc(group_idx);
+        let mut ret = Vec::with_capacity(group.graph.node_count());
+        for node_idx in group.graph.node_indices() {
+            let Some(meta) = group.graph.node_weight(node_idx) else {
+                continue;
+            };
+            let deps = group
+                .active_neighbors(node_idx, petgraph::Incoming, profile.as_deref())
+                .into_iter()
+                .filter_map(|dep_idx| group.graph.node_weight(dep_idx))
+                .map(|dep_meta| (dep_meta.name.to_string(), dep_meta.version.to_string()))
+                .collect();
+            ret.push((profile_meta(meta, profile.as_deref()), deps));
+        }
+        ret
+    }
+
+    /// Returns (name, version) for services in the group that have in-degree 0 (no dependencies
+    /// within the group under its active profile).
     /// Stopping these roots will cascade-stop the whole group via [Self::stop_service].
     ///
     /// # Arguments
@@ -965,13 +4029,12 @@ impl ServiceManager {
                 return Vec::new();
             }
         };
+        let profile = self.group_profile_arc(group_idx);
         let mut ret = Vec::new();
         for node_idx in group.graph.node_indices() {
-            let has_incoming = group
-                .graph
-                .neighbors_directed(node_idx, petgraph::Incoming)
-                .next()
-                .is_some();
+            let has_incoming = !group
+                .active_neighbors(node_idx, petgraph::Incoming, profile.as_deref())
+                .is_empty();
             if !has_incoming {
                 if let Some(meta) = group.graph.node_weight(node_idx) {
                     ret.push((meta.name.to_string(), meta.version.to_string()));
@@ -980,10 +4043,22 @@ impl ServiceManager {
         }
         ret
     }
+
+    /// Sends `record` to [ServiceManagerOptions::run_records], waiting while the channel is full.
+    async fn publish_run_record(&self, record: ServiceRunRecord) {
+        let Some(run_record_tx) = &self.run_record_tx else {
+            return;
+        };
+        if run_record_tx.send(record).await.is_err() {
+            warn!("Run record receiver dropped, run not recorded");
+        }
+    }
 }
 
 impl Drop for ServiceManager {
     /// Cancels the root token so all child service tasks receive cancellation and kill their subprocesses.
+    /// Detached services are left running for the next manager to adopt.
+    /// Unlike [ServiceManager::shutdown], this neither orders the stops nor waits for them.
     fn drop(&mut self) {
         info!("Service manager dropped, cancelling root token");
         self.cancel_token.cancel();
@@ -1003,7 +4078,14 @@ async fn handle_service_manager_event(
             }
         };
         match event {
-            ServiceManagerEvent::ServiceStarted { service_key } => {
+            ServiceManagerEvent::ServiceStarted { service_key, pid } => {
+                if let Some(mut runtime) = manager.service_runtime_map.get_mut(&service_key) {
+                    runtime.pid = pid;
+                    runtime.started_at = Some(unix_millis_now());
+                    runtime.stopped_at = None;
+                    runtime.exit_code = None;
+                    runtime.signal = None;
+                }
                 let mut entry = match manager.service_state_map.get_mut(&service_key) {
                     Some(entry) => entry,
                     None => {
@@ -1025,8 +4107,25 @@ async fn handle_service_manager_event(
                         "service is not starting, ignoring event"
                     );
                 }
+                drop(entry);
+                if let Some(base_key) = manager.replica_base_key(&service_key) {
+                    manager.refresh_replica_aggregate(&base_key);
+                }
             }
-            ServiceManagerEvent::ServiceStopped { service_key } => {
+            ServiceManagerEvent::ServiceStopped {
+                service_key,
+                exit_code,
+                signal,
+            } => {
+                if let Some(mut runtime) = manager.service_runtime_map.get_mut(&service_key) {
+                    runtime.pid = None;
+                    runtime.stopped_at = Some(unix_millis_now());
+                    runtime.exit_code = exit_code;
+                    runtime.signal = signal;
+                    let record = run_record(&service_key, &runtime, None);
+                    drop(runtime);
+                    manager.publish_run_record(record).await;
+                }
                 let mut entry = match manager.service_state_map.get_mut(&service_key) {
                     Some(entry) => entry,
                     None => {
@@ -1048,11 +4147,29 @@ async fn handle_service_manager_event(
                         "service is not stopping, ignoring event"
                     );
                 }
+                drop(entry);
+                // A stop also drops the scheduled run queued behind the stopped one.
+                manager.queued_runs.remove(&service_key);
+                if let Some(base_key) = manager.replica_base_key(&service_key) {
+                    manager.refresh_replica_aggregate(&base_key);
+                }
             }
             ServiceManagerEvent::ServiceCrashed {
                 service_key,
                 reason,
+                exit_code,
+                signal,
             } => {
+                if let Some(mut runtime) = manager.service_runtime_map.get_mut(&service_key) {
+                    runtime.pid = None;
+                    runtime.stopped_at = Some(unix_millis_now());
+                    runtime.exit_code = exit_code;
+                    runtime.signal = signal;
+                    runtime.last_failure_reason = Some(reason.clone());
+                    let record = run_record(&service_key, &runtime, Some(reason.clone()));
+                    drop(runtime);
+                    manager.publish_run_record(record).await;
+                }
                 let mut entry = match manager.service_state_map.get_mut(&service_key) {
                     Some(entry) => entry,
                     None => {
@@ -1073,8 +4190,29 @@ async fn handle_service_manager_event(
                     "service crashed"
                 );
                 *entry.value_mut() = ServiceState::Failed(reason);
+                drop(entry);
+                manager.launch_queued_run(&service_key);
+                // A crashed replica only takes dependents down when the service loses its quorum.
+                let stopped_key = match manager.replica_base_key(&service_key) {
+                    Some(base_key) => {
+                        let was_running = matches!(
+                            manager.service_state_map.get(&base_key).as_deref(),
+                            Some(ServiceState::Running)
+                        );
+                        manager.refresh_replica_aggregate(&base_key);
+                        let is_running = matches!(
+                            manager.service_state_map.get(&base_key).as_deref(),
+                            Some(ServiceState::Running)
+                        );
+                        if !was_running || is_running {
+                            continue;
+                        }
+                        base_key
+                    }
+                    None => service_key,
+                };
                 let manager_clone = manager.clone();
-                let (name, version) = (service_key.0.to_string(), service_key.1.to_string());
+                let (name, version) = (stopped_key.0.to_string(), stopped_key.1.to_string());
                 let fut = async move {
                     let rev_dep_keys = match manager_clone.rev_dep_keys(&name, &version) {
                         Ok(keys) => keys,
@@ -1091,7 +4229,159 @@ async fn handle_service_manager_event(
                 };
                 tokio::spawn(fut);
             }
+            ServiceManagerEvent::ServiceFinished {
+                service_key,
+                exit_code,
+                signal,
+            } => {
+                if let Some(mut runtime) = manager.service_runtime_map.get_mut(&service_key) {
+                    runtime.pid = None;
+                    runtime.stopped_at = Some(unix_millis_now());
+                    runtime.exit_code = exit_code;
+                    runtime.signal = signal;
+                    let record = run_record(&service_key, &runtime, None);
+                    drop(runtime);
+                    manager.publish_run_record(record).await;
+                }
+                manager.service_canceltoken_map.remove(&service_key);
+                manager
+                    .service_state_map
+                    .insert(service_key.clone(), ServiceState::Stopped);
+                manager.launch_queued_run(&service_key);
+            }
         }
     }
     info!("Service manager event handler stopped");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_names_reserve_replica_and_instance_separators() {
+        for name in ["api", "worker@", "worker@emails", "a.b-c_d"] {
+            assert_eq!(validate_service_name(name), Ok(()), "{name}");
+        }
+        for name in ["api#0", "#", "@emails", "worker@a@b", "worker@@"] {
+            assert!(validate_service_name(name).is_err(), "{name}");
+        }
+    }
+
+    fn config(name: &str) -> ServiceConfig {
+        ServiceConfig {
+            name: name.to_string(),
+            version: "1".to_string(),
+            program: "true".into(),
+            ..Default::default()
+        }
+    }
+
+    fn rejected(configs: &[ServiceConfig]) -> Vec<(String, String)> {
+        let mut ret: Vec<(String, String)> = validate_configs(configs)
+            .issues
+            .into_iter()
+            .map(|issue| (issue.name, issue.reason))
+            .collect();
+        ret.sort();
+        ret
+    }
+
+    #[test]
+    fn replica_names() {
+        let key: ServiceKey = ("api".into(), "1".into());
+        let replica = replica_key(&key, 3);
+        assert_eq!((&*replica.0, &*replica.1), ("api#3", "1"));
+        assert_eq!(split_replica_name(&replica.0), Some(("api", 3)));
+        assert_eq!(
+            split_replica_name("worker@emails#0"),
+            Some(("worker@emails", 0))
+        );
+        for name in ["api", "api#", "#0", "api#x", "api#-1", "api#99999999999"] {
+            assert_eq!(split_replica_name(name), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn replicas_and_quorum() {
+        assert_eq!(validate_replicas(None, None), Ok(()));
+        assert_eq!(validate_replicas(Some(3), Some(2)), Ok(()));
+        assert_eq!(validate_replicas(Some(3), Some(3)), Ok(()));
+        assert_eq!(validate_replicas(None, Some(1)), Ok(()));
+        assert!(validate_replicas(Some(0), None).is_err());
+        assert!(validate_replicas(Some(3), Some(0)).is_err());
+        assert!(validate_replicas(Some(2), Some(3)).is_err());
+
+        let mut api = config("api");
+        api.replicas = Some(2);
+        api.ports = BTreeMap::from([
+            ("admin".to_string(), PortSpec::Auto),
+            ("http".to_string(), PortSpec::Fixed(8080)),
+        ]);
+        let issues = rejected(std::slice::from_ref(&api));
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].1.contains("\"http\""), "{}", issues[0].1);
+        api.ports.insert("http".to_string(), PortSpec::Auto);
+        assert_eq!(rejected(&[api]), []);
+    }
+
+    #[test]
+    fn port_references() {
+        assert!(is_valid_port_name("http-admin_2"));
+        assert!(!is_valid_port_name(""));
+        assert!(!is_valid_port_name("http.admin"));
+
+        let mut db = config("db");
+        db.ports = BTreeMap::from([("pg".to_string(), PortSpec::Auto)]);
+        let mut api = config("api");
+        api.ports = BTreeMap::from([("http".to_string(), PortSpec::Fixed(8080))]);
+        api.dependencies = vec![("db".to_string(), "1".to_string())];
+        api.args = vec!["--listen=${port.http}".to_string()];
+        api.env = BTreeMap::from([("DB".to_string(), "localhost:${dep.db.port.pg}".to_string())]);
+        assert_eq!(rejected(&[db.clone(), api.clone()]), []);
+
+        // Only declared ports of the service and of its dependencies can be referenced.
+        let mut bad = api.clone();
+        bad.args = vec![
+            "${port.admin}".to_string(),
+            "${dep.db.port.http}".to_string(),
+        ];
+        let issues = rejected(&[db.clone(), bad]);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].1.contains("${port.admin}"), "{}", issues[0].1);
+        assert!(
+            issues[0].1.contains("${dep.db.port.http}"),
+            "{}",
+            issues[0].1
+        );
+        let mut bad = config("web");
+        bad.args = vec!["${dep.db.port.pg}".to_string()];
+        assert_eq!(rejected(&[db, bad]).len(), 1);
+    }
+
+    #[test]
+    fn profile_dependency_cycles() {
+        let overlay = |dep: &str| ProfileOverlay {
+            dependencies: Some(vec![(dep.to_string(), "1".to_string())]),
+            ..Default::default()
+        };
+        // a -> b only in dev and b -> a only in test: no profile has a cycle.
+        let mut a = config("a");
+        a.profiles = BTreeMap::from([("dev".to_string(), overlay("b"))]);
+        let mut b = config("b");
+        b.profiles = BTreeMap::from([("test".to_string(), overlay("a"))]);
+        assert_eq!(rejected(&[a.clone(), b.clone()]), []);
+
+        b.profiles.insert("dev".to_string(), overlay("a"));
+        let issues = rejected(&[a.clone(), b]);
+        assert_eq!(issues.len(), 2);
+        for (_, reason) in &issues {
+            assert!(reason.ends_with("in profile dev"), "{reason}");
+        }
+
+        // A base dependency applies to every profile without its own dependencies.
+        let mut b = config("b");
+        b.dependencies = vec![("a".to_string(), "1".to_string())];
+        assert_eq!(rejected(&[a, b]).len(), 2);
+    }
+}
@@ -0,0 +1,776 @@
+//! Captured output of services, kept in one rotating log file per service.
+//!
+//! Each line is written as `<RFC 3339 UTC time> <stream> <line>` to `<service>.log` in the log
+//! directory. Once the file rotates (daily, or when it would exceed a size), it is renamed to
+//! `<service>.<YYYYMMDDTHHMMSSmmm>.log` and a fresh file is started; only the newest rotated
+//! files are kept. The history of a service is its rotated files, oldest first, followed by the
+//! current file, and can be read back by byte or line range.
+//!
+//! Detached services outlive Spindle, so they write their output straight to the current file,
+//! without time or stream; it is rotated by copying and truncating it, see
+//! [DetachedLogRotation].
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::service::ServiceKey;
+
+/// Rotated files kept per service when no retention is set.
+pub const DEFAULT_MAX_LOG_FILES: usize = 7;
+
+/// Max bytes returned by one [read_service_log] call.
+const MAX_PAGE_BYTES: u64 = 4 * 1024 * 1024;
+/// Max lines returned by one [read_service_log] call.
+const MAX_PAGE_LINES: u64 = 10_000;
+
+/// Output stream of a service process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    /// Returns the stable string form, as used in serialization and log files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stdout => "stdout",
+            Self::Stderr => "stderr",
+        }
+    }
+}
+
+/// A line of captured service output, published by [crate::service::ServiceManager].
+#[derive(Debug, Clone)]
+pub struct ServiceOutputLine {
+    pub key: ServiceKey,
+    pub stream: OutputStream,
+    /// Line without its line ending; invalid UTF-8 is replaced.
+    pub line: String,
+    /// Capture time in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// When a service log file is rotated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    /// At the first line written on a new day (UTC).
+    #[default]
+    Daily,
+    /// Before a line would make the file larger than `max_bytes`.
+    Size { max_bytes: u64 },
+}
+
+/// Where and how captured service output is written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceLogOptions {
+    /// Directory holding the log files of all services.
+    pub directory: PathBuf,
+    /// When a log file is rotated.
+    #[serde(default)]
+    pub rotation: LogRotation,
+    /// Rotated files kept per service, besides the current one; older ones are deleted.
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+}
+
+fn default_max_files() -> usize {
+    DEFAULT_MAX_LOG_FILES
+}
+
+/// Returns the path of the current log file of a service.
+pub fn log_path(directory: &Path, name: &str, version: &str) -> PathBuf {
+    directory.join(format!("{}.log", crate::detach::record_stem(name, version)))
+}
+
+/// Returns true if `suffix` is a rotation time as in `<stem>.<suffix>.log`.
+fn is_rotation_suffix(suffix: &str) -> bool {
+    let bytes = suffix.as_bytes();
+    bytes.len() == 18
+        && bytes[8] == b'T'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(idx, byte)| idx == 8 || byte.is_ascii_digit())
+}
+
+/// Returns the rotated log files of a service, oldest first.
+fn rotated_files(directory: &Path, stem: &str) -> std::io::Result<Vec<PathBuf>> {
+    let prefix = format!("{stem}.");
+    let mut ret = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        if let Some(suffix) = file_name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(".log"))
+            && is_rotation_suffix(suffix)
+        {
+            ret.push(entry.path());
+        }
+    }
+    ret.sort();
+    Ok(ret)
+}
+
+/// Returns the log files of a service, oldest first and the current file last.
+///
+/// # Returns
+///
+/// The files; empty if the service has no log yet.
+pub fn log_files(directory: &Path, name: &str, version: &str) -> std::io::Result<Vec<PathBuf>> {
+    let stem = crate::detach::record_stem(name, version);
+    let mut ret = match rotated_files(directory, &stem) {
+        Ok(files) => files,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let current = log_path(directory, name, version);
+    if current.exists() {
+        ret.push(current);
+    }
+    Ok(ret)
+}
+
+/// Appends captured lines of one service to its log file, rotating and pruning as configured.
+pub(crate) struct ServiceLogWriter {
+    options: ServiceLogOptions,
+    stem: String,
+    path: PathBuf,
+    file: File,
+    size: u64,
+    /// UTC day the current file was started on, for daily rotation.
+    opened_on: NaiveDate,
+}
+
+impl ServiceLogWriter {
+    /// Opens the current log file of a service for appending, creating the directory if needed.
+    pub(crate) fn open(
+        options: &ServiceLogOptions,
+        name: &str,
+        version: &str,
+    ) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&options.directory)?;
+        let path = log_path(&options.directory, name, version);
+        let file = File::options().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+        let opened_on = metadata
+            .modified()
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now())
+            .date_naive();
+        Ok(Self {
+            options: options.clone(),
+            stem: crate::detach::record_stem(name, version),
+            path,
+            file,
+            size: metadata.len(),
+            opened_on,
+        })
+    }
+
+    /// Writes one line, rotating the file first if it is due.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - Stream the line was captured from.
+    /// * `line` - Line without its line ending.
+    /// * `timestamp` - Capture time in milliseconds since the Unix epoch.
+    pub(crate) fn write_line(
+        &mut self,
+        stream: OutputStream,
+        line: &str,
+        timestamp: u64,
+    ) -> std::io::Result<()> {
+        let time = DateTime::<Utc>::from_timestamp_millis(timestamp as i64).unwrap_or_default();
+        let entry = format!(
+            "{} {} {line}\n",
+            time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            stream.as_str()
+        );
+        let due = self.size > 0
+            && match self.options.rotation {
+                LogRotation::Daily => time.date_naive() != self.opened_on,
+                LogRotation::Size { max_bytes } => self.size + entry.len() as u64 > max_bytes,
+            };
+        if due {
+            self.rotate()?;
+        }
+        if self.size == 0 {
+            self.opened_on = time.date_naive();
+        }
+        self.file.write_all(entry.as_bytes())?;
+        self.size += entry.len() as u64;
+        Ok(())
+    }
+
+    /// Renames the current file to a rotated one, starts a new one and prunes old rotated files.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        std::fs::rename(
+            &self.path,
+            next_rotated_path(&self.options.directory, &self.stem),
+        )?;
+        forget_line_count(&self.path);
+        self.file = File::options().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        prune_rotated_files(&self.options, &self.stem)
+    }
+}
+
+/// Rotates the log file a detached service writes to itself, with the same settings as
+/// [ServiceLogWriter].
+///
+/// The service keeps the file open for appending, so a due file is copied to a rotated one and
+/// truncated instead of renamed; lines written while it is copied may be lost. The file is only
+/// checked when [DetachedLogRotation::rotate_if_due] is called, so a size limit may be overrun
+/// in between.
+pub(crate) struct DetachedLogRotation {
+    options: ServiceLogOptions,
+    stem: String,
+    path: PathBuf,
+    /// UTC day the current file was started on, for daily rotation.
+    opened_on: NaiveDate,
+}
+
+impl DetachedLogRotation {
+    /// Creates the rotation of the current log file of a service.
+    pub(crate) fn new(options: &ServiceLogOptions, name: &str, version: &str) -> Self {
+        let path = log_path(&options.directory, name, version);
+        let opened_on = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now())
+            .date_naive();
+        Self {
+            options: options.clone(),
+            stem: crate::detach::record_stem(name, version),
+            path,
+            opened_on,
+        }
+    }
+
+    /// Returns the path of the log file the service writes to.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Rotates the file and prunes old rotated files if the file is due.
+    pub(crate) fn rotate_if_due(&mut self) -> std::io::Result<()> {
+        let size = match std::fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        let today = Utc::now().date_naive();
+        if size == 0 {
+            self.opened_on = today;
+            return Ok(());
+        }
+        let due = match self.options.rotation {
+            LogRotation::Daily => today != self.opened_on,
+            LogRotation::Size { max_bytes } => size > max_bytes,
+        };
+        if !due {
+            return Ok(());
+        }
+        std::fs::copy(
+            &self.path,
+            next_rotated_path(&self.options.directory, &self.stem),
+        )?;
+        File::options().write(true).open(&self.path)?.set_len(0)?;
+        forget_line_count(&self.path);
+        self.opened_on = today;
+        prune_rotated_files(&self.options, &self.stem)
+    }
+}
+
+/// Returns an unused path for a new rotated file of `stem`, named after the current time.
+fn next_rotated_path(directory: &Path, stem: &str) -> PathBuf {
+    let rotated_path = |time: DateTime<Utc>| {
+        directory.join(format!("{stem}.{}.log", time.format("%Y%m%dT%H%M%S%3f")))
+    };
+    // Rotations within the same millisecond get later names, keeping them in order.
+    let mut time = Utc::now();
+    while rotated_path(time).exists() {
+        time += chrono::Duration::milliseconds(1);
+    }
+    rotated_path(time)
+}
+
+/// Deletes the oldest rotated files of `stem` beyond [ServiceLogOptions::max_files].
+fn prune_rotated_files(options: &ServiceLogOptions, stem: &str) -> std::io::Result<()> {
+    let rotated = rotated_files(&options.directory, stem)?;
+    let excess = rotated.len().saturating_sub(options.max_files);
+    for path in &rotated[..excess] {
+        match std::fs::remove_file(path) {
+            Ok(()) => forget_line_count(path),
+            Err(e) => {
+                tracing::warn!("error" = %e, "path" = ?path, "Failed to remove old service log")
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Range of a service's log history to read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRange {
+    /// `len` bytes from byte `offset`.
+    Bytes { offset: u64, len: u64 },
+    /// `count` lines from line `start` (0-based); the last `count` lines if `start` is unset.
+    Lines { start: Option<u64>, count: u64 },
+}
+
+/// A page of a service's log history.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ServiceLogPage {
+    /// First byte or line of the page.
+    pub start: u64,
+    /// End (exclusive) of the page, in the unit of the requested range.
+    pub end: u64,
+    /// Bytes or lines in the whole history.
+    pub total: u64,
+    /// Text of the page; for a line range, the lines each with a trailing newline.
+    pub content: String,
+}
+
+/// Reads a range of the log history of a service.
+///
+/// Positions count from the start of the oldest file kept, so they shift when old files are
+/// pruned. Pages are capped at 4 MiB or 10 000 lines.
+///
+/// # Arguments
+///
+/// * `directory` - Directory of the service logs.
+/// * `name` - Service name.
+/// * `version` - Service version.
+/// * `range` - Byte or line range to read.
+///
+/// # Returns
+///
+/// `Ok(page)` (empty if the range is past the end or there is no log), or an error if a file
+/// cannot be read.
+pub fn read_service_log(
+    directory: &Path,
+    name: &str,
+    version: &str,
+    range: LogRange,
+) -> anyhow::Result<ServiceLogPage> {
+    let files = log_files(directory, name, version)?;
+    match range {
+        LogRange::Bytes { offset, len } => read_bytes(&files, offset, len.min(MAX_PAGE_BYTES)),
+        LogRange::Lines { start, count } => read_lines(&files, start, count.min(MAX_PAGE_LINES)),
+    }
+}
+
+fn read_bytes(files: &[PathBuf], offset: u64, len: u64) -> anyhow::Result<ServiceLogPage> {
+    let mut sizes = Vec::with_capacity(files.len());
+    for path in files {
+        sizes.push(std::fs::metadata(path)?.len());
+    }
+    let total: u64 = sizes.iter().sum();
+    let start = offset.min(total);
+    let end = (start + len).min(total);
+    let mut data = Vec::with_capacity((end - start) as usize);
+    let mut file_start = 0;
+    for (path, size) in files.iter().zip(sizes) {
+        let file_end = file_start + size;
+        if file_end > start && file_start < end {
+            let from = start.max(file_start) - file_start;
+            let to = end.min(file_end) - file_start;
+            let mut file = File::open(path)?;
+            file.seek(SeekFrom::Start(from))?;
+            file.take(to - from).read_to_end(&mut data)?;
+        }
+        file_start = file_end;
+    }
+    Ok(ServiceLogPage {
+        start,
+        end: start + data.len() as u64,
+        total,
+        content: String::from_utf8_lossy(&data).into_owned(),
+    })
+}
+
+/// Lines between two entries of [LineCount::line_offsets].
+const LINE_INDEX_STEP: u64 = 1024;
+
+/// Lines counted in the first `len` bytes of a log file.
+#[derive(Debug, Clone)]
+struct LineCount {
+    len: u64,
+    created: Option<SystemTime>,
+    modified: Option<SystemTime>,
+    newlines: u64,
+    ends_with_newline: bool,
+    /// Byte offset of line `(i + 1) * LINE_INDEX_STEP` at index `i`, so a page is read from
+    /// near its first line instead of from the start of the file.
+    line_offsets: Vec<u64>,
+}
+
+impl LineCount {
+    /// Returns the line count; a last line without a newline counts too.
+    fn lines(&self) -> u64 {
+        self.newlines + u64::from(self.len > 0 && !self.ends_with_newline)
+    }
+
+    /// Returns the indexed line at or before `line` as (line, byte offset).
+    fn seek_point(&self, line: u64) -> (u64, u64) {
+        let steps = (line / LINE_INDEX_STEP).min(self.line_offsets.len() as u64);
+        match steps {
+            0 => (0, 0),
+            _ => (
+                steps * LINE_INDEX_STEP,
+                self.line_offsets[steps as usize - 1],
+            ),
+        }
+    }
+}
+
+/// Line counts of the log files read so far, so that paging does not rescan the history. Log
+/// files are only appended to, so the count of a file that grew is carried on from where it was
+/// taken; entries are dropped when a file is rotated away or pruned.
+static LINE_COUNTS: Mutex<BTreeMap<PathBuf, LineCount>> = Mutex::new(BTreeMap::new());
+
+/// Drops the cached line count of a log file that was removed, renamed or truncated.
+fn forget_line_count(path: &Path) {
+    LINE_COUNTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(path);
+}
+
+/// Counts the lines of a log file, reading only what was appended since `cached` was taken.
+fn count_lines(path: &Path, cached: Option<LineCount>) -> std::io::Result<LineCount> {
+    let mut file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let len = metadata.len();
+    let created = metadata.created().ok();
+    let modified = metadata.modified().ok();
+    let mut count = match cached {
+        Some(cached) if cached.len == len && cached.modified == modified => return Ok(cached),
+        // A recreated file (after a rotation) has a new creation time.
+        Some(cached) if created.is_some() && cached.created == created && cached.len <= len => {
+            cached
+        }
+        _ => LineCount {
+            len: 0,
+            created,
+            modified,
+            newlines: 0,
+            ends_with_newline: false,
+            line_offsets: Vec::new(),
+        },
+    };
+    count.modified = modified;
+    file.seek(SeekFrom::Start(count.len))?;
+    let mut reader = file.take(len - count.len);
+    let mut chunk = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        for (idx, _) in chunk[..n]
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| **byte == b'\n')
+        {
+            count.newlines += 1;
+            if count.newlines % LINE_INDEX_STEP == 0 {
+                count.line_offsets.push(count.len + idx as u64 + 1);
+            }
+        }
+        count.ends_with_newline = chunk[n - 1] == b'\n';
+        count.len += n as u64;
+    }
+    Ok(count)
+}
+
+/// Returns the line count of each of `files`, updating [LINE_COUNTS].
+fn line_counts(files: &[PathBuf]) -> std::io::Result<Vec<LineCount>> {
+    let mut cache = LINE_COUNTS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ret = Vec::with_capacity(files.len());
+    for path in files {
+        let count = count_lines(path, cache.remove(path))?;
+        cache.insert(path.clone(), count.clone());
+        ret.push(count);
+    }
+    Ok(ret)
+}
+
+fn read_lines(files: &[PathBuf], start: Option<u64>, count: u64) -> anyhow::Result<ServiceLogPage> {
+    let counts = line_counts(files)?;
+    let total: u64 = counts.iter().map(LineCount::lines).sum();
+    let start = start.unwrap_or(total.saturating_sub(count)).min(total);
+    let end = start.saturating_add(count).min(total);
+    let mut data = Vec::new();
+    let mut buf = Vec::new();
+    let mut file_start = 0;
+    for (path, count) in files.iter().zip(counts) {
+        let file_end = file_start + count.lines();
+        if file_end > start && file_start < end {
+            let skip = start.saturating_sub(file_start);
+            let take = end.min(file_end) - file_start - skip;
+            let (indexed_line, offset) = count.seek_point(skip);
+            let mut file = File::open(path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut reader = BufReader::new(file);
+            for _ in indexed_line..skip {
+                reader.skip_until(b'\n')?;
+            }
+            for _ in 0..take {
+                buf.clear();
+                if reader.read_until(b'\n', &mut buf)? == 0 {
+                    break;
+                }
+                data.extend_from_slice(&buf);
+                if !buf.ends_with(b"\n") {
+                    data.push(b'\n');
+                }
+            }
+        }
+        file_start = file_end;
+    }
+    Ok(ServiceLogPage {
+        start,
+        end,
+        total,
+        content: String::from_utf8_lossy(&data).into_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an empty directory for one test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("spindle-service-log-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn options(directory: &Path, rotation: LogRotation, max_files: usize) -> ServiceLogOptions {
+        ServiceLogOptions {
+            directory: directory.to_path_buf(),
+            rotation,
+            max_files,
+        }
+    }
+
+    /// 2025-06-02T10:00:00Z in milliseconds.
+    const T0: u64 = 1_748_858_400_000;
+    const DAY: u64 = 24 * 60 * 60 * 1000;
+
+    fn lines(directory: &Path, start: Option<u64>, count: u64) -> ServiceLogPage {
+        read_service_log(directory, "api", "1", LogRange::Lines { start, count }).unwrap()
+    }
+
+    #[test]
+    fn line_format() {
+        let dir = test_dir("format");
+        let mut writer =
+            ServiceLogWriter::open(&options(&dir, LogRotation::Daily, 7), "api", "1").unwrap();
+        writer
+            .write_line(OutputStream::Stdout, "hello", T0)
+            .unwrap();
+        writer
+            .write_line(OutputStream::Stderr, "oops", T0 + 1)
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(log_path(&dir, "api", "1")).unwrap(),
+            "2025-06-02T10:00:00.000Z stdout hello\n2025-06-02T10:00:00.001Z stderr oops\n"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn size_rotation_prunes_old_files() {
+        let dir = test_dir("size");
+        // Each entry is 37 bytes, so two fit in a file.
+        let rotation = LogRotation::Size { max_bytes: 80 };
+        let mut writer = ServiceLogWriter::open(&options(&dir, rotation, 2), "api", "1").unwrap();
+        for idx in 0..8 {
+            writer
+                .write_line(OutputStream::Stdout, &format!("l{idx:03}"), T0)
+                .unwrap();
+        }
+        let files = log_files(&dir, "api", "1").unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[2], log_path(&dir, "api", "1"));
+        let page = lines(&dir, Some(0), 100);
+        assert_eq!(page.total, 6);
+        let kept: Vec<&str> = page
+            .content
+            .lines()
+            .map(|line| line.rsplit(' ').next().unwrap())
+            .collect();
+        assert_eq!(kept, ["l002", "l003", "l004", "l005", "l006", "l007"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn daily_rotation() {
+        let dir = test_dir("daily");
+        let mut writer =
+            ServiceLogWriter::open(&options(&dir, LogRotation::Daily, 7), "api", "1").unwrap();
+        writer.write_line(OutputStream::Stdout, "day1", T0).unwrap();
+        writer
+            .write_line(OutputStream::Stdout, "day1 again", T0 + 1)
+            .unwrap();
+        writer
+            .write_line(OutputStream::Stdout, "day2", T0 + DAY)
+            .unwrap();
+        let files = log_files(&dir, "api", "1").unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(&files[0]).unwrap().lines().count(),
+            2
+        );
+        assert!(
+            std::fs::read_to_string(&files[1])
+                .unwrap()
+                .ends_with(" stdout day2\n")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn line_pages_across_files() {
+        let dir = test_dir("lines");
+        std::fs::write(dir.join("api~1.20250601T000000000.log"), "l0\nl1\nl2").unwrap();
+        std::fs::write(log_path(&dir, "api", "1"), "l3\nl4\n").unwrap();
+        let page = lines(&dir, None, 2);
+        assert_eq!((page.start, page.end, page.total), (3, 5, 5));
+        assert_eq!(page.content, "l3\nl4\n");
+        // A last line without a newline is its own line and gets one.
+        let page = lines(&dir, Some(1), 3);
+        assert_eq!((page.start, page.end), (1, 4));
+        assert_eq!(page.content, "l1\nl2\nl3\n");
+        let page = lines(&dir, Some(9), 3);
+        assert_eq!((page.start, page.end, page.content.as_str()), (5, 5, ""));
+        let page = lines(&dir, None, 100);
+        assert_eq!((page.start, page.end), (0, 5));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn line_counts_follow_appends_and_rotation() {
+        let dir = test_dir("counts");
+        let current = log_path(&dir, "api", "1");
+        std::fs::write(&current, "a\nb\n").unwrap();
+        assert_eq!(lines(&dir, None, 1).content, "b\n");
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&current)
+            .unwrap()
+            .write_all(b"c\nd")
+            .unwrap();
+        let page = lines(&dir, None, 2);
+        assert_eq!((page.total, page.content.as_str()), (4, "c\nd\n"));
+        std::fs::rename(&current, dir.join("api~1.20250601T000000000.log")).unwrap();
+        std::fs::write(&current, "e\n").unwrap();
+        let page = lines(&dir, None, 2);
+        assert_eq!((page.total, page.content.as_str()), (5, "d\ne\n"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn line_pages_start_from_the_line_index() {
+        let dir = test_dir("index");
+        let content: String = (0..3000).map(|idx| format!("l{idx}\n")).collect();
+        std::fs::write(log_path(&dir, "api", "1"), content).unwrap();
+        for start in [0, 1023, 1024, 2048, 2500, 2999] {
+            let page = lines(&dir, Some(start), 2);
+            let expected: String = (start..(start + 2).min(3000))
+                .map(|idx| format!("l{idx}\n"))
+                .collect();
+            assert_eq!(page.content, expected, "{start}");
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn pruned_files_leave_the_line_cache() {
+        let dir = test_dir("evict");
+        let rotation = LogRotation::Size { max_bytes: 80 };
+        let mut writer = ServiceLogWriter::open(&options(&dir, rotation, 1), "api", "1").unwrap();
+        for idx in 0..8 {
+            writer
+                .write_line(OutputStream::Stdout, &format!("l{idx:03}"), T0)
+                .unwrap();
+            lines(&dir, Some(0), 100);
+        }
+        let cache = LINE_COUNTS.lock().unwrap();
+        let cached: Vec<&PathBuf> = cache.keys().filter(|path| path.starts_with(&dir)).collect();
+        assert!(cached.iter().all(|path| path.exists()), "{cached:?}");
+        drop(cache);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn detached_log_rotation_copies_and_truncates() {
+        let dir = test_dir("detached");
+        let rotation = LogRotation::Size { max_bytes: 4 };
+        let mut rotation = DetachedLogRotation::new(&options(&dir, rotation, 1), "api", "1");
+        let mut file = File::options()
+            .create(true)
+            .append(true)
+            .open(rotation.path())
+            .unwrap();
+        file.write_all(b"abc\n").unwrap();
+        rotation.rotate_if_due().unwrap();
+        assert_eq!(log_files(&dir, "api", "1").unwrap().len(), 1);
+        file.write_all(b"defgh\n").unwrap();
+        rotation.rotate_if_due().unwrap();
+        // The process keeps appending at the start of the truncated file.
+        file.write_all(b"ij\n").unwrap();
+        let files = log_files(&dir, "api", "1").unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(std::fs::read_to_string(&files[0]).unwrap(), "abc\ndefgh\n");
+        assert_eq!(std::fs::read_to_string(&files[1]).unwrap(), "ij\n");
+        assert_eq!(lines(&dir, None, 10).content, "abc\ndefgh\nij\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn byte_ranges_across_files() {
+        let dir = test_dir("bytes");
+        std::fs::write(dir.join("api~1.20250601T000000000.log"), "0123").unwrap();
+        std::fs::write(log_path(&dir, "api", "1"), "4567").unwrap();
+        let page =
+            read_service_log(&dir, "api", "1", LogRange::Bytes { offset: 2, len: 4 }).unwrap();
+        assert_eq!((page.start, page.end, page.total), (2, 6, 8));
+        assert_eq!(page.content, "2345");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_log() {
+        let dir = test_dir("missing");
+        assert_eq!(lines(&dir, None, 10), ServiceLogPage::default());
+        assert_eq!(
+            lines(&dir.join("nonexistent"), None, 10),
+            ServiceLogPage::default()
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
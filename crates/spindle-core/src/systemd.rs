@@ -0,0 +1,259 @@
+//! Rendering of service groups as systemd user units.
+//!
+//! Each service becomes a `.service` unit and the group a `.target` that wants all of them.
+//! Units are only written to a directory; installing and enabling them is left to the user.
+
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use crate::service::{ServiceManager, ServiceMeta, static_meta};
+
+/// Returns the unit name of a service without the `.service` suffix.
+///
+/// # Arguments
+///
+/// * `name` - Service name.
+/// * `version` - Service version.
+///
+/// # Returns
+///
+/// `spindle-<name>-<version>` with characters not allowed in unit names escaped as `\xNN`.
+pub fn service_unit_stem(name: &str, version: &str) -> String {
+    escape_unit_name(&format!("spindle-{name}-{version}"))
+}
+
+/// Renders the `.service` unit of a service.
+///
+/// The umask maps to `UMask=` and resource limits to the matching `Limit*=`, `MemoryMax=`,
+/// `CPUQuota=` and `TasksMax=` settings; user and group are left out, as user units cannot
+/// switch users. Hooks map to `ExecStartPre=`, `ExecStartPost=`, `ExecStop=` and
+/// `ExecStopPost=` run with `/bin/sh -c`; all but the pre-start hook may fail, as in Spindle.
+/// Hook timeouts have no per-command equivalent and are left out.
+///
+/// # Arguments
+///
+/// * `meta` - Service to render.
+/// * `deps` - (name, version) of its dependencies; each maps to `Requires=` and `After=`.
+/// * `target` - Full name of the group target (e.g. `spindle-web.target`).
+///
+/// # Returns
+///
+/// Unit file content.
+pub fn render_service_unit(meta: &ServiceMeta, deps: &[(String, String)], target: &str) -> String {
+    let mut ret = String::new();
+    let _ = writeln!(ret, "[Unit]");
+    let _ = writeln!(
+        ret,
+        "Description=Spindle service {}:v{}",
+        meta.name, meta.version
+    );
+    let _ = writeln!(ret, "PartOf={target}");
+    for (dep_name, dep_version) in deps {
+        let dep_unit = format!("{}.service", service_unit_stem(dep_name, dep_version));
+        let _ = writeln!(ret, "Requires={dep_unit}");
+        let _ = writeln!(ret, "After={dep_unit}");
+    }
+    let _ = writeln!(ret);
+    let _ = writeln!(ret, "[Service]");
+    let _ = writeln!(ret, "Type=simple");
+    if let Some(umask) = meta.identity.umask {
+        let _ = writeln!(ret, "UMask={umask:04o}");
+    }
+    let limits = &meta.limits;
+    let settings = [
+        ("LimitNOFILE", limits.open_files),
+        ("LimitCORE", limits.core_size),
+        ("LimitAS", limits.address_space),
+        ("LimitCPU", limits.cpu_seconds),
+        ("MemoryMax", limits.memory_max),
+        ("TasksMax", limits.pids_max),
+    ];
+    for (setting, value) in settings {
+        if let Some(value) = value {
+            let _ = writeln!(ret, "{setting}={value}");
+        }
+    }
+    if let Some(cpu_max) = limits.cpu_max {
+        let _ = writeln!(ret, "CPUQuota={}%", (cpu_max * 100.0).round() as u64);
+    }
+    if let Some(workspace) = &meta.workspace {
+        let _ = writeln!(
+            ret,
+            "WorkingDirectory={}",
+            escape_specifiers(&workspace.to_string_lossy())
+        );
+    }
+    for (key, value) in &meta.env {
+        let _ = writeln!(
+            ret,
+            "Environment={}",
+            quote_unit_word(&escape_specifiers(&format!("{key}={value}")))
+        );
+    }
+    let program = meta.program.to_string_lossy();
+    let exec_start: Vec<String> = std::iter::once(program.as_ref())
+        .chain(meta.args.iter().map(|arg| &**arg))
+        .map(quote_exec_word)
+        .collect();
+    let _ = writeln!(ret, "ExecStart={}", exec_start.join(" "));
+    let hook_settings = [
+        ("ExecStartPre=", &meta.hooks.pre_start),
+        ("ExecStartPost=-", &meta.hooks.post_start),
+        ("ExecStop=-", &meta.hooks.pre_stop),
+        ("ExecStopPost=-", &meta.hooks.post_stop),
+    ];
+    for (setting, hook) in hook_settings {
+        if let Some(hook) = hook {
+            let _ = writeln!(
+                ret,
+                "{setting}/bin/sh -c {}",
+                quote_exec_word(&hook.command)
+            );
+        }
+    }
+    let _ = writeln!(ret);
+    let _ = writeln!(ret, "[Install]");
+    let _ = writeln!(ret, "WantedBy={target}");
+    ret
+}
+
+/// Renders the `.target` unit of a group.
+///
+/// # Arguments
+///
+/// * `description` - Human-readable group name.
+/// * `service_units` - Full names of the group's service units.
+///
+/// # Returns
+///
+/// Unit file content.
+pub fn render_group_target(description: &str, service_units: &[String]) -> String {
+    let mut ret = String::new();
+    let _ = writeln!(ret, "[Unit]");
+    let _ = writeln!(ret, "Description=Spindle service group {description}");
+    for unit in service_units {
+        let _ = writeln!(ret, "Wants={unit}");
+    }
+    let _ = writeln!(ret);
+    let _ = writeln!(ret, "[Install]");
+    let _ = writeln!(ret, "WantedBy=default.target");
+    ret
+}
+
+/// Writes the units of a group into `out_dir`: one `.service` per service and one `.target`.
+///
+/// # Arguments
+///
+/// * `service_manager` - Manager holding the group.
+/// * `group_idx` - Index of the group.
+/// * `group_name` - Group name used for the target (`spindle-<group_name>.target`).
+/// * `out_dir` - Output directory; created if missing, existing files with the same names are overwritten.
+///
+/// # Returns
+///
+/// `Ok(paths)` of the written files (target last), or an error if the group is invalid, a service
+/// uses a feature units cannot express (replicas, ports picked at spawn time) or on I/O failure.
+/// Nothing is written when a service cannot be exported.
+pub fn export_group_units(
+    service_manager: &ServiceManager,
+    group_idx: usize,
+    group_name: &str,
+    out_dir: &Path,
+) -> anyhow::Result<Vec<PathBuf>> {
+    if group_idx >= service_manager.group_num() {
+        anyhow::bail!("Invalid group index: {}", group_idx);
+    }
+    let mut services = Vec::new();
+    for (meta, deps) in service_manager.group_service_metas(group_idx) {
+        services.push((exportable_meta(&meta)?, deps));
+    }
+    std::fs::create_dir_all(out_dir)?;
+    let target = format!(
+        "{}.target",
+        escape_unit_name(&format!("spindle-{group_name}"))
+    );
+    let mut ret = Vec::new();
+    let mut service_units = Vec::new();
+    for (meta, deps) in services {
+        let unit = format!("{}.service", service_unit_stem(&meta.name, &meta.version));
+        let path = out_dir.join(&unit);
+        std::fs::write(&path, render_service_unit(&meta, &deps, &target))?;
+        ret.push(path);
+        service_units.push(unit);
+    }
+    let path = out_dir.join(&target);
+    std::fs::write(&path, render_group_target(group_name, &service_units))?;
+    ret.push(path);
+    Ok(ret)
+}
+
+/// Returns `meta` with its templates expanded, or an error naming what a unit cannot express:
+/// replicas, and ports, which Spindle picks or checks and passes on at spawn time.
+fn exportable_meta(meta: &ServiceMeta) -> anyhow::Result<ServiceMeta> {
+    let service = format!("Service {}:v{}", meta.name, meta.version);
+    if let Some(replicas) = meta.replicas {
+        anyhow::bail!("{service} cannot be exported: it runs as {replicas} replicas");
+    }
+    if let Some(port) = meta.ports.keys().next() {
+        anyhow::bail!("{service} cannot be exported: it has named ports (e.g. {port:?})");
+    }
+    static_meta(meta).map_err(|names| {
+        anyhow::anyhow!(
+            "{service} cannot be exported: it uses values only known at spawn time: {}",
+            names.join(", ")
+        )
+    })
+}
+
+/// Escapes characters not allowed in unit names, as `systemd-escape` does.
+fn escape_unit_name(name: &str) -> String {
+    let mut ret = String::with_capacity(name.len());
+    for (idx, byte) in name.bytes().enumerate() {
+        let allowed = byte.is_ascii_alphanumeric()
+            || matches!(byte, b':' | b'_' | b'-')
+            || (byte == b'.' && idx > 0);
+        if allowed {
+            ret.push(byte as char);
+        } else {
+            let _ = write!(ret, "\\x{byte:02x}");
+        }
+    }
+    ret
+}
+
+/// Escapes `%` specifiers in a unit file value.
+fn escape_specifiers(value: &str) -> String {
+    value.replace('%', "%%")
+}
+
+/// Quotes one `ExecStart=` word; `$` is escaped since systemd expands variables there.
+fn quote_exec_word(word: &str) -> String {
+    quote_unit_word(&escape_specifiers(word).replace('$', "$$"))
+}
+
+/// Double-quotes an already escaped unit file word if it contains whitespace, quotes or `;`.
+fn quote_unit_word(escaped: &str) -> String {
+    let needs_quotes = escaped.is_empty()
+        || escaped
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | ';'));
+    if !needs_quotes {
+        return escaped.to_string();
+    }
+    let mut ret = String::with_capacity(escaped.len() + 2);
+    ret.push('"');
+    for c in escaped.chars() {
+        match c {
+            '"' | '\\' => {
+                ret.push('\\');
+                ret.push(c);
+            }
+            '\n' => ret.push_str("\\n"),
+            _ => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
@@ -0,0 +1,348 @@
+//! Restarting services when their files change.
+//!
+//! A service's watch section lists glob patterns of files, relative to its workspace, and
+//! patterns of files to ignore. Once the changes settle for the debounce time, the service is
+//! restarted. The program binary is always watched as well, so rebuilding it restarts the
+//! service; a binary that is replaced by a rename (as most build tools do) is picked up too.
+
+use std::{
+    path::{Component, Path, PathBuf},
+    time::Duration,
+};
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use notify::{EventKind, RecursiveMode};
+use serde::{Deserialize, Serialize};
+
+/// Time the watched files must stay unchanged before a restart, when no debounce is set.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Files watched for changes and how changes restart the service.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Glob patterns of the watched files, relative to the workspace unless absolute, e.g.
+    /// `src/**/*.rs`; a directory is watched with everything under it.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Glob patterns of files whose changes are ignored, e.g. `**/*.log`.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Milliseconds without changes before restarting; defaults to [DEFAULT_DEBOUNCE].
+    #[serde(default)]
+    pub debounce_ms: Option<u64>,
+    /// Stop and relaunch the running dependents with the service; otherwise they keep running
+    /// while only the service restarts.
+    #[serde(default)]
+    pub restart_dependents: bool,
+}
+
+impl WatchConfig {
+    /// Returns the time the watched files must stay unchanged before a restart.
+    pub fn debounce(&self) -> Duration {
+        self.debounce_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_DEBOUNCE)
+    }
+
+    /// Applies `f` to every path and ignore pattern.
+    pub(crate) fn map_patterns(&self, mut f: impl FnMut(&str) -> String) -> Self {
+        let mut ret = self.clone();
+        for pattern in ret.paths.iter_mut().chain(ret.ignore.iter_mut()) {
+            *pattern = f(pattern);
+        }
+        ret
+    }
+
+    /// Checks the patterns and the debounce.
+    ///
+    /// # Arguments
+    ///
+    /// * `workspace` - Workspace of the service, needed by relative patterns.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if valid, or `Err(message)` naming the invalid setting.
+    pub fn validate(&self, workspace: Option<&Path>) -> Result<(), String> {
+        for pattern in self.paths.iter().chain(&self.ignore) {
+            if pattern.trim().is_empty() {
+                return Err("Invalid watch pattern: empty pattern".to_string());
+            }
+            if let Err(e) = Glob::new(pattern) {
+                return Err(format!("Invalid watch pattern {pattern:?}: {e}"));
+            }
+        }
+        if workspace.is_none() && self.paths.iter().any(|path| Path::new(path).is_relative()) {
+            return Err("Relative watch paths require a workspace".to_string());
+        }
+        if self.debounce_ms == Some(0) {
+            return Err("Invalid watch debounce: must be at least 1 millisecond".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if a path component contains glob syntax.
+fn has_glob_syntax(component: &str) -> bool {
+    component.contains(['*', '?', '[', '{'])
+}
+
+/// Returns the longest leading directory of `pattern` without glob syntax.
+fn glob_base(pattern: &Path) -> PathBuf {
+    let mut ret = PathBuf::new();
+    for component in pattern.components() {
+        if let Component::Normal(part) = component
+            && has_glob_syntax(&part.to_string_lossy())
+        {
+            break;
+        }
+        ret.push(component);
+    }
+    ret
+}
+
+/// Compiles `patterns`, joined to `root` unless absolute; `*` does not cross directories.
+fn build_glob_set(root: &Path, patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = root.join(pattern);
+        let glob = GlobBuilder::new(&pattern.to_string_lossy())
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("Invalid watch pattern {pattern:?}: {e}"))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Resolves the program of a service to the file that is run, searching `PATH` for a bare name.
+fn resolve_program(
+    program: &Path,
+    workspace: Option<&Path>,
+    path_var: Option<&str>,
+) -> Option<PathBuf> {
+    if program.components().count() > 1 || program.is_absolute() {
+        let ret = match workspace {
+            Some(workspace) => workspace.join(program),
+            None => program.to_path_buf(),
+        };
+        return Some(ret);
+    }
+    let path_var = path_var
+        .map(Into::into)
+        .or_else(|| std::env::var_os("PATH"))?;
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// A [WatchConfig] resolved against the workspace and program of a service.
+#[derive(Debug, Clone)]
+pub(crate) struct WatchSpec {
+    include: GlobSet,
+    ignore: GlobSet,
+    /// Directories to watch, with whether they are watched recursively.
+    roots: Vec<(PathBuf, RecursiveMode)>,
+    /// Program binary, watched through its directory.
+    program: Option<PathBuf>,
+    pub(crate) debounce: Duration,
+    pub(crate) restart_dependents: bool,
+}
+
+impl WatchSpec {
+    /// Resolves `watch` for a service with `workspace` and `program`.
+    ///
+    /// # Arguments
+    ///
+    /// * `watch` - Validated watch config.
+    /// * `workspace` - Workspace the patterns are relative to.
+    /// * `program` - Program of the service, as configured.
+    /// * `path_var` - `PATH` of the service, used to find a bare program name.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(spec)` on success, or `Err(message)` if a pattern does not compile.
+    pub(crate) fn new(
+        watch: &WatchConfig,
+        workspace: Option<&Path>,
+        program: &Path,
+        path_var: Option<&str>,
+    ) -> Result<Self, String> {
+        let root = workspace.unwrap_or(Path::new(""));
+        let mut paths = Vec::new();
+        let mut roots = Vec::new();
+        for pattern in &watch.paths {
+            let full = root.join(pattern);
+            if !has_glob_syntax(pattern) && full.is_dir() {
+                paths.push(format!("{pattern}/**"));
+                roots.push((full, RecursiveMode::Recursive));
+                continue;
+            }
+            paths.push(pattern.clone());
+            let base = glob_base(&full);
+            if base == full {
+                // A single file is watched through its directory, so it survives being replaced.
+                if let Some(parent) = full.parent() {
+                    roots.push((parent.to_path_buf(), RecursiveMode::NonRecursive));
+                }
+            } else {
+                roots.push((base, RecursiveMode::Recursive));
+            }
+        }
+        let program = resolve_program(program, workspace, path_var);
+        if let Some(parent) = program.as_deref().and_then(Path::parent) {
+            roots.push((parent.to_path_buf(), RecursiveMode::NonRecursive));
+        }
+        roots.sort();
+        roots.dedup();
+        Ok(Self {
+            include: build_glob_set(root, &paths)?,
+            ignore: build_glob_set(root, &watch.ignore)?,
+            roots,
+            program,
+            debounce: watch.debounce(),
+            restart_dependents: watch.restart_dependents,
+        })
+    }
+
+    /// Returns the directories to watch.
+    pub(crate) fn roots(&self) -> &[(PathBuf, RecursiveMode)] {
+        &self.roots
+    }
+
+    /// Returns the program binary, if it was found.
+    pub(crate) fn program(&self) -> Option<&Path> {
+        self.program.as_deref()
+    }
+
+    /// Returns the first path of `event` that should restart the service, if any.
+    pub(crate) fn relevant_path<'a>(&self, event: &'a notify::Event) -> Option<&'a Path> {
+        if !matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) {
+            return None;
+        }
+        event
+            .paths
+            .iter()
+            .map(PathBuf::as_path)
+            .find(|path| self.program.as_deref() == Some(*path) || self.is_watched(path))
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        self.include.is_match(path) && !self.ignore.is_match(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+
+    fn event(kind: EventKind, path: &Path) -> notify::Event {
+        notify::Event::new(kind).add_path(path.to_path_buf())
+    }
+
+    #[test]
+    fn glob_bases() {
+        assert_eq!(
+            glob_base(Path::new("/ws/src/**/*.rs")),
+            Path::new("/ws/src")
+        );
+        assert_eq!(
+            glob_base(Path::new("/ws/config/app.toml")),
+            Path::new("/ws/config/app.toml")
+        );
+        assert_eq!(glob_base(Path::new("/ws/{a,b}/x")), Path::new("/ws"));
+    }
+
+    #[test]
+    fn validation() {
+        let watch = WatchConfig {
+            paths: vec!["src/**/*.rs".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(watch.validate(Some(Path::new("/ws"))), Ok(()));
+        assert!(watch.validate(None).is_err());
+        assert_eq!(watch.debounce(), DEFAULT_DEBOUNCE);
+        for bad in [
+            WatchConfig {
+                paths: vec!["src/[".to_string()],
+                ..Default::default()
+            },
+            WatchConfig {
+                ignore: vec![" ".to_string()],
+                ..Default::default()
+            },
+            WatchConfig {
+                debounce_ms: Some(0),
+                ..Default::default()
+            },
+        ] {
+            assert!(bad.validate(Some(Path::new("/ws"))).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn matching() {
+        let workspace = std::env::temp_dir().join(format!("spindle-watch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&workspace);
+        std::fs::create_dir_all(workspace.join("src/nested")).unwrap();
+        let watch = WatchConfig {
+            paths: vec![
+                "src".to_string(),
+                "config/*.toml".to_string(),
+                "Cargo.toml".to_string(),
+            ],
+            ignore: vec!["**/*.log".to_string()],
+            ..Default::default()
+        };
+        let spec = WatchSpec::new(&watch, Some(&workspace), Path::new("bin/app"), None).unwrap();
+        let program = workspace.join("bin/app");
+        assert_eq!(spec.program(), Some(program.as_path()));
+        assert_eq!(
+            spec.roots(),
+            [
+                (workspace.clone(), RecursiveMode::NonRecursive),
+                (workspace.join("bin"), RecursiveMode::NonRecursive),
+                (workspace.join("config"), RecursiveMode::Recursive),
+                (workspace.join("src"), RecursiveMode::Recursive),
+            ]
+        );
+
+        let modify = EventKind::Modify(ModifyKind::Any);
+        for path in [
+            "src/main.rs",
+            "src/nested/mod.rs",
+            "config/app.toml",
+            "Cargo.toml",
+            "bin/app",
+        ] {
+            let path = workspace.join(path);
+            assert_eq!(
+                spec.relevant_path(&event(modify, &path)),
+                Some(path.as_path())
+            );
+        }
+        // `*` does not cross directories, ignored files and unlisted files do not count.
+        for path in [
+            "config/old/app.toml",
+            "src/debug.log",
+            "README.md",
+            "bin/other",
+        ] {
+            let path = workspace.join(path);
+            assert_eq!(spec.relevant_path(&event(modify, &path)), None, "{path:?}");
+        }
+        let main = workspace.join("src/main.rs");
+        assert_eq!(
+            spec.relevant_path(&event(EventKind::Create(CreateKind::File), &main)),
+            Some(main.as_path())
+        );
+        assert_eq!(
+            spec.relevant_path(&event(EventKind::Access(AccessKind::Any), &main)),
+            None
+        );
+        std::fs::remove_dir_all(&workspace).unwrap();
+    }
+}